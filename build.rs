@@ -19,7 +19,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     config.out_dir(&out_dir)
         .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
         .file_descriptor_set_path(out_dir.join("descriptor.bin"))
-        .compile_protos(&[proto_path.clone()], &[proto_dir])?;
+        .compile_protos(&[proto_path.clone()], &[&proto_dir])?;
 
     // 重命名输出文件
     std::fs::rename(
@@ -34,8 +34,77 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         pub use zergpool::{Task, Response, response};\n"
     )?;
 
+    // 线协议版本写入编译期环境变量(src/version.rs中读取)
+    let descriptor = std::fs::read(out_dir.join("descriptor.bin"))?;
+    let version = protocol_version(&descriptor, "task.proto")
+        .ok_or("proto/task.proto缺少zerg.protocol_version选项")?;
+    println!("cargo:rustc-env=ZERG_PROTOCOL_VERSION={}", version);
+
     // 重新编译当proto文件变化时
     println!("cargo:rerun-if-changed={}", proto_path.display());
+    println!("cargo:rerun-if-changed={}", proto_dir.join("version.proto").display());
 
     Ok(())
+}
+
+/// FileDescriptorProto.options字段号
+const FILE_OPTIONS_FIELD: u64 = 8;
+/// proto/version.proto中`protocol_version`扩展的字段号
+const PROTOCOL_VERSION_FIELD: u64 = 50000;
+
+/// 从descriptor中读取指定文件的`zerg.protocol_version`选项
+///
+/// prost_types解码时会丢弃扩展字段，这里直接按protobuf编码逐层查找：
+/// FileDescriptorSet.file(1) -> FileDescriptorProto.name(1)/options(8) -> FileOptions扩展(50000)
+fn protocol_version(descriptor: &[u8], file_name: &str) -> Option<String> {
+    let file = length_delimited(descriptor)
+        .into_iter()
+        .filter(|(field, _)| *field == 1)
+        .map(|(_, file)| length_delimited(file))
+        .find(|fields| fields.iter().any(|(field, name)| *field == 1 && *name == file_name.as_bytes()))?;
+    let (_, options) = file.into_iter().find(|(field, _)| *field == FILE_OPTIONS_FIELD)?;
+    let (_, version) = length_delimited(options).into_iter().find(|(field, _)| *field == PROTOCOL_VERSION_FIELD)?;
+    String::from_utf8(version.to_vec()).ok()
+}
+
+/// 解析消息中所有长度前缀类型的字段(字段号, 内容)，跳过其他类型
+fn length_delimited(mut buf: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut fields = Vec::new();
+    while let Some(key) = read_varint(&mut buf) {
+        let len = match key & 0x7 {
+            0 => match read_varint(&mut buf) {
+                Some(_) => continue,
+                None => break,
+            },
+            1 => 8,
+            2 => match read_varint(&mut buf) {
+                Some(len) => len as usize,
+                None => break,
+            },
+            5 => 4,
+            _ => break,
+        };
+        if len > buf.len() {
+            break;
+        }
+        let (value, rest) = buf.split_at(len);
+        if key & 0x7 == 2 {
+            fields.push((key >> 3, value));
+        }
+        buf = rest;
+    }
+    fields
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first()?;
+        *buf = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
 }
\ No newline at end of file
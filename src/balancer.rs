@@ -3,12 +3,20 @@
 //! 实现基于CPU(60%)、内存(30%)、网络(10%)的加权计算
 //! 并集成EMA平滑处理
 
+use std::collections::HashMap;
+use std::time::Instant;
 use moving_averages::ema::Ema;
 use rand::prelude::*;
 use thiserror::Error;
-use tokio::time::{Duration, sleep};
+use tokio::time::Duration;
 use metrics::{counter, gauge};
-use crate::Process;
+use crate::{Process, ProcessId};
+
+/// 预热期内的最小有效权重系数(避免新节点完全无流量)
+const MIN_WARMUP_FACTOR: f64 = 0.1;
+
+/// 满载节点保留的最小剩余容量(避免权重为0)
+const MIN_HEADROOM: f64 = 0.01;
 
 /// 权重计算器
 #[derive(Debug)]
@@ -61,34 +69,131 @@ impl WeightCalculator {
     }
 }
 
-/// 备用节点池配置
+/// 预热曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WarmupCurve {
+    /// 线性增长
+    #[default]
+    Linear,
+    /// 二次曲线(前期增长慢，后期加速)
+    Quadratic,
+}
+
+impl WarmupCurve {
+    /// 根据预热进度(0.0-1.0)计算权重系数
+    pub fn apply(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self {
+            WarmupCurve::Linear => progress,
+            WarmupCurve::Quadratic => progress * progress,
+        }
+    }
+}
+
+/// 节点预热跟踪器
+///
+/// 记录每个新注册/新晋升节点的预热起点，按曲线计算其有效权重系数
 #[derive(Debug)]
+pub struct WarmupTracker {
+    started: HashMap<ProcessId, Instant>,
+    duration: Duration,
+    curve: WarmupCurve,
+}
+
+impl WarmupTracker {
+    /// 创建新的预热跟踪器
+    pub fn new(duration: Duration, curve: WarmupCurve) -> Self {
+        Self {
+            started: HashMap::new(),
+            duration,
+            curve,
+        }
+    }
+
+    /// 开始节点预热(重复调用会重新计时)
+    pub fn begin(&mut self, id: &ProcessId) {
+        self.begin_at(id, Instant::now());
+    }
+
+    /// 以指定时间点开始节点预热
+    pub fn begin_at(&mut self, id: &ProcessId, at: Instant) {
+        if self.duration.is_zero() {
+            return;
+        }
+        self.started.insert(id.clone(), at);
+    }
+
+    /// 移除节点的预热记录
+    pub fn remove(&mut self, id: &ProcessId) {
+        self.started.remove(id);
+    }
+
+    /// 获取节点当前权重系数(0.1-1.0)，未在预热中的节点返回1.0
+    pub fn factor(&self, id: &ProcessId) -> f64 {
+        self.factor_at(id, Instant::now())
+    }
+
+    /// 获取节点在指定时间点的权重系数
+    pub fn factor_at(&self, id: &ProcessId, now: Instant) -> f64 {
+        match self.started.get(id) {
+            Some(start) => {
+                let elapsed = now.saturating_duration_since(*start);
+                let progress = elapsed.as_secs_f64() / self.duration.as_secs_f64();
+                self.curve.apply(progress).max(MIN_WARMUP_FACTOR)
+            }
+            None => 1.0,
+        }
+    }
+
+    /// 节点是否仍处于预热期
+    pub fn is_warming(&self, id: &ProcessId) -> bool {
+        self.started
+            .get(id)
+            .is_some_and(|start| start.elapsed() < self.duration)
+    }
+
+    /// 清理已完成预热的节点记录
+    pub fn prune(&mut self) {
+        let duration = self.duration;
+        self.started.retain(|_, start| start.elapsed() < duration);
+    }
+
+    /// 预热时长
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// 修改预热时长(已在预热中的节点按新时长继续计算)
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+        if duration.is_zero() {
+            self.started.clear();
+        }
+    }
+}
+
+/// 备用节点池配置
+#[derive(Debug, Default)]
 pub struct BackupPool {
     nodes: Vec<Process>,
-    warmup_duration: Duration,
 }
 
 impl BackupPool {
     /// 创建新的备用节点池
-    pub fn new(warmup_duration: Duration) -> Self {
-        Self {
-            nodes: Vec::new(),
-            warmup_duration,
-        }
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
     }
 
     /// 添加节点到备用池
     pub fn add_node(&mut self, node: Process) {
         self.nodes.push(node);
-        counter!("zergpool.backup_nodes").increment(1);
+        gauge!("zergpool.backup_nodes").set(self.nodes.len() as f64);
     }
 
     /// 从备用池取出节点
     pub fn take_node(&mut self) -> Option<Process> {
         let node = self.nodes.pop();
-        if node.is_some() {
-            counter!("zergpool.backup_nodes").increment(0); // 重置为0
-        }
+        gauge!("zergpool.backup_nodes").set(self.nodes.len() as f64);
         node
     }
 
@@ -128,6 +233,8 @@ pub struct ZergRushSelector {
     check_interval: Duration,
     /// 备用进程池
     backup_pool: BackupPool,
+    /// 节点预热跟踪
+    warmup: WarmupTracker,
 }
 
 impl ZergRushSelector {
@@ -145,8 +252,54 @@ impl ZergRushSelector {
             total_duration: 0,
             max_load_threshold,
             check_interval,
-            backup_pool: BackupPool::new(warmup_duration),
+            backup_pool: BackupPool::new(),
+            warmup: WarmupTracker::new(warmup_duration, WarmupCurve::default()),
+        }
+    }
+
+    /// 设置预热曲线
+    pub fn with_warmup_curve(mut self, curve: WarmupCurve) -> Self {
+        self.warmup.curve = curve;
+        self
+    }
+
+    /// 修改最大负载阈值
+    pub fn set_max_load_threshold(&mut self, threshold: f64) {
+        self.max_load_threshold = threshold;
+    }
+
+    /// 修改节点预热时长
+    pub fn set_warmup_duration(&mut self, duration: Duration) {
+        self.warmup.set_duration(duration);
+    }
+
+    /// 开始节点预热(新注册或从备用池晋升的节点)
+    pub fn begin_warmup(&mut self, id: &ProcessId) {
+        self.warmup.begin(id);
+        gauge!("zergpool.warmup_factor", "worker" => id.clone()).set(MIN_WARMUP_FACTOR);
+    }
+
+    /// 移除节点的预热状态(节点下线时调用)
+    pub fn end_warmup(&mut self, id: &ProcessId) {
+        self.warmup.remove(id);
+    }
+
+    /// 获取预热跟踪器
+    pub fn warmup(&self) -> &WarmupTracker {
+        &self.warmup
+    }
+
+    /// 计算节点有效权重(基础权重 × 预热系数)
+    pub fn effective_weight(&self, process: &Process) -> f64 {
+        process.weight * self.warmup.factor(&process.id)
+    }
+
+    /// 更新预热系数指标并清理已完成预热的节点记录(定期调用)
+    pub fn refresh_warmup(&mut self) {
+        for id in self.warmup.started.keys() {
+            gauge!("zergpool.warmup_factor", "worker" => id.clone()).set(self.warmup.factor(id));
         }
+        self.warmup.prune();
     }
 
     /// 任务完成回调
@@ -162,12 +315,15 @@ impl ZergRushSelector {
     /// 
     /// # 返回
     /// 选中的Process或错误
+    ///
+    /// 负载不超过阈值的节点按 有效权重 × 剩余容量(1 - 负载) 随机选择，
+    /// 预热中或负载较高的节点获得相应较少的流量
     pub fn select<'a>(
         &self,
         nodes: &'a [(&Process, f64)],
     ) -> Result<&'a Process, SelectorError> {
         // 过滤负载低于阈值的节点
-        let mut candidates: Vec<&(&Process, f64)> = nodes
+        let candidates: Vec<&(&Process, f64)> = nodes
             .iter()
             .filter(|(_, load)| *load <= self.max_load_threshold)
             .collect();
@@ -176,16 +332,12 @@ impl ZergRushSelector {
             return Err(SelectorError::NoNodesAvailable);
         }
 
-        // 按负载升序排序
-        candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-
-        // 取前N个节点(N = CPU核心数/2)
-        let num_candidates = (num_cpus::get() / 2).max(1);
-        candidates.truncate(num_candidates);
-
-        // 随机选择
         let mut rng = rand::rng();
-        Ok(candidates.choose(&mut rng).unwrap().0)
+        let weight = |(p, load): &&(&Process, f64)| self.effective_weight(p) * (1.0 - load).max(MIN_HEADROOM);
+        match candidates.choose_weighted(&mut rng, weight) {
+            Ok(chosen) => Ok(chosen.0),
+            Err(_) => Ok(candidates.choose(&mut rng).unwrap().0),
+        }
     }
 
     /// 扩容操作
@@ -198,17 +350,8 @@ impl ZergRushSelector {
         let new_node = self.backup_pool.take_node()
             .ok_or(ScaleError::NoBackupNodes)?;
 
-        // 渐进式权重迁移
-        let steps = 10;
-        let step_duration = self.backup_pool.warmup_duration / steps as u32;
-        
-        for i in 1..=steps {
-            let weight = i as f64 / steps as f64;
-            gauge!("zergpool.migration_weight").set(weight);
-            sleep(step_duration).await;
-        }
-
-        // 加入主节点池
+        // 立即加入主节点池，由预热跟踪器逐步提升其有效权重
+        self.begin_warmup(&new_node.id);
         main_pool.push(new_node);
         counter!("zergpool.scale_out").increment(1);
         Ok(())
@@ -222,6 +365,7 @@ impl ZergRushSelector {
 
         // 选择负载最低的节点
         let node = main_pool.pop().unwrap();
+        self.end_warmup(&node.id);
         self.backup_pool.add_node(node);
         counter!("zergpool.scale_in").increment(1);
         Ok(())
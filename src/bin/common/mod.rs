@@ -0,0 +1,62 @@
+//! zerg-queen/zerg-drone共用的启动辅助
+
+use std::io::Write;
+use zerg_pool::telemetry;
+
+/// 初始化日志：默认`info`级别，可通过`RUST_LOG`调整
+///
+/// 每行输出时间、级别、模块target与消息，便于日志系统按字段解析
+pub fn init_logging() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format(|buf, record| {
+            writeln!(
+                buf,
+                "{} {:<5} {} {}",
+                chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+                record.level(),
+                record.target(),
+                record.args()
+            )
+        })
+        .init();
+}
+
+/// 安装全局追踪订阅器，配置了`otlp_endpoint`时span由库启动的导出器发送
+pub fn init_tracing() {
+    if let Err(e) = telemetry::install_subscriber() {
+        log::warn!("追踪订阅器安装失败: {}", e);
+    }
+}
+
+/// 注册退出信号(Ctrl-C/SIGINT，Unix下另含SIGTERM)处理，收到信号后在后台线程调用`on_signal`
+///
+/// 信号处理在返回前即已注册，启动过程中收到的信号同样会触发优雅关闭
+pub fn on_shutdown_signal(name: &str, on_signal: impl FnOnce() + Send + 'static) -> std::io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    #[cfg(unix)]
+    let signalled = {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let _guard = runtime.enter();
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        async move {
+            let interrupt = Box::pin(interrupt.recv());
+            let terminate = Box::pin(terminate.recv());
+            futures::future::select(interrupt, terminate).await;
+        }
+    };
+    #[cfg(not(unix))]
+    let signalled = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    std::thread::Builder::new()
+        .name(format!("{}-signal", name))
+        .spawn(move || {
+            runtime.block_on(signalled);
+            log::info!("收到退出信号，开始优雅关闭");
+            on_signal();
+        })?;
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! zerg-ctl: queen运维管理工具
+//!
+//! 示例: `zerg-ctl --control 127.0.0.1:5556 workers`、`zerg-ctl -o json dead-letters`
+//!
+//! 连接`zerg-queen --control`提供的控制端点；未指定`--control`时使用
+//! `ZERG_CONTROL_ADDR`，再缺省为`127.0.0.1:5556`；共享令牌取`--token`或`ZERG_CONTROL_TOKEN`
+//!
+//! `zerg-ctl keygen queen.key`在本地生成CURVE密钥文件及公钥文件`queen.key.pub`，不连接queen
+
+use std::error::Error;
+use std::time::Duration;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use zerg_pool::queen::admin::{AdminClient, AdminRequest, AdminResponse, QueueInfo, WorkerInfo, CONTROL_TOKEN_ENV};
+use zerg_pool::queen::DeadLetter;
+use zerg_pool::transport::curve::CurveKeyPair;
+
+/// 控制端点地址环境变量
+const CONTROL_ADDR_ENV: &str = "ZERG_CONTROL_ADDR";
+/// 默认控制端点地址
+const DEFAULT_CONTROL_ADDR: &str = "127.0.0.1:5556";
+
+fn worker_arg() -> Arg<'static> {
+    Arg::new("worker-id")
+        .value_name("WORKER_ID")
+        .required(true)
+        .value_parser(value_parser!(String))
+}
+
+fn cli() -> Command<'static> {
+    Command::new("zerg-ctl")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("zerg_pool运维工具: 查看和管理运行中的queen")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(Arg::new("control")
+            .long("control")
+            .short('c')
+            .value_name("ADDR")
+            .global(true)
+            .value_parser(value_parser!(String))
+            .help("queen控制端点地址(默认取ZERG_CONTROL_ADDR或127.0.0.1:5556)"))
+        .arg(Arg::new("token")
+            .long("token")
+            .value_name("TOKEN")
+            .global(true)
+            .value_parser(value_parser!(String))
+            .help("控制端点共享令牌(默认取ZERG_CONTROL_TOKEN)"))
+        .arg(Arg::new("output")
+            .long("output")
+            .short('o')
+            .value_name("FORMAT")
+            .global(true)
+            .default_value("table")
+            .value_parser(["table", "json"])
+            .help("输出格式"))
+        .arg(Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECS")
+            .global(true)
+            .default_value("5")
+            .value_parser(value_parser!(u64))
+            .help("连接与等待回复的超时(秒)"))
+        .subcommand(Command::new("workers").about("列出主池与备用池节点"))
+        .subcommand(Command::new("backups").about("列出备用池节点"))
+        .subcommand(Command::new("drain").about("排空节点：不再派发新任务，在途任务照常完成").arg(worker_arg()))
+        .subcommand(Command::new("evict").about("移除节点：在途任务重新入队，drone退出，解除前不可重新注册").arg(worker_arg()))
+        .subcommand(Command::new("unevict").about("解除移除，允许节点重新注册").arg(worker_arg()))
+        .subcommand(Command::new("promote").about("将备用节点晋升到主池").arg(worker_arg()))
+        .subcommand(Command::new("pause").about("暂停任务派发"))
+        .subcommand(Command::new("resume").about("恢复任务派发"))
+        .subcommand(Command::new("queue").about("查看队列深度"))
+        .subcommand(Command::new("dead-letters").about("查看超过重试次数的失败任务"))
+        .subcommand(Command::new("config").about("导出queen当前配置"))
+        .subcommand(Command::new("keygen")
+            .about("生成CURVE密钥文件(公钥另存为<PATH>.pub)")
+            .arg(Arg::new("path")
+                .value_name("PATH")
+                .required(true)
+                .value_parser(value_parser!(std::path::PathBuf))))
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("zerg-ctl: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let matches = cli().get_matches();
+    let (name, sub) = matches.subcommand().expect("subcommand_required");
+    if name == "keygen" {
+        return keygen(sub);
+    }
+    let request = match name {
+        "workers" => AdminRequest::ListWorkers,
+        "backups" => AdminRequest::ListBackups,
+        "drain" => AdminRequest::Drain { worker_id: worker_id(sub) },
+        "evict" => AdminRequest::Evict { worker_id: worker_id(sub) },
+        "unevict" => AdminRequest::Unevict { worker_id: worker_id(sub) },
+        "promote" => AdminRequest::Promote { worker_id: worker_id(sub) },
+        "pause" => AdminRequest::PauseDispatch,
+        "resume" => AdminRequest::ResumeDispatch,
+        "queue" => AdminRequest::Queue,
+        "dead-letters" => AdminRequest::DeadLetters,
+        "config" => AdminRequest::Config,
+        other => unreachable!("未注册的子命令: {}", other),
+    };
+
+    let addr = sub.get_one::<String>("control").cloned()
+        .or_else(|| std::env::var(CONTROL_ADDR_ENV).ok())
+        .unwrap_or_else(|| DEFAULT_CONTROL_ADDR.to_string());
+    let token = sub.get_one::<String>("token").cloned()
+        .or_else(|| std::env::var(CONTROL_TOKEN_ENV).ok())
+        .ok_or_else(|| format!("缺少控制端点令牌: 使用--token或{}", CONTROL_TOKEN_ENV))?;
+    let timeout = Duration::from_secs(*sub.get_one::<u64>("timeout").unwrap());
+    let mut client = AdminClient::connect(addr.as_str(), token, timeout)
+        .map_err(|e| format!("无法连接控制端点 {}: {}", addr, e))?;
+    let response = client.request(&request)?;
+
+    if sub.get_one::<String>("output").map(String::as_str) == Some("json") {
+        println!("{}", serde_json::to_string_pretty(&response)?);
+        return Ok(());
+    }
+    match response {
+        AdminResponse::Workers { workers } => print_workers(&workers),
+        AdminResponse::Queue(queue) => print_queue(&queue),
+        AdminResponse::DeadLetters { tasks } => print_dead_letters(&tasks),
+        AdminResponse::Config { config } => print!("{}", toml::to_string_pretty(&config)?),
+        AdminResponse::Ok { message } => println!("{}", message),
+        // `AdminClient::request`已将错误回复转为Err
+        AdminResponse::Error { message } => return Err(message.into()),
+    }
+    Ok(())
+}
+
+/// 生成密钥文件与公钥文件
+fn keygen(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let path = matches.get_one::<std::path::PathBuf>("path").expect("required");
+    let public_path = format!("{}.pub", path.display());
+    let keypair = CurveKeyPair::generate()?;
+    keypair.save(path)?;
+    keypair.save_public(&public_path)?;
+    println!("已生成密钥文件 {} 与公钥文件 {}", path.display(), public_path);
+    println!("public_key = {}", keypair.public_key);
+    Ok(())
+}
+
+fn worker_id(matches: &ArgMatches) -> String {
+    matches.get_one::<String>("worker-id").cloned().expect("required")
+}
+
+fn print_workers(workers: &[WorkerInfo]) {
+    let rows = workers.iter()
+        .map(|w| vec![
+            w.id.clone(),
+            format!("{:?}", w.role).to_lowercase(),
+            w.health_state.as_str_name().to_lowercase(),
+            format!("{:.1}%", w.cpu_usage * 100.0),
+            format!("{:.1}%", w.mem_usage * 100.0),
+            format!("{}ms", w.net_latency),
+            format!("{}/{}", w.current_tasks, w.max_tasks),
+            w.in_flight.to_string(),
+            if w.draining { "yes" } else { "no" }.to_string(),
+            format!("{:.2}", w.warmup),
+            format!("{:.1}s", w.last_heartbeat_ms as f64 / 1000.0),
+            w.capabilities.join(","),
+        ])
+        .collect();
+    print_table(
+        &["ID", "ROLE", "HEALTH", "CPU", "MEM", "LATENCY", "TASKS", "IN_FLIGHT", "DRAINING", "WARMUP", "LAST_HEARTBEAT", "CAPABILITIES"],
+        rows,
+    );
+}
+
+fn print_queue(queue: &QueueInfo) {
+    let rows = [
+        ("pending", queue.pending.to_string()),
+        ("in_flight", queue.in_flight.to_string()),
+        ("dead_letters", queue.dead_letters.to_string()),
+        ("dispatch_paused", queue.dispatch_paused.to_string()),
+        ("is_leader", queue.is_leader.to_string()),
+    ];
+    print_table(&["FIELD", "VALUE"], rows.into_iter().map(|(k, v)| vec![k.to_string(), v]).collect());
+}
+
+fn print_dead_letters(tasks: &[DeadLetter]) {
+    let rows = tasks.iter()
+        .map(|d| vec![
+            d.task.id.clone(),
+            d.worker_id.clone(),
+            d.attempts.to_string(),
+            chrono::DateTime::from_timestamp_millis(d.failed_at_ms as i64)
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            d.error.clone(),
+        ])
+        .collect();
+    print_table(&["TASK_ID", "WORKER", "ATTEMPTS", "FAILED_AT", "ERROR"], rows);
+}
+
+/// 按列宽对齐输出表格
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_row = |cells: Vec<&str>| {
+        let line: Vec<String> = cells.iter().zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        line.join("  ").trim_end().to_string()
+    };
+    println!("{}", format_row(headers.to_vec()));
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
@@ -0,0 +1,189 @@
+//! zerg-drone: 独立运行的drone进程
+//!
+//! 示例: `zerg-drone --queen tcp://10.0.0.1:5555 --capability gpu --executor command -- ./handler.sh`
+//!
+//! 未指定`--queen`时使用`ZERG_QUEEN_ENDPOINT`(由queen的子进程监管器注入)；
+//! 未指定配置文件时从`ZERG_DRONE_*`环境变量加载配置；收到SIGINT/SIGTERM后
+//! 停止接收新任务，等待执行中的任务完成并回传结果后退出。
+//! 被queen移出进程池时以`EVICTED_EXIT_CODE`退出，注册被拒且重试无效时以`REJECTED_EXIT_CODE`退出，
+//! 监管器据此不再重启
+
+mod common;
+
+use std::error::Error;
+use std::path::PathBuf;
+use clap::builder::PossibleValuesParser;
+use clap::{value_parser, Arg, Command};
+use zerg_pool::config::DroneConfig;
+use zerg_pool::drone::executor::{self, BUILTIN_EXECUTORS};
+use zerg_pool::drone::network::NetworkError;
+use zerg_pool::proto::zergpool::RejectReason;
+use zerg_pool::queen::supervisor::{EVICTED_EXIT_CODE, QUEEN_ENDPOINT_ENV, REJECTED_EXIT_CODE};
+use zerg_pool::telemetry;
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{Drone, DroneError};
+
+fn cli() -> Command<'static> {
+    Command::new("zerg-drone")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("zerg_pool drone: 连接queen并执行任务")
+        .arg(Arg::new("queen")
+            .long("queen")
+            .short('q')
+            .value_name("ENDPOINT")
+            .multiple_occurrences(true)
+            .use_value_delimiter(true)
+            .value_parser(value_parser!(String))
+            .help("queen地址，可重复指定或以逗号分隔(高可用集群)"))
+        .arg(Arg::new("config")
+            .long("config")
+            .short('c')
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("配置文件(.toml/.json)"))
+        .arg(Arg::new("worker-id")
+            .long("worker-id")
+            .value_name("ID")
+            .value_parser(value_parser!(String))
+            .help("节点ID，覆盖配置中的worker_id"))
+        .arg(Arg::new("capability")
+            .long("capability")
+            .value_name("NAME")
+            .multiple_occurrences(true)
+            .use_value_delimiter(true)
+            .value_parser(value_parser!(String))
+            .help("节点能力标签，可重复指定或以逗号分隔"))
+        .arg(Arg::new("executor")
+            .long("executor")
+            .short('e')
+            .value_name("NAME")
+            .default_value("echo")
+            .value_parser(PossibleValuesParser::new(BUILTIN_EXECUTORS.to_vec()))
+            .help("任务执行器: echo原样返回负载，command为每个任务执行`--`之后的命令"))
+        .arg(Arg::new("concurrency")
+            .long("concurrency")
+            .short('j')
+            .value_name("N")
+            .value_parser(value_parser!(usize))
+            .help("并发执行的任务数(执行线程数与上报的最大任务数)"))
+        .arg(Arg::new("metrics")
+            .long("metrics")
+            .value_name("ADDR")
+            .value_parser(value_parser!(String))
+            .help("Prometheus指标HTTP地址(如127.0.0.1:9101)，覆盖配置中的metrics_addr"))
+        .arg(Arg::new("otlp")
+            .long("otlp")
+            .value_name("URL")
+            .value_parser(value_parser!(String))
+            .help("span导出的OTLP/HTTP收集器地址(如http://127.0.0.1:4318)，覆盖配置中的otlp_endpoint"))
+        .arg(Arg::new("transport")
+            .long("transport")
+            .value_name("KIND")
+            .value_parser(["zmq", "native"])
+            .help("传输实现，需与queen一致"))
+        .arg(Arg::new("command")
+            .value_name("COMMAND")
+            .multiple_values(true)
+            .last(true)
+            .value_parser(value_parser!(String))
+            .help("command执行器运行的命令及参数(任务负载写入stdin，stdout作为输出)"))
+}
+
+fn main() {
+    common::init_logging();
+    common::init_tracing();
+    let result = run();
+    // 退出前发送尚未导出的span
+    telemetry::flush();
+    if let Err(e) = result {
+        log::error!("zerg-drone异常退出: {}", e);
+        let code = if is_evicted(e.as_ref()) {
+            EVICTED_EXIT_CODE
+        } else if is_rejected(e.as_ref()) {
+            REJECTED_EXIT_CODE
+        } else {
+            1
+        };
+        std::process::exit(code);
+    }
+}
+
+/// 是否因被queen移出进程池而退出
+fn is_evicted(error: &(dyn Error + 'static)) -> bool {
+    matches!(
+        error.downcast_ref::<DroneError>(),
+        Some(DroneError::Network(NetworkError::Evicted(_)))
+            | Some(DroneError::Network(NetworkError::Rejected { reason: RejectReason::WorkerEvicted, .. }))
+    )
+}
+
+/// 是否因注册被拒绝且重试无效而退出(节点数上限与重复ID可能随后恢复，不在此列)
+fn is_rejected(error: &(dyn Error + 'static)) -> bool {
+    match error.downcast_ref::<DroneError>() {
+        Some(DroneError::Network(NetworkError::Rejected { reason, .. })) => {
+            !matches!(reason, RejectReason::PoolFull | RejectReason::DuplicateId)
+        }
+        Some(DroneError::Network(NetworkError::IncompatibleProtocol { .. })) => true,
+        _ => false,
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let matches = cli().get_matches();
+    let mut config = match matches.get_one::<PathBuf>("config") {
+        Some(path) => DroneConfig::from_file(path)?.with_env_overrides()?,
+        None => DroneConfig::from_env()?,
+    };
+    if let Some(id) = matches.get_one::<String>("worker-id") {
+        config.worker_id = Some(id.clone());
+    }
+    if let Some(&concurrency) = matches.get_one::<usize>("concurrency") {
+        config.worker_threads = concurrency;
+        config.max_tasks = concurrency as u32;
+    }
+    if let Some(transport) = matches.get_one::<String>("transport") {
+        config.transport = transport.parse::<TransportKind>()?;
+    }
+    if let Some(addr) = matches.get_one::<String>("metrics") {
+        config.metrics_addr = Some(addr.clone());
+    }
+    if let Some(endpoint) = matches.get_one::<String>("otlp") {
+        config.otlp_endpoint = Some(endpoint.clone());
+    }
+
+    let mut endpoints: Vec<String> = match matches.get_many::<String>("queen") {
+        Some(values) => values.cloned().collect(),
+        None => std::env::var(QUEEN_ENDPOINT_ENV)
+            .map(|value| value.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    };
+    endpoints.retain(|endpoint| !endpoint.is_empty());
+    if endpoints.is_empty() {
+        return Err(format!("未指定queen地址(--queen或{})", QUEEN_ENDPOINT_ENV).into());
+    }
+
+    let command: Vec<String> = matches.get_many::<String>("command")
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default();
+    let executor = executor::builtin(matches.get_one::<String>("executor").unwrap(), &command)?;
+    let capabilities = matches.get_many::<String>("capability")
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default();
+
+    let mut builder = Drone::builder(endpoints[0].clone())
+        .config(config)
+        .capabilities(capabilities)
+        .shared_executor(executor);
+    for endpoint in &endpoints[1..] {
+        builder = builder.endpoint(endpoint.clone());
+    }
+    let drone = builder.build()?;
+    log::info!("zerg-drone {} 已启动，queen: {}", drone.worker_id(), endpoints.join(","));
+
+    let handle = drone.handle();
+    common::on_shutdown_signal("zerg-drone", move || handle.stop())?;
+
+    drone.run()?;
+    log::info!("zerg-drone已退出");
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! zerg-queen: 独立运行的queen进程
+//!
+//! 示例: `zerg-queen --bind tcp://0.0.0.0:5555 --config queen.toml --metrics 127.0.0.1:9100 --control 127.0.0.1:5556`
+//!
+//! 启用控制端点时必须通过`--control-token`或`ZERG_CONTROL_TOKEN`配置共享令牌
+//!
+//! 未指定配置文件时从`ZERG_POOL_*`环境变量加载配置；收到SIGINT/SIGTERM后
+//! 停止drone子进程、让出leader身份并退出
+
+mod common;
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
+use clap::{value_parser, Arg, Command};
+use crossbeam_channel::unbounded;
+use zerg_pool::config::PoolConfig;
+use zerg_pool::queen::admin::{AdminServer, CONTROL_TOKEN_ENV};
+use zerg_pool::queen::PoolCommand;
+use zerg_pool::telemetry;
+use zerg_pool::transport::TransportKind;
+use zerg_pool::DronePool;
+
+/// 配置文件检查间隔
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+fn cli() -> Command<'static> {
+    Command::new("zerg-queen")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("zerg_pool queen: 接收drone注册并派发任务")
+        .arg(Arg::new("bind")
+            .long("bind")
+            .value_name("ENDPOINT")
+            .default_value("tcp://0.0.0.0:5555")
+            .value_parser(value_parser!(String))
+            .help("监听地址(tcp://host:port、ipc:///path)"))
+        .arg(Arg::new("config")
+            .long("config")
+            .short('c')
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("配置文件(.toml/.json)，运行期间修改会自动热加载"))
+        .arg(Arg::new("journal")
+            .long("journal")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("任务日志文件，覆盖配置中的journal_path"))
+        .arg(Arg::new("transport")
+            .long("transport")
+            .value_name("KIND")
+            .value_parser(["zmq", "native"])
+            .help("传输实现，覆盖配置中的transport"))
+        .arg(Arg::new("metrics")
+            .long("metrics")
+            .value_name("ADDR")
+            .value_parser(value_parser!(String))
+            .help("Prometheus指标HTTP地址(如127.0.0.1:9100)，覆盖配置中的metrics_addr"))
+        .arg(Arg::new("otlp")
+            .long("otlp")
+            .value_name("URL")
+            .value_parser(value_parser!(String))
+            .help("span导出的OTLP/HTTP收集器地址(如http://127.0.0.1:4318)，覆盖配置中的otlp_endpoint"))
+        .arg(Arg::new("control")
+            .long("control")
+            .value_name("ADDR")
+            .value_parser(value_parser!(String))
+            .help("控制端点地址(如127.0.0.1:5556)，供zerg-ctl查看和管理进程池"))
+        .arg(Arg::new("control-token")
+            .long("control-token")
+            .value_name("TOKEN")
+            .value_parser(value_parser!(String))
+            .help("控制端点共享令牌(默认取ZERG_CONTROL_TOKEN)，启用--control时必填"))
+}
+
+fn main() {
+    common::init_logging();
+    common::init_tracing();
+    let result = run();
+    // 退出前发送尚未导出的span
+    telemetry::flush();
+    if let Err(e) = result {
+        log::error!("zerg-queen异常退出: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let matches = cli().get_matches();
+    let config_path = matches.get_one::<PathBuf>("config").cloned();
+    let mut config = match &config_path {
+        Some(path) => PoolConfig::from_file(path)?.with_env_overrides()?,
+        None => PoolConfig::from_env()?,
+    };
+    if let Some(journal) = matches.get_one::<PathBuf>("journal") {
+        config.journal_path = Some(journal.clone());
+    }
+    if let Some(transport) = matches.get_one::<String>("transport") {
+        config.transport = transport.parse::<TransportKind>()?;
+    }
+    if let Some(addr) = matches.get_one::<String>("metrics") {
+        config.metrics_addr = Some(addr.clone());
+    }
+    if let Some(endpoint) = matches.get_one::<String>("otlp") {
+        config.otlp_endpoint = Some(endpoint.clone());
+    }
+    config.validate()?;
+    // 启用控制端点时先检查令牌，避免绑定后才失败
+    let control = match matches.get_one::<String>("control") {
+        Some(addr) => {
+            let token = matches.get_one::<String>("control-token").cloned()
+                .or_else(|| std::env::var(CONTROL_TOKEN_ENV).ok())
+                .ok_or_else(|| format!("启用控制端点需要--control-token或{}", CONTROL_TOKEN_ENV))?;
+            Some((addr.clone(), token))
+        }
+        None => None,
+    };
+
+    let bind = matches.get_one::<String>("bind").unwrap();
+    let mut pool = DronePool::bind(bind, config)?;
+    if let Some(path) = config_path {
+        pool.watch_config(path, CONFIG_WATCH_INTERVAL);
+    }
+    log::info!("zerg-queen已启动: {}", bind);
+
+    let (commands, receiver) = unbounded();
+    if let Some((addr, token)) = control {
+        AdminServer::bind(addr.as_str(), token, commands.clone())?;
+    }
+    common::on_shutdown_signal("zerg-queen", move || {
+        let _ = commands.send(PoolCommand::Shutdown);
+    })?;
+
+    pool.run(receiver)?;
+    log::info!("zerg-queen已退出");
+    Ok(())
+}
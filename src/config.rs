@@ -0,0 +1,1051 @@
+//! 运行时配置模块
+//!
+//! 提供queen端`PoolConfig`与drone端`DroneConfig`的构建器和校验，
+//! 支持从TOML/JSON文件及环境变量加载
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use crossbeam_channel::{unbounded, Receiver};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::transport::TransportKind;
+
+/// queen端环境变量前缀
+pub const POOL_ENV_PREFIX: &str = "ZERG_POOL_";
+/// drone端环境变量前缀
+pub const DRONE_ENV_PREFIX: &str = "ZERG_DRONE_";
+
+/// 配置错误类型
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("配置文件读取失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TOML解析失败: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("JSON解析失败: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("不支持的配置文件格式: {0}")]
+    UnsupportedFormat(String),
+    #[error("环境变量 {key} 的值无效: {value}")]
+    InvalidEnv { key: String, value: String },
+    #[error("配置项 {field} 无效: {reason}")]
+    Invalid { field: &'static str, reason: String },
+}
+
+fn invalid(field: &'static str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid { field, reason: reason.into() }
+}
+
+fn check_ratio(field: &'static str, value: f64) -> Result<(), ConfigError> {
+    if !(0.0..=1.0).contains(&value) || value.is_nan() {
+        return Err(invalid(field, format!("必须在0.0-1.0之间, 实际为{}", value)));
+    }
+    Ok(())
+}
+
+fn check_metrics_addr(addr: Option<&str>) -> Result<(), ConfigError> {
+    match addr {
+        Some(addr) if addr.parse::<std::net::SocketAddr>().is_err() => {
+            Err(invalid("metrics_addr", format!("应为ip:port格式, 实际为{}", addr)))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_otlp_endpoint(endpoint: Option<&str>) -> Result<(), ConfigError> {
+    match endpoint.map(crate::telemetry::check_endpoint) {
+        Some(Err(e)) => Err(invalid("otlp_endpoint", format!("{}(应为http://host:port)", e))),
+        _ => Ok(()),
+    }
+}
+
+/// 节点评分权重(用于选择最优工作节点，四项之和应为1.0)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BalanceWeights {
+    pub cpu: f32,
+    pub mem: f32,
+    pub latency: f32,
+    pub tasks: f32,
+}
+
+impl Default for BalanceWeights {
+    fn default() -> Self {
+        Self {
+            cpu: 0.4,
+            mem: 0.3,
+            latency: 0.2,
+            tasks: 0.1,
+        }
+    }
+}
+
+/// 任务重试策略
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// 最大重试次数(0表示不重试)
+    pub max_retries: u32,
+    /// 首次重试退避时间(ms)，之后按指数增长
+    pub backoff_ms: u64,
+    /// 退避时间上限(ms)
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            backoff_ms: 100,
+            max_backoff_ms: 5_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 第`attempt`次重试(从1开始)前的退避时间
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        Duration::from_millis(self.backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+/// 本地drone子进程配置(由queen启动并监管)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupervisorConfig {
+    /// drone可执行文件(为空时使用当前可执行文件，通过`args`切换到drone模式)
+    pub program: Option<PathBuf>,
+    /// 启动参数
+    pub args: Vec<String>,
+    /// 额外环境变量
+    pub env: BTreeMap<String, String>,
+    /// 子进程数量
+    pub instances: usize,
+    /// 节点ID前缀，第i个子进程的ID为`<prefix>-<i>`，重启后保持不变
+    pub worker_id_prefix: String,
+    /// 首次重启前的退避时间(ms)，连续崩溃时按指数增长
+    pub restart_backoff_ms: u64,
+    /// 重启退避上限(ms)，子进程稳定运行超过该时长后退避重新计算
+    pub max_restart_backoff_ms: u64,
+    /// 关闭时等待子进程退出的时间(ms)，超时后强制结束
+    pub shutdown_timeout_ms: u64,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            program: None,
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            instances: 1,
+            worker_id_prefix: "drone".to_string(),
+            restart_backoff_ms: 500,
+            max_restart_backoff_ms: 30_000,
+            shutdown_timeout_ms: 5_000,
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// 连续第`crashes`次(从1开始)崩溃后的重启退避时间
+    pub fn restart_backoff(&self, crashes: u32) -> Duration {
+        let factor = 1u64 << crashes.saturating_sub(1).min(16);
+        Duration::from_millis(self.restart_backoff_ms.saturating_mul(factor).min(self.max_restart_backoff_ms))
+    }
+
+    /// 重启退避上限
+    pub fn max_restart_backoff(&self) -> Duration {
+        Duration::from_millis(self.max_restart_backoff_ms)
+    }
+
+    /// 关闭等待时间
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_millis(self.shutdown_timeout_ms)
+    }
+
+    /// 校验配置合法性
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.instances == 0 {
+            return Err(invalid("drones.instances", "必须大于0"));
+        }
+        if self.worker_id_prefix.is_empty() {
+            return Err(invalid("drones.worker_id_prefix", "不能为空"));
+        }
+        if self.restart_backoff_ms > self.max_restart_backoff_ms {
+            return Err(invalid("drones", "restart_backoff_ms不能大于max_restart_backoff_ms"));
+        }
+        Ok(())
+    }
+}
+
+/// queen端CURVE安全配置(仅`zmq`传输)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveServerConfig {
+    /// queen密钥文件(含公钥与私钥，可用`zerg-ctl keygen`生成)
+    pub key_file: PathBuf,
+    /// drone公钥允许列表(文件或目录，为空时接受任何持有queen公钥的drone)
+    #[serde(default)]
+    pub authorized_keys: Option<PathBuf>,
+}
+
+/// drone端CURVE安全配置(仅`zmq`传输)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveClientConfig {
+    /// drone密钥文件(含公钥与私钥)
+    pub key_file: PathBuf,
+    /// queen公钥文件
+    pub server_key_file: PathBuf,
+}
+
+fn check_curve(enabled: bool, transport: TransportKind) -> Result<(), ConfigError> {
+    if enabled && transport != TransportKind::Zmq {
+        return Err(invalid("curve", format!("只有zmq传输支持CURVE, 当前为{}", transport.as_str())));
+    }
+    Ok(())
+}
+
+/// 在线节点ID重复时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// 拒绝新的注册
+    #[default]
+    Reject,
+    /// 新连接接管该ID，原连接被移出进程池
+    Replace,
+}
+
+/// drone注册准入策略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmissionConfig {
+    /// 注册令牌(为空时不校验，配置后drone需使用相同令牌)
+    pub token: Option<String>,
+    /// 拒绝版本与queen不兼容的drone(主版本不同，`0.x`时次版本不同)
+    pub check_version: bool,
+    /// 主池与备用池的节点总数上限(为空时不限制)
+    pub max_workers: Option<usize>,
+    /// 在线节点ID重复(来自不同连接)时的处理方式
+    pub duplicate_ids: DuplicatePolicy,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        Self {
+            token: None,
+            check_version: true,
+            max_workers: None,
+            duplicate_ids: DuplicatePolicy::Reject,
+        }
+    }
+}
+
+impl AdmissionConfig {
+    /// 校验配置合法性
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.token.as_deref().is_some_and(str::is_empty) {
+            return Err(invalid("admission.token", "不能为空"));
+        }
+        if self.max_workers == Some(0) {
+            return Err(invalid("admission.max_workers", "必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// 变更日志中隐藏取值的字段
+const SECRET_FIELDS: &[&str] = &["admission.token"];
+
+/// 热加载时不生效、需重启queen的字段(均在绑定或启动时读取)
+///
+/// 与`PoolConfig::keep_restart_required`保持一致
+const RESTART_REQUIRED_FIELDS: &[&str] = &[
+    "engine_workers",
+    "engine_queue_capacity",
+    "journal_path",
+    "lease_path",
+    "lease_ttl_ms",
+    "queen_id",
+    "transport",
+    "ipc_mode",
+    "drones",
+    "metrics_addr",
+    "otlp_endpoint",
+    "curve",
+];
+
+/// 配置项变更记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl ConfigChange {
+    /// 该变更是否需要重启queen才能生效
+    pub fn requires_restart(&self) -> bool {
+        let top = self.field.split('.').next().unwrap_or_default();
+        RESTART_REQUIRED_FIELDS.contains(&top)
+    }
+}
+
+impl std::fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+/// queen端(DronePool/TaskEngine)配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PoolConfig {
+    /// 主工作池最大容量，超出的节点进入备用池
+    pub max_main_pool_size: usize,
+    /// 选择器最大负载阈值(0.0-1.0)
+    pub max_load_threshold: f64,
+    /// 健康检查间隔(ms)
+    pub check_interval_ms: u64,
+    /// 新节点预热时长(ms)
+    pub warmup_duration_ms: u64,
+    /// 心跳超时时间(ms)
+    pub heartbeat_timeout_ms: u64,
+    /// 连续超时多少次后熔断
+    pub circuit_breaker_threshold: u32,
+    /// CPU过载阈值(0.0-1.0)
+    pub cpu_overload_threshold: f32,
+    /// 内存过载阈值(0.0-1.0)
+    pub mem_overload_threshold: f32,
+    /// 任务引擎worker数量
+    pub engine_workers: usize,
+    /// 任务引擎队列容量
+    pub engine_queue_capacity: usize,
+    /// 节点评分权重
+    pub balance_weights: BalanceWeights,
+    /// 任务重试策略
+    pub retry: RetryPolicy,
+    /// 死信队列容量(超过重试次数仍失败的任务，超出后丢弃最早的记录；0为不保留)
+    pub dead_letter_capacity: usize,
+    /// 任务日志文件路径(为空时不启用持久化)
+    pub journal_path: Option<PathBuf>,
+    /// 任务日志追加多少条记录后触发压缩
+    pub journal_compact_threshold: usize,
+    /// 高可用租约文件路径(为空时为单queen模式)
+    pub lease_path: Option<PathBuf>,
+    /// 租约有效期(ms)
+    pub lease_ttl_ms: u64,
+    /// 参与选举的queen ID(为空时根据进程号和绑定地址生成)
+    pub queen_id: Option<String>,
+    /// 传输实现
+    pub transport: TransportKind,
+    /// ipc地址socket文件权限(如`0o660`，为空时由umask决定)
+    pub ipc_mode: Option<u32>,
+    /// 由queen启动并监管的本地drone子进程(为空时不启动)
+    pub drones: Option<SupervisorConfig>,
+    /// Prometheus指标HTTP地址(如`127.0.0.1:9100`，为空时不启用)
+    pub metrics_addr: Option<String>,
+    /// span导出的OTLP/HTTP收集器地址(如`http://127.0.0.1:4318`，为空时不导出)
+    pub otlp_endpoint: Option<String>,
+    /// CURVE加密与drone认证(为空时明文传输)
+    pub curve: Option<CurveServerConfig>,
+    /// drone注册准入策略
+    pub admission: AdmissionConfig,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_main_pool_size: 10,
+            max_load_threshold: 0.8,
+            check_interval_ms: 5_000,
+            warmup_duration_ms: 5_000,
+            heartbeat_timeout_ms: 9_000,
+            circuit_breaker_threshold: 3,
+            cpu_overload_threshold: 0.9,
+            mem_overload_threshold: 0.9,
+            engine_workers: num_cpus::get(),
+            engine_queue_capacity: 1024,
+            balance_weights: BalanceWeights::default(),
+            retry: RetryPolicy::default(),
+            dead_letter_capacity: 1_000,
+            journal_path: None,
+            journal_compact_threshold: 10_000,
+            lease_path: None,
+            lease_ttl_ms: 3_000,
+            queen_id: None,
+            transport: TransportKind::Zmq,
+            ipc_mode: None,
+            drones: None,
+            metrics_addr: None,
+            otlp_endpoint: None,
+            curve: None,
+            admission: AdmissionConfig::default(),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// 创建配置构建器
+    pub fn builder() -> PoolConfigBuilder {
+        PoolConfigBuilder::default()
+    }
+
+    /// 健康检查间隔
+    pub fn check_interval(&self) -> Duration {
+        Duration::from_millis(self.check_interval_ms)
+    }
+
+    /// 节点预热时长
+    pub fn warmup_duration(&self) -> Duration {
+        Duration::from_millis(self.warmup_duration_ms)
+    }
+
+    /// 心跳超时时间
+    pub fn heartbeat_timeout(&self) -> Duration {
+        Duration::from_millis(self.heartbeat_timeout_ms)
+    }
+
+    /// 租约有效期
+    pub fn lease_ttl(&self) -> Duration {
+        Duration::from_millis(self.lease_ttl_ms)
+    }
+
+    /// 校验配置合法性
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_main_pool_size == 0 {
+            return Err(invalid("max_main_pool_size", "必须大于0"));
+        }
+        check_ratio("max_load_threshold", self.max_load_threshold)?;
+        check_ratio("cpu_overload_threshold", self.cpu_overload_threshold as f64)?;
+        check_ratio("mem_overload_threshold", self.mem_overload_threshold as f64)?;
+        if self.check_interval_ms == 0 {
+            return Err(invalid("check_interval_ms", "必须大于0"));
+        }
+        if self.heartbeat_timeout_ms == 0 {
+            return Err(invalid("heartbeat_timeout_ms", "必须大于0"));
+        }
+        if self.circuit_breaker_threshold == 0 {
+            return Err(invalid("circuit_breaker_threshold", "必须大于0"));
+        }
+        if self.engine_workers == 0 {
+            return Err(invalid("engine_workers", "必须大于0"));
+        }
+        if self.engine_queue_capacity == 0 {
+            return Err(invalid("engine_queue_capacity", "必须大于0"));
+        }
+        let w = &self.balance_weights;
+        for weight in [w.cpu, w.mem, w.latency, w.tasks] {
+            check_ratio("balance_weights", weight as f64)?;
+        }
+        if ((w.cpu + w.mem + w.latency + w.tasks) - 1.0).abs() > 1e-3 {
+            return Err(invalid("balance_weights", "权重之和必须为1.0"));
+        }
+        if self.lease_path.is_some() && self.lease_ttl_ms < 300 {
+            return Err(invalid("lease_ttl_ms", "启用高可用时不能小于300ms"));
+        }
+        if self.ipc_mode.is_some_and(|mode| mode > 0o777) {
+            return Err(invalid("ipc_mode", "只能包含0o777以内的权限位"));
+        }
+        if let Some(drones) = &self.drones {
+            drones.validate()?;
+        }
+        check_metrics_addr(self.metrics_addr.as_deref())?;
+        check_otlp_endpoint(self.otlp_endpoint.as_deref())?;
+        check_curve(self.curve.is_some(), self.transport)?;
+        self.admission.validate()?;
+        if self.journal_compact_threshold == 0 {
+            return Err(invalid("journal_compact_threshold", "必须大于0"));
+        }
+        if self.retry.backoff_ms > self.retry.max_backoff_ms {
+            return Err(invalid("retry", "backoff_ms不能大于max_backoff_ms"));
+        }
+        Ok(())
+    }
+
+    /// 需重启才生效的字段沿用`running`中的取值(热加载时保留正在使用的配置)
+    pub fn keep_restart_required(self, running: &PoolConfig) -> Self {
+        Self {
+            engine_workers: running.engine_workers,
+            engine_queue_capacity: running.engine_queue_capacity,
+            journal_path: running.journal_path.clone(),
+            lease_path: running.lease_path.clone(),
+            lease_ttl_ms: running.lease_ttl_ms,
+            queen_id: running.queen_id.clone(),
+            transport: running.transport,
+            ipc_mode: running.ipc_mode,
+            drones: running.drones.clone(),
+            metrics_addr: running.metrics_addr.clone(),
+            otlp_endpoint: running.otlp_endpoint.clone(),
+            curve: running.curve.clone(),
+            ..self
+        }
+    }
+
+    /// 对比两份配置，返回发生变化的字段
+    pub fn diff(&self, other: &PoolConfig) -> Vec<ConfigChange> {
+        let (Ok(old), Ok(new)) = (serde_json::to_value(self), serde_json::to_value(other)) else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        diff_values("", &old, &new, &mut changes);
+        changes
+    }
+
+    /// 从TOML字符串加载
+    pub fn from_toml_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(s)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从JSON字符串加载
+    pub fn from_json_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(s)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从配置文件加载(按扩展名识别.toml/.json)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let config: Self = load_file(path.as_ref())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从环境变量加载(以默认配置为基础)
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::default().with_env_overrides()
+    }
+
+    /// 使用`ZERG_POOL_*`环境变量覆盖当前配置
+    ///
+    /// 例如`ZERG_POOL_MAX_MAIN_POOL_SIZE=20`
+    pub fn with_env_overrides(self) -> Result<Self, ConfigError> {
+        let config: Self = apply_env(&self, POOL_ENV_PREFIX)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// PoolConfig构建器
+#[derive(Debug, Default)]
+pub struct PoolConfigBuilder {
+    config: PoolConfig,
+}
+
+impl PoolConfigBuilder {
+    /// 设置主工作池最大容量
+    pub fn max_main_pool_size(mut self, size: usize) -> Self {
+        self.config.max_main_pool_size = size;
+        self
+    }
+
+    /// 设置选择器最大负载阈值
+    pub fn max_load_threshold(mut self, threshold: f64) -> Self {
+        self.config.max_load_threshold = threshold;
+        self
+    }
+
+    /// 设置健康检查间隔
+    pub fn check_interval(mut self, interval: Duration) -> Self {
+        self.config.check_interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    /// 设置新节点预热时长
+    pub fn warmup_duration(mut self, duration: Duration) -> Self {
+        self.config.warmup_duration_ms = duration.as_millis() as u64;
+        self
+    }
+
+    /// 设置心跳超时时间
+    pub fn heartbeat_timeout(mut self, timeout: Duration) -> Self {
+        self.config.heartbeat_timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    /// 设置熔断阈值
+    pub fn circuit_breaker_threshold(mut self, count: u32) -> Self {
+        self.config.circuit_breaker_threshold = count;
+        self
+    }
+
+    /// 设置CPU/内存过载阈值
+    pub fn overload_thresholds(mut self, cpu: f32, mem: f32) -> Self {
+        self.config.cpu_overload_threshold = cpu;
+        self.config.mem_overload_threshold = mem;
+        self
+    }
+
+    /// 设置任务引擎worker数量
+    pub fn engine_workers(mut self, workers: usize) -> Self {
+        self.config.engine_workers = workers;
+        self
+    }
+
+    /// 设置任务引擎队列容量
+    pub fn engine_queue_capacity(mut self, capacity: usize) -> Self {
+        self.config.engine_queue_capacity = capacity;
+        self
+    }
+
+    /// 设置节点评分权重
+    pub fn balance_weights(mut self, weights: BalanceWeights) -> Self {
+        self.config.balance_weights = weights;
+        self
+    }
+
+    /// 设置任务重试策略
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.config.retry = retry;
+        self
+    }
+
+    /// 设置死信队列容量
+    pub fn dead_letter_capacity(mut self, capacity: usize) -> Self {
+        self.config.dead_letter_capacity = capacity;
+        self
+    }
+
+    /// 启用任务日志
+    pub fn journal_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.journal_path = Some(path.into());
+        self
+    }
+
+    /// 启用高可用选主(多个queen共享同一租约文件)
+    pub fn lease(mut self, path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        self.config.lease_path = Some(path.into());
+        self.config.lease_ttl_ms = ttl.as_millis() as u64;
+        self
+    }
+
+    /// 设置参与选举的queen ID
+    pub fn queen_id(mut self, id: impl Into<String>) -> Self {
+        self.config.queen_id = Some(id.into());
+        self
+    }
+
+    /// 设置传输实现
+    pub fn transport(mut self, kind: TransportKind) -> Self {
+        self.config.transport = kind;
+        self
+    }
+
+    /// 设置ipc地址socket文件权限
+    pub fn ipc_mode(mut self, mode: u32) -> Self {
+        self.config.ipc_mode = Some(mode);
+        self
+    }
+
+    /// 由queen启动并监管本地drone子进程
+    pub fn drones(mut self, drones: SupervisorConfig) -> Self {
+        self.config.drones = Some(drones);
+        self
+    }
+
+    /// 启用Prometheus指标导出
+    pub fn metrics_addr(mut self, addr: impl Into<String>) -> Self {
+        self.config.metrics_addr = Some(addr.into());
+        self
+    }
+
+    /// 将span导出到OTLP/HTTP收集器
+    pub fn otlp_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.config.otlp_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// 启用CURVE加密与drone认证
+    pub fn curve(mut self, curve: CurveServerConfig) -> Self {
+        self.config.curve = Some(curve);
+        self
+    }
+
+    /// 设置drone注册准入策略
+    pub fn admission(mut self, admission: AdmissionConfig) -> Self {
+        self.config.admission = admission;
+        self
+    }
+
+    /// 设置任务日志压缩阈值
+    pub fn journal_compact_threshold(mut self, records: usize) -> Self {
+        self.config.journal_compact_threshold = records;
+        self
+    }
+
+    /// 校验并生成配置
+    pub fn build(self) -> Result<PoolConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// drone端(HeartbeatManager/TaskQueue)配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DroneConfig {
+    /// 心跳发送间隔(ms)
+    pub heartbeat_interval_ms: u64,
+    /// 心跳超时时间(ms)
+    pub heartbeat_timeout_ms: u64,
+    /// 连续超时多少次后熔断
+    pub circuit_breaker_threshold: u32,
+    /// 节点最大并发任务数
+    pub max_tasks: u32,
+    /// 任务队列容量
+    pub queue_capacity: usize,
+    /// 任务执行线程数
+    pub worker_threads: usize,
+    /// 节点ID(同时作为ZMQ身份帧)
+    pub worker_id: Option<String>,
+    /// 节点ID持久化文件(未指定`worker_id`时使用，保证重启后ID不变)
+    pub identity_path: Option<PathBuf>,
+    /// 传输实现(需与queen一致)
+    pub transport: TransportKind,
+    /// Prometheus指标HTTP地址(如`127.0.0.1:9101`，为空时不启用)
+    pub metrics_addr: Option<String>,
+    /// span导出的OTLP/HTTP收集器地址(如`http://127.0.0.1:4318`，为空时不导出)
+    pub otlp_endpoint: Option<String>,
+    /// CURVE加密与认证(需与queen一致，为空时明文传输)
+    pub curve: Option<CurveClientConfig>,
+    /// 注册令牌(queen启用准入令牌时必填)
+    pub token: Option<String>,
+}
+
+impl Default for DroneConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_ms: 3_000,
+            heartbeat_timeout_ms: 9_000,
+            circuit_breaker_threshold: 3,
+            max_tasks: num_cpus::get() as u32,
+            queue_capacity: 1000,
+            worker_threads: num_cpus::get(),
+            worker_id: None,
+            identity_path: None,
+            transport: TransportKind::Zmq,
+            metrics_addr: None,
+            otlp_endpoint: None,
+            curve: None,
+            token: None,
+        }
+    }
+}
+
+impl DroneConfig {
+    /// 创建配置构建器
+    pub fn builder() -> DroneConfigBuilder {
+        DroneConfigBuilder::default()
+    }
+
+    /// 心跳发送间隔
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_millis(self.heartbeat_interval_ms)
+    }
+
+    /// 心跳超时时间
+    pub fn heartbeat_timeout(&self) -> Duration {
+        Duration::from_millis(self.heartbeat_timeout_ms)
+    }
+
+    /// 确定节点ID
+    ///
+    /// 优先使用`worker_id`；其次读取`identity_path`中持久化的ID，
+    /// 文件不存在时生成新ID并写入；都未配置时生成随机ID
+    pub fn resolve_worker_id(&self) -> Result<String, ConfigError> {
+        if let Some(id) = &self.worker_id {
+            return Ok(id.clone());
+        }
+        let Some(path) = &self.identity_path else {
+            return Ok(uuid::Uuid::new_v4().to_string());
+        };
+        match std::fs::read_to_string(path) {
+            Ok(content) if !content.trim().is_empty() => return Ok(content.trim().to_string()),
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, &id)?;
+        Ok(id)
+    }
+
+    /// 校验配置合法性
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.heartbeat_interval_ms == 0 {
+            return Err(invalid("heartbeat_interval_ms", "必须大于0"));
+        }
+        if self.heartbeat_timeout_ms <= self.heartbeat_interval_ms {
+            return Err(invalid("heartbeat_timeout_ms", "必须大于心跳发送间隔"));
+        }
+        if self.circuit_breaker_threshold == 0 {
+            return Err(invalid("circuit_breaker_threshold", "必须大于0"));
+        }
+        if self.max_tasks == 0 {
+            return Err(invalid("max_tasks", "必须大于0"));
+        }
+        if self.queue_capacity == 0 {
+            return Err(invalid("queue_capacity", "必须大于0"));
+        }
+        if self.worker_id.as_deref().is_some_and(|id| id.trim().is_empty()) {
+            return Err(invalid("worker_id", "不能为空"));
+        }
+        if self.worker_threads == 0 {
+            return Err(invalid("worker_threads", "必须大于0"));
+        }
+        check_metrics_addr(self.metrics_addr.as_deref())?;
+        check_otlp_endpoint(self.otlp_endpoint.as_deref())?;
+        check_curve(self.curve.is_some(), self.transport)?;
+        if self.token.as_deref().is_some_and(str::is_empty) {
+            return Err(invalid("token", "不能为空"));
+        }
+        Ok(())
+    }
+
+    /// 从TOML字符串加载
+    pub fn from_toml_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(s)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从JSON字符串加载
+    pub fn from_json_str(s: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(s)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从配置文件加载(按扩展名识别.toml/.json)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let config: Self = load_file(path.as_ref())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从环境变量加载(以默认配置为基础)
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::default().with_env_overrides()
+    }
+
+    /// 使用`ZERG_DRONE_*`环境变量覆盖当前配置
+    pub fn with_env_overrides(self) -> Result<Self, ConfigError> {
+        let config: Self = apply_env(&self, DRONE_ENV_PREFIX)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// DroneConfig构建器
+#[derive(Debug, Default)]
+pub struct DroneConfigBuilder {
+    config: DroneConfig,
+}
+
+impl DroneConfigBuilder {
+    /// 设置心跳发送间隔
+    pub fn heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.config.heartbeat_interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    /// 设置心跳超时时间
+    pub fn heartbeat_timeout(mut self, timeout: Duration) -> Self {
+        self.config.heartbeat_timeout_ms = timeout.as_millis() as u64;
+        self
+    }
+
+    /// 设置熔断阈值
+    pub fn circuit_breaker_threshold(mut self, count: u32) -> Self {
+        self.config.circuit_breaker_threshold = count;
+        self
+    }
+
+    /// 设置最大并发任务数
+    pub fn max_tasks(mut self, max_tasks: u32) -> Self {
+        self.config.max_tasks = max_tasks;
+        self
+    }
+
+    /// 设置任务队列容量
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.config.queue_capacity = capacity;
+        self
+    }
+
+    /// 设置任务执行线程数
+    pub fn worker_threads(mut self, threads: usize) -> Self {
+        self.config.worker_threads = threads;
+        self
+    }
+
+    /// 设置节点ID
+    pub fn worker_id(mut self, id: impl Into<String>) -> Self {
+        self.config.worker_id = Some(id.into());
+        self
+    }
+
+    /// 设置节点ID持久化文件
+    pub fn identity_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.identity_path = Some(path.into());
+        self
+    }
+
+    /// 设置传输实现
+    pub fn transport(mut self, kind: TransportKind) -> Self {
+        self.config.transport = kind;
+        self
+    }
+
+    /// 启用Prometheus指标导出
+    pub fn metrics_addr(mut self, addr: impl Into<String>) -> Self {
+        self.config.metrics_addr = Some(addr.into());
+        self
+    }
+
+    /// 将span导出到OTLP/HTTP收集器
+    pub fn otlp_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.config.otlp_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// 启用CURVE加密与认证
+    pub fn curve(mut self, curve: CurveClientConfig) -> Self {
+        self.config.curve = Some(curve);
+        self
+    }
+
+    /// 设置注册令牌
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.config.token = Some(token.into());
+        self
+    }
+
+    /// 校验并生成配置
+    pub fn build(self) -> Result<DroneConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// 按扩展名解析配置文件
+fn load_file<T: DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let content = std::fs::read_to_string(path)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => Ok(toml::from_str(&content)?),
+        Some("json") => Ok(serde_json::from_str(&content)?),
+        other => Err(ConfigError::UnsupportedFormat(other.unwrap_or("").to_string())),
+    }
+}
+
+/// 使用`<prefix><FIELD>`环境变量覆盖配置字段
+fn apply_env<T: Serialize + DeserializeOwned>(config: &T, prefix: &str) -> Result<T, ConfigError> {
+    let mut value = serde_json::to_value(config)?;
+    if let Some(fields) = value.as_object_mut() {
+        for (field, current) in fields.iter_mut() {
+            let key = format!("{}{}", prefix, field.to_uppercase());
+            let Ok(raw) = std::env::var(&key) else { continue };
+            let parsed = serde_json::from_str::<serde_json::Value>(&raw)
+                .unwrap_or_else(|_| serde_json::Value::String(raw.clone()));
+            // 类型必须与原字段一致(均为数字或均为字符串等)，未设置的可选字段除外
+            if !current.is_null() && std::mem::discriminant(current) != std::mem::discriminant(&parsed) {
+                return Err(ConfigError::InvalidEnv { key, value: raw });
+            }
+            *current = parsed;
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// 递归对比JSON值，嵌套字段以`.`连接
+fn diff_values(path: &str, old: &serde_json::Value, new: &serde_json::Value, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            for (key, old_value) in a {
+                let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                if let Some(new_value) = b.get(key) {
+                    diff_values(&field, old_value, new_value, changes);
+                }
+            }
+        }
+        _ if old != new && SECRET_FIELDS.contains(&path) => changes.push(ConfigChange {
+            field: path.to_string(),
+            old: "***".to_string(),
+            new: "***".to_string(),
+        }),
+        _ if old != new => changes.push(ConfigChange {
+            field: path.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        }),
+        _ => {}
+    }
+}
+
+/// 配置文件监视器
+///
+/// 后台线程按固定间隔检查文件修改时间，变化后重新加载并校验，
+/// 结果通过`try_recv`交给持有者(如`DronePool::poll_events`)应用
+pub struct ConfigWatcher {
+    path: PathBuf,
+    receiver: Receiver<Result<PoolConfig, ConfigError>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ConfigWatcher {
+    /// 启动监视线程
+    pub fn spawn(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        let path = path.into();
+        let (sender, receiver) = unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let watch_path = path.clone();
+        let watch_stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            let mut last_modified = modified_time(&watch_path);
+            while !watch_stop.load(Ordering::Relaxed) {
+                thread::sleep(interval);
+                let modified = modified_time(&watch_path);
+                if modified.is_some() && modified != last_modified {
+                    last_modified = modified;
+                    if sender.send(PoolConfig::from_file(&watch_path)).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self { path, receiver, stop }
+    }
+
+    /// 被监视的文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 获取最新一次加载结果(无变化时返回None)
+    pub fn try_recv(&self) -> Option<Result<PoolConfig, ConfigError>> {
+        self.receiver.try_iter().last()
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
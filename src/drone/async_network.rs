@@ -0,0 +1,143 @@
+//! Drone异步网络接口
+//!
+//! `DroneNetwork`运行在独立IO线程中，负责心跳与消息收发；
+//! 收到的消息经tokio通道交给异步调用方，发送请求通过命令通道转交IO线程
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use tokio::sync::mpsc;
+
+use crate::config::DroneConfig;
+use crate::drone::network::{DroneNetwork, NetworkError};
+use crate::drone::runtime::DroneError;
+use crate::proto::zergpool::{Response, Task};
+use crate::ProcessMessage;
+
+/// IO线程单次等待queen消息的时间(ms)
+const IO_POLL_TIMEOUT_MS: i64 = 10;
+
+/// 交给IO线程发送的请求
+enum Outgoing {
+    Register(Vec<String>),
+    Response(Response),
+    CurrentTasks(u32),
+}
+
+/// 异步Drone网络连接
+pub struct AsyncDroneNetwork {
+    worker_id: String,
+    outgoing: Sender<Outgoing>,
+    incoming: mpsc::UnboundedReceiver<ProcessMessage>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<(), NetworkError>>>,
+}
+
+impl AsyncDroneNetwork {
+    /// 按配置连接queen集群并启动IO线程
+    pub fn connect(endpoints: Vec<String>, config: &DroneConfig) -> Result<Self, DroneError> {
+        config.validate()?;
+        let worker_id = config.resolve_worker_id()?;
+        let network = DroneNetwork::connect_config(config, worker_id.clone(), endpoints)?;
+
+        let (outgoing, outgoing_rx) = unbounded();
+        let (incoming_tx, incoming) = mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::Builder::new()
+            .name(format!("zerg-drone-io-{}", worker_id))
+            .spawn(move || io_loop(network, outgoing_rx, incoming_tx, thread_stop))
+            .map_err(|e| DroneError::ThreadPool(e.to_string()))?;
+
+        Ok(Self {
+            worker_id,
+            outgoing,
+            incoming,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// 节点ID
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    /// 注册到queen
+    pub fn register(&self, capabilities: Vec<String>) -> Result<(), NetworkError> {
+        self.send(Outgoing::Register(capabilities))
+    }
+
+    /// 发送任务结果
+    pub fn send_response(&self, response: Response) -> Result<(), NetworkError> {
+        self.send(Outgoing::Response(response))
+    }
+
+    /// 更新心跳上报的当前任务数
+    pub fn set_current_tasks(&self, current: u32) -> Result<(), NetworkError> {
+        self.send(Outgoing::CurrentTasks(current))
+    }
+
+    /// 等待queen的下一条消息，连接关闭后返回`None`
+    pub async fn recv(&mut self) -> Option<ProcessMessage> {
+        self.incoming.recv().await
+    }
+
+    /// 等待下一个任务，连接关闭后返回`None`
+    pub async fn recv_task(&mut self) -> Option<Task> {
+        loop {
+            if let ProcessMessage::Task(task) = self.recv().await? {
+                return Some(task);
+            }
+        }
+    }
+
+    /// 关闭连接并等待IO线程退出
+    pub async fn close(mut self) -> Result<(), NetworkError> {
+        self.stop.store(true, Ordering::Release);
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        tokio::task::spawn_blocking(move || thread.join())
+            .await
+            .map_err(|_| NetworkError::Closed)?
+            .map_err(|_| NetworkError::Closed)?
+    }
+
+    fn send(&self, request: Outgoing) -> Result<(), NetworkError> {
+        self.outgoing.send(request).map_err(|_| NetworkError::Closed)
+    }
+}
+
+impl Drop for AsyncDroneNetwork {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+    }
+}
+
+fn io_loop(
+    mut network: DroneNetwork,
+    outgoing: Receiver<Outgoing>,
+    incoming: mpsc::UnboundedSender<ProcessMessage>,
+    stop: Arc<AtomicBool>,
+) -> Result<(), NetworkError> {
+    let worker_id = network.worker_id().to_string();
+    while !stop.load(Ordering::Acquire) {
+        while let Ok(request) = outgoing.try_recv() {
+            match request {
+                Outgoing::Register(capabilities) => network.register(&worker_id, capabilities)?,
+                Outgoing::Response(response) => network.send_response(&response)?,
+                Outgoing::CurrentTasks(current) => network.set_current_tasks(current),
+            }
+        }
+        network.send_heartbeat()?;
+        if let Some(message) = network.poll_message(IO_POLL_TIMEOUT_MS)? {
+            // 接收端已释放，结束IO线程
+            if incoming.send(message).is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! 内置任务执行器
+//!
+//! - `echo`: 原样返回任务负载(联调与压测)
+//! - `command`: 每个任务启动一次外部命令，负载写入stdin，stdout作为输出
+//!
+//! `zerg-drone`按名称选择执行器；嵌入使用时也可直接实现`TaskExecutor`
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use crate::drone::runtime::{DroneError, TaskExecutor};
+use crate::proto::zergpool::Task;
+
+/// 传给外部命令的任务ID环境变量
+pub const TASK_ID_ENV: &str = "ZERG_TASK_ID";
+
+/// 内置执行器名称
+pub const BUILTIN_EXECUTORS: &[&str] = &["echo", "command"];
+
+/// 原样返回任务负载
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EchoExecutor;
+
+impl TaskExecutor for EchoExecutor {
+    fn execute(&self, task: &Task) -> Result<Vec<u8>, String> {
+        Ok(task.payload.clone())
+    }
+}
+
+/// 外部命令执行器
+///
+/// 任务负载写入stdin，退出码为0时stdout作为任务输出，否则以stderr作为错误信息
+#[derive(Debug, Clone)]
+pub struct CommandExecutor {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandExecutor {
+    pub fn new(program: impl Into<String>, args: Vec<String>) -> Self {
+        Self { program: program.into(), args }
+    }
+}
+
+impl TaskExecutor for CommandExecutor {
+    fn execute(&self, task: &Task) -> Result<Vec<u8>, String> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env(TASK_ID_ENV, &task.id)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("启动 {} 失败: {}", self.program, e))?;
+
+        // 另起线程写入stdin，避免输出过多时双方互相等待
+        let stdin = child.stdin.take();
+        let payload = task.payload.clone();
+        let writer = std::thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(&payload);
+            }
+        });
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        let _ = writer.join();
+
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(format!("{} 退出({}): {}", self.program, output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()))
+        }
+    }
+}
+
+/// 按名称创建内置执行器，`command`执行器需提供命令及参数
+pub fn builtin(name: &str, command: &[String]) -> Result<Arc<dyn TaskExecutor>, DroneError> {
+    match name {
+        "echo" => Ok(Arc::new(EchoExecutor)),
+        "command" => {
+            let (program, args) = command.split_first()
+                .ok_or_else(|| DroneError::InvalidExecutor("command执行器需要指定命令".to_string()))?;
+            Ok(Arc::new(CommandExecutor::new(program.clone(), args.to_vec())))
+        }
+        other => Err(DroneError::InvalidExecutor(format!("未知执行器: {}", other))),
+    }
+}
@@ -1,19 +1,25 @@
 //! 工蜂节点心跳机制实现
 //! 
 //! 严格遵循docs/架构设计.md第206-215行规范
+//!
+//! queen只接受节点注册时所用连接发来的心跳：本模块的socket以节点ID为连接身份，
+//! 需先通过`register`在同一连接上注册，不能与同ID的`DroneNetwork`同时使用
 
 use std::time::{Duration, Instant};
 use tokio::time;
 use zmq::{Context, Socket, DEALER};
-use prost::Message;
 use sysinfo::{System, SystemExt, CpuExt};
-use crate::ProcessId;
-use crate::proto::zergpool::{Heartbeat, HealthState};
+use crate::{ProcessId, ProcessMessage};
+use crate::config::DroneConfig;
+use crate::proto::zergpool::{ControlKind, Heartbeat, HealthState, Registration};
+use crate::version;
 
 /// 心跳管理器
 pub struct HeartbeatManager {
     worker_id: ProcessId,
     zmq_socket: Socket,
+    endpoints: Vec<String>,
+    current: usize,
     last_recv_time: Instant,
     last_send_time: Instant,
     timeout_count: u32,
@@ -22,37 +28,96 @@ pub struct HeartbeatManager {
     current_tasks: u32,
     max_tasks: u32,
     last_latency: u32,
+    interval: Duration,
+    timeout: Duration,
+    breaker_threshold: u32,
+    token: Option<String>,
+    /// 最近一次注册消息(queen未识别节点时重发)
+    registration: Option<Registration>,
 }
 
 impl HeartbeatManager {
-    /// 创建新的心跳管理器
+    /// 创建新的心跳管理器(使用默认心跳间隔与超时)
     pub fn new(worker_id: ProcessId, zmq_endpoint: &str, max_tasks: u32) -> Result<Self, zmq::Error> {
+        let config = DroneConfig {
+            max_tasks,
+            ..DroneConfig::default()
+        };
+        Self::with_config(worker_id, zmq_endpoint, &config)
+    }
+
+    /// 使用指定配置创建心跳管理器
+    pub fn with_config(worker_id: ProcessId, zmq_endpoint: &str, config: &DroneConfig) -> Result<Self, zmq::Error> {
+        Self::with_endpoints(worker_id, vec![zmq_endpoint.to_string()], config)
+    }
+
+    /// 使用queen集群地址列表创建心跳管理器(超时或收到NOT_LEADER时切换queen)
+    pub fn with_endpoints(worker_id: ProcessId, endpoints: Vec<String>, config: &DroneConfig) -> Result<Self, zmq::Error> {
+        let first = endpoints.first().ok_or(zmq::Error::EINVAL)?;
         let ctx = Context::new();
         let socket = ctx.socket(DEALER)?;
-        socket.connect(zmq_endpoint)?;
+        // 连接身份即节点ID，queen据此核对心跳来源
+        socket.set_identity(worker_id.as_bytes())?;
+        socket.connect(first)?;
 
         Ok(Self {
             worker_id,
             zmq_socket: socket,
+            endpoints,
+            current: 0,
             last_recv_time: Instant::now(),
             last_send_time: Instant::now(),
             timeout_count: 0,
             health_state: HealthState::Healthy,
             sys: System::new_all(),
             current_tasks: 0,
-            max_tasks,
+            max_tasks: config.max_tasks,
             last_latency: 10,
+            interval: config.heartbeat_interval(),
+            timeout: config.heartbeat_timeout(),
+            breaker_threshold: config.circuit_breaker_threshold,
+            token: config.token.clone(),
+            registration: None,
         })
     }
 
+    /// 在心跳连接上注册节点，queen未识别节点时自动重新注册
+    pub fn register(&mut self, capabilities: Vec<String>) -> Result<(), HeartbeatError> {
+        let reg = Registration {
+            worker_id: self.worker_id.clone(),
+            max_threads: self.max_tasks as i32,
+            version: version::CRATE_VERSION.to_string(),
+            capabilities,
+            token: self.token.clone().unwrap_or_default(),
+            protocol_version: version::PROTOCOL_VERSION.to_string(),
+        };
+        self.send_message(ProcessMessage::Registration(reg.clone()))?;
+        self.registration = Some(reg);
+        Ok(())
+    }
+
+    /// 当前健康状态
+    pub fn health_state(&self) -> HealthState {
+        self.health_state
+    }
+
+    fn send_message(&mut self, message: ProcessMessage) -> Result<(), HeartbeatError> {
+        let buf = message.encode_envelope();
+        self.zmq_socket.send("", zmq::SNDMORE)?;
+        self.zmq_socket.send("", zmq::SNDMORE)?;
+        self.zmq_socket.send(&buf, 0)?;
+        Ok(())
+    }
+
     /// 启动心跳循环
     pub async fn start(&mut self) -> Result<(), HeartbeatError> {
-        let mut interval = time::interval(Duration::from_secs(3)); // 调整为3秒
+        let mut interval = time::interval(self.interval);
         
         loop {
             interval.tick().await;
             
             self.send_heartbeat().await?;
+            self.drain_replies()?;
             
             if self.check_timeout() {
                 return Err(HeartbeatError::Timeout);
@@ -77,14 +142,68 @@ impl HeartbeatManager {
             max_tasks: self.max_tasks,
         };
         
-        let mut buf = Vec::new();
-        msg.encode(&mut buf)?;
         self.last_send_time = Instant::now();
-        self.zmq_socket.send(&buf, 0)?;
-        
+        self.send_message(ProcessMessage::Heartbeat(msg))
+    }
+
+    /// 非阻塞读取queen的控制回复
+    fn drain_replies(&mut self) -> Result<(), HeartbeatError> {
+        loop {
+            let frames = match self.zmq_socket.recv_multipart(zmq::DONTWAIT) {
+                Ok(frames) => frames,
+                Err(zmq::Error::EAGAIN) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            };
+            let control = match frames.last().map(|data| ProcessMessage::decode_envelope(data)) {
+                Some(Ok(ProcessMessage::Control(control))) => control,
+                Some(Ok(ProcessMessage::RegistrationRejected(rejected))) => {
+                    return Err(HeartbeatError::Rejected(rejected.message));
+                }
+                _ => continue,
+            };
+            match control.kind() {
+                ControlKind::HeartbeatAck => self.handle_response(),
+                ControlKind::NotLeader => {
+                    let hint = Some(control.leader_endpoint.as_str()).filter(|e| !e.is_empty());
+                    self.failover(hint)?;
+                }
+                ControlKind::UnknownWorker => match self.registration.clone() {
+                    Some(reg) => {
+                        tracing::warn!(worker_id = %self.worker_id, "queen未识别节点，重新注册");
+                        self.send_message(ProcessMessage::Registration(reg))?;
+                    }
+                    None => {
+                        tracing::warn!(worker_id = %self.worker_id, "queen未识别节点，需先调用register注册");
+                        self.health_state = HealthState::Unhealthy;
+                    }
+                },
+                ControlKind::Evicted => {
+                    tracing::warn!(worker_id = %self.worker_id, reason = %control.reason, "节点已被移出进程池");
+                    self.health_state = HealthState::CircuitBreaker;
+                }
+            }
+        }
+    }
+
+    /// 切换到其他queen(优先使用leader提示地址)
+    fn failover(&mut self, leader_hint: Option<&str>) -> Result<(), HeartbeatError> {
+        let next = leader_hint
+            .and_then(|hint| self.endpoints.iter().position(|e| e == hint))
+            .unwrap_or((self.current + 1) % self.endpoints.len());
+        if next != self.current {
+            self.zmq_socket.disconnect(&self.endpoints[self.current])?;
+            self.zmq_socket.connect(&self.endpoints[next])?;
+            tracing::warn!(worker_id = %self.worker_id, from = %self.endpoints[self.current], to = %self.endpoints[next], "心跳切换queen");
+            self.current = next;
+        }
         Ok(())
     }
 
+    /// 当前连接的queen地址
+    pub fn current_endpoint(&self) -> &str {
+        &self.endpoints[self.current]
+    }
+
     /// 处理心跳响应
     pub fn handle_response(&mut self) {
         let rtt = self.last_send_time.elapsed().as_millis() as u32;
@@ -92,11 +211,15 @@ impl HeartbeatManager {
         self.last_recv_time = Instant::now();
     }
 
-    /// 检查超时(9秒超时，连续3次触发熔断)
+    /// 检查超时(默认9秒超时，连续3次触发熔断)
     fn check_timeout(&mut self) -> bool {
-        if self.last_recv_time.elapsed() > Duration::from_secs(9) { // 调整为9秒
+        if self.last_recv_time.elapsed() > self.timeout {
             self.timeout_count += 1;
-            if self.timeout_count >= 3 {
+            // 有备用queen时先尝试切换，切换后重新计时
+            if self.endpoints.len() > 1 && self.failover(None).is_ok() {
+                self.last_recv_time = Instant::now();
+            }
+            if self.timeout_count >= self.breaker_threshold {
                 self.health_state = HealthState::CircuitBreaker;
                 return true;
             }
@@ -125,4 +248,6 @@ pub enum HeartbeatError {
     Timeout,
     #[error("节点已熔断")]
     CircuitBreaker,
+    #[error("注册被拒绝: {0}")]
+    Rejected(String),
 }
\ No newline at end of file
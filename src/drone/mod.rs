@@ -1,9 +1,13 @@
 //! 工蜂(Worker)节点实现模块
 
+pub mod async_network;
+pub mod executor;
 pub mod heartbeat;
 pub mod network;
+pub mod runtime;
 pub mod task_queue;
 
-pub use network::get_worker_id;
+pub use async_network::AsyncDroneNetwork;
 pub use heartbeat::HeartbeatManager;
-pub use task_queue::{TaskQueue, CallbackHandler, set_callback_handler};
\ No newline at end of file
+pub use runtime::{Drone, DroneBuilder, DroneError, DroneHandle, TaskExecutor};
+pub use task_queue::{AsyncCallbackHandler, CallbackHandler, CallbackId, TaskQueue};
\ No newline at end of file
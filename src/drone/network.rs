@@ -1,25 +1,19 @@
-//! Drone端网络通信模块 - DEALER socket实现
+//! Drone端网络通信模块 - DEALER socket实现(可替换为原生TCP传输)
 
 use std::time::{Duration, Instant};
 use sysinfo::{System, CpuExt, SystemExt};
 use bincode;
 use thiserror::Error;
-use zmq::{Context, Socket};
 use uuid::Uuid;
 
-use crate::proto::zergpool::{Heartbeat, Registration, Response, Task};
+use crate::config::DroneConfig;
+use crate::transport::curve::CurveClient;
+use crate::transport::{self, ClientTransport, TransportError, TransportKind};
+use crate::proto::zergpool::{ControlKind, HealthState, Heartbeat, Registration, RegistrationAccepted, RejectReason, Response, Task};
+use crate::version::{self, Version};
 use crate::ProcessMessage;
-use prost::Message;
 use std::env;
 use std::thread;
-use std::sync::OnceLock;
-
-static WORKER_ID: OnceLock<String> = OnceLock::new();
-
-/// 获取当前worker ID
-pub fn get_worker_id() -> Option<&'static String> {
-    WORKER_ID.get()
-}
 
 /// 网络通信错误类型
 #[derive(Error, Debug)]
@@ -32,108 +26,333 @@ pub enum NetworkError {
     Encode(#[from] prost::EncodeError),
     #[error("Serialization error: {0}")]
     Serialize(#[from] Box<bincode::ErrorKind>),
+    #[error("No queen endpoint configured")]
+    NoEndpoints,
+    #[error("Connection closed")]
+    Closed,
+    #[error("Transport error: {0}")]
+    Transport(#[from] TransportError),
+    #[error("Evicted by queen: {0}")]
+    Evicted(String),
+    #[error("Registration rejected by queen {queen_version} ({reason:?}): {message}")]
+    Rejected {
+        reason: RejectReason,
+        message: String,
+        queen_version: String,
+    },
+    #[error("Incompatible queen protocol version {remote} (local {local})")]
+    IncompatibleProtocol { local: String, remote: String },
+    #[error("Worker ID {requested} does not match connection identity {connected}")]
+    WorkerIdMismatch { connected: String, requested: String },
 }
 
+/// 未收到queen心跳确认时触发故障转移的默认时间
+const DEFAULT_FAILOVER_TIMEOUT: Duration = Duration::from_secs(9);
+
 /// Drone网络连接
 pub struct DroneNetwork {
-    socket: Socket, // DEALER socket
+    transport: Box<dyn ClientTransport>, // DEALER socket或原生TCP连接
+    endpoints: Vec<String>, // 候选queen地址
+    current: usize, // 当前连接的queen下标
+    registration: Option<Registration>, // 故障转移后重新注册使用
+    token: Option<String>, // 注册令牌
+    queen_protocol: Option<String>, // 注册成功后queen告知的协议版本
+    last_ack: Instant, // 最近一次收到queen确认的时间
+    failover_timeout: Duration,
     last_heartbeat: Instant,
+    heartbeat_interval: Duration,
     id: String,     // Worker ID
     sys: System,    // 系统监控
     current_tasks: u32, // 当前任务数
+    max_tasks: u32, // 最大并发任务数
 }
 
 impl DroneNetwork {
     /// 创建新的Drone网络连接
     pub fn connect(queen_addr: &str, port: u16) -> Result<Self, NetworkError> {
-        let ctx = Context::new();
-        let socket = ctx.socket(zmq::DEALER)?;
-        socket.connect(&format!("tcp://{}:{}", queen_addr, port))?;
+        Self::connect_cluster(vec![format!("tcp://{}:{}", queen_addr, port)])
+    }
+
+    /// 连接queen集群(按顺序尝试，leader不可用时自动切换到下一个地址)
+    pub fn connect_cluster(endpoints: Vec<String>) -> Result<Self, NetworkError> {
+        Self::connect_as(Uuid::new_v4().to_string(), endpoints)
+    }
+
+    /// 以指定节点ID连接queen集群
+    ///
+    /// 节点ID同时设置为socket身份帧，queen端ROUTER身份与节点ID一致，
+    /// drone重启后使用相同ID即可被识别为同一节点
+    pub fn connect_as(worker_id: impl Into<String>, endpoints: Vec<String>) -> Result<Self, NetworkError> {
+        Self::connect_with(TransportKind::Zmq, worker_id, endpoints)
+    }
+
+    /// 使用指定传输实现连接queen集群
+    pub fn connect_with(kind: TransportKind, worker_id: impl Into<String>, endpoints: Vec<String>) -> Result<Self, NetworkError> {
+        Self::connect_secure(kind, worker_id, endpoints, None)
+    }
+
+    /// 按drone配置连接queen集群(传输实现、CURVE密钥、心跳与故障转移参数)
+    pub fn connect_config(config: &DroneConfig, worker_id: impl Into<String>, endpoints: Vec<String>) -> Result<Self, NetworkError> {
+        let curve = config.curve.as_ref().map(CurveClient::from_config).transpose()?;
+        let mut network = Self::connect_secure(config.transport, worker_id, endpoints, curve.as_ref())?;
+        network.configure(config);
+        Ok(network)
+    }
+
+    /// 连接queen集群，指定`curve`时使用CURVE握手(故障转移后沿用同一密钥)
+    pub fn connect_secure(
+        kind: TransportKind,
+        worker_id: impl Into<String>,
+        endpoints: Vec<String>,
+        curve: Option<&CurveClient>,
+    ) -> Result<Self, NetworkError> {
+        let first = endpoints.first().ok_or(NetworkError::NoEndpoints)?;
+        let id = worker_id.into();
+        let mut transport = transport::client_secure(kind, &id, curve)?;
+        transport.connect(first)?;
 
-        let id = Uuid::new_v4().to_string();
-        WORKER_ID.set(id.clone()).expect("Worker ID already set");
-        
         Ok(Self {
-            socket,
+            transport,
+            endpoints,
+            current: 0,
+            registration: None,
+            token: None,
+            queen_protocol: None,
+            last_ack: Instant::now(),
+            failover_timeout: DEFAULT_FAILOVER_TIMEOUT,
             last_heartbeat: Instant::now(),
+            heartbeat_interval: Duration::from_secs(3),
             id,
             sys: <System as SystemExt>::new_all(), // 完全限定路径调用
             current_tasks: 0,
+            max_tasks: thread::available_parallelism().map_or(4, |n| n.get() as u32),
         })
     }
 
+    /// 节点ID
+    pub fn worker_id(&self) -> &str {
+        &self.id
+    }
+
+    /// 当前连接的queen地址
+    pub fn current_endpoint(&self) -> &str {
+        &self.endpoints[self.current]
+    }
+
+    /// 协商得到的queen协议版本(收到注册回复前为空，旧版queen不回复)
+    pub fn queen_protocol_version(&self) -> Option<&str> {
+        self.queen_protocol.as_deref()
+    }
+
+    /// 应用drone配置(心跳间隔、故障转移超时、最大任务数与注册令牌)
+    pub fn configure(&mut self, config: &DroneConfig) {
+        self.heartbeat_interval = config.heartbeat_interval();
+        self.failover_timeout = config.heartbeat_timeout();
+        self.max_tasks = config.max_tasks;
+        self.token = config.token.clone();
+    }
+
+    /// 设置注册令牌(在`register`之前调用)
+    pub fn set_token(&mut self, token: impl Into<String>) {
+        self.token = Some(token.into());
+    }
+
+    /// 更新心跳上报的当前任务数
+    pub fn set_current_tasks(&mut self, current: u32) {
+        self.current_tasks = current;
+    }
+
+    /// 设置心跳发送间隔
+    pub fn set_heartbeat_interval(&mut self, interval: Duration) {
+        self.heartbeat_interval = interval;
+    }
+
+    /// 设置未收到确认时触发故障转移的时间
+    pub fn set_failover_timeout(&mut self, timeout: Duration) {
+        self.failover_timeout = timeout;
+    }
+
+    /// 切换到其他queen并重新注册
+    ///
+    /// 优先使用备用queen告知的leader地址，否则轮换到下一个候选地址
+    pub fn failover(&mut self, leader_hint: Option<&str>) -> Result<(), NetworkError> {
+        let next = leader_hint
+            .and_then(|hint| self.endpoints.iter().position(|e| e == hint))
+            .unwrap_or((self.current + 1) % self.endpoints.len());
+        if next != self.current {
+            let previous = self.endpoints[self.current].clone();
+            self.transport.disconnect(&previous)?;
+            self.transport.connect(&self.endpoints[next])?;
+            self.current = next;
+            tracing::warn!(worker_id = %self.id, from = %previous, to = %self.endpoints[next], "queen故障转移");
+        }
+        self.last_ack = Instant::now();
+        self.queen_protocol = None;
+        self.reregister()
+    }
+
+    /// 等待queen消息(超时毫秒，-1为阻塞)
+    ///
+    /// 控制消息在内部处理：心跳确认刷新存活时间，NOT_LEADER触发故障转移，
+    /// UNKNOWN_WORKER(queen重启)时以相同ID重新注册；长时间未收到确认时同样切换queen；
+    /// 被管理员移出进程池(EVICTED)时返回`NetworkError::Evicted`，
+    /// 注册被拒绝时返回`NetworkError::Rejected`；注册成功回复(协议握手)对调用方透明，
+    /// 其中的queen协议版本不在兼容矩阵内时返回`NetworkError::IncompatibleProtocol`
+    pub fn poll_message(&mut self, mut timeout_ms: i64) -> Result<Option<ProcessMessage>, NetworkError> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms.max(0) as u64);
+        loop {
+            let Some(data) = self.transport.recv(timeout_ms)? else {
+                if self.endpoints.len() > 1 && self.last_ack.elapsed() > self.failover_timeout {
+                    self.failover(None)?;
+                }
+                return Ok(None);
+            };
+            self.last_ack = Instant::now();
+            match ProcessMessage::decode_envelope(&data)? {
+                ProcessMessage::Control(control) => {
+                    match control.kind() {
+                        ControlKind::HeartbeatAck => {}
+                        ControlKind::NotLeader => {
+                            let hint = Some(control.leader_endpoint.as_str()).filter(|e| !e.is_empty());
+                            self.failover(hint)?;
+                        }
+                        ControlKind::UnknownWorker => self.reregister()?,
+                        ControlKind::Evicted => return Err(NetworkError::Evicted(control.reason)),
+                    }
+                    return Ok(None);
+                }
+                // 握手完成后在剩余时间内继续等待
+                ProcessMessage::RegistrationAccepted(accepted) => {
+                    self.complete_handshake(accepted)?;
+                    if timeout_ms >= 0 {
+                        timeout_ms = deadline.saturating_duration_since(Instant::now()).as_millis() as i64;
+                    }
+                }
+                ProcessMessage::RegistrationRejected(rejected) => {
+                    tracing::error!(
+                        worker_id = %self.id,
+                        reason = rejected.reason().as_str_name(),
+                        queen_version = %rejected.queen_version,
+                        protocol_version = %rejected.protocol_version,
+                        message = %rejected.message,
+                        "注册被queen拒绝"
+                    );
+                    return Err(NetworkError::Rejected {
+                        reason: rejected.reason(),
+                        message: rejected.message,
+                        queen_version: rejected.queen_version,
+                    });
+                }
+                message => return Ok(Some(message)),
+            }
+        }
+    }
+
+    /// 按兼容矩阵检查queen协议版本并记录
+    fn complete_handshake(&mut self, accepted: RegistrationAccepted) -> Result<(), NetworkError> {
+        let compatible = version::parse_protocol(&accepted.protocol_version)
+            .is_ok_and(|remote| version::protocol_compatible(&Version::protocol(), &remote));
+        if !compatible {
+            tracing::error!(worker_id = %self.id, protocol_version = %accepted.protocol_version, "queen协议版本不兼容");
+            return Err(NetworkError::IncompatibleProtocol {
+                local: version::PROTOCOL_VERSION.to_string(),
+                remote: accepted.protocol_version,
+            });
+        }
+        tracing::debug!(
+            worker_id = %self.id,
+            protocol_version = %accepted.protocol_version,
+            queen_version = %accepted.queen_version,
+            "注册成功"
+        );
+        self.queen_protocol = Some(accepted.protocol_version);
+        Ok(())
+    }
+
+    /// 以上次注册的信息重新注册
+    pub fn reregister(&mut self) -> Result<(), NetworkError> {
+        if let Some(reg) = self.registration.clone() {
+            tracing::info!(worker_id = %reg.worker_id, endpoint = %self.endpoints[self.current], "重新注册");
+            self.send_message(ProcessMessage::Registration(reg))?;
+        }
+        Ok(())
+    }
+
+    /// 发送信封消息
+    fn send_message(&mut self, message: ProcessMessage) -> Result<usize, NetworkError> {
+        let buf = message.encode_envelope();
+        self.transport.send(&buf)?;
+        Ok(buf.len())
+    }
+
     /// 发送注册消息(四帧格式)
-    pub fn register(&self, worker_id: &str, capabilities: Vec<String>) -> Result<(), NetworkError> {
+    ///
+    /// `worker_id`必须与连接时的节点ID(socket身份帧)一致，否则返回`NetworkError::WorkerIdMismatch`
+    pub fn register(&mut self, worker_id: &str, capabilities: Vec<String>) -> Result<(), NetworkError> {
+        if worker_id != self.id {
+            return Err(NetworkError::WorkerIdMismatch {
+                connected: self.id.clone(),
+                requested: worker_id.to_string(),
+            });
+        }
         let reg = Registration {
             worker_id: worker_id.to_string(),
             max_threads: thread::available_parallelism().map_or(4, |n| n.get() as i32),
-            version: env!("CARGO_PKG_VERSION").to_string(),
+            version: version::CRATE_VERSION.to_string(),
             capabilities,
+            token: self.token.clone().unwrap_or_default(),
+            protocol_version: version::PROTOCOL_VERSION.to_string(),
         };
-        let mut buf = Vec::new();
-        reg.encode(&mut buf)?;
-        println!("[DRONE NET] 发送注册消息: {}字节", buf.len());
-        
-        // 三帧格式: 两空帧 + 数据帧 (ROUTER会自动添加身份帧)
-        self.socket.send("", zmq::SNDMORE)?; // 空帧1
-        self.socket.send("", zmq::SNDMORE)?; // 空帧2
-        self.socket.send(&buf, 0)?; // 数据帧
+        self.registration = Some(reg.clone());
+        let bytes = self.send_message(ProcessMessage::Registration(reg))?;
+        tracing::debug!(worker_id = %self.id, endpoint = %self.endpoints[self.current], bytes, "已发送注册消息");
         Ok(())
     }
 
-    /// 发送心跳(3秒间隔, 四帧格式)
+    /// 发送心跳(默认3秒间隔)
     pub fn send_heartbeat(&mut self) -> Result<(), NetworkError> {
-        if self.last_heartbeat.elapsed() > Duration::from_secs(3) {
+        if self.last_heartbeat.elapsed() >= self.heartbeat_interval {
             <System as SystemExt>::refresh_all(&mut self.sys);
             
             let hb = Heartbeat {
                 worker_id: self.id.clone(),
                 timestamp: chrono::Utc::now().timestamp(),
-                state: 0, // Healthy
+                // 长时间未收到queen确认时上报为不健康
+                state: if self.last_ack.elapsed() > self.failover_timeout {
+                    HealthState::Unhealthy as i32
+                } else {
+                    HealthState::Healthy as i32
+                },
                 cpu_usage: <System as SystemExt>::global_cpu_info(&self.sys).cpu_usage() / 100.0,
                 mem_usage: <System as SystemExt>::used_memory(&self.sys) as f32 /
                           <System as SystemExt>::total_memory(&self.sys) as f32,
                 net_latency: 10, // 默认值，实际由heartbeat模块计算
                 current_tasks: self.current_tasks,
-                max_tasks: thread::available_parallelism().map_or(4, |n| n.get() as u32),
+                max_tasks: self.max_tasks,
             };
             
-            let mut buf = Vec::new();
-            hb.encode(&mut buf)?;
-            println!("[DRONE NET] 发送心跳: {}字节", buf.len());
-            
-            // 三帧格式: 两空帧 + 数据帧 (ROUTER会自动添加身份帧)
-            self.socket.send("", zmq::SNDMORE)?; // 空帧1
-            self.socket.send("", zmq::SNDMORE)?; // 空帧2
-            self.socket.send(&buf, 0)?; // 数据帧
+            let bytes = self.send_message(ProcessMessage::Heartbeat(hb))?;
+            tracing::trace!(worker_id = %self.id, bytes, "已发送心跳");
             self.last_heartbeat = Instant::now();
         }
         Ok(())
     }
 
-    /// 接收任务
-    pub fn recv_task(&self) -> Result<Option<Task>, NetworkError> {
-        if let Ok(msg) = self.socket.recv_bytes(0) {
-            if let ProcessMessage::Task(task) = bincode::deserialize(&msg)? {
-                Ok(Some(task))
-            } else {
-                Ok(None)
+    /// 接收任务(阻塞直到收到queen发来的非控制消息)
+    pub fn recv_task(&mut self) -> Result<Option<Task>, NetworkError> {
+        loop {
+            match self.poll_message(-1)? {
+                Some(ProcessMessage::Task(task)) => return Ok(Some(task)),
+                Some(_) => return Ok(None),
+                None => continue,
             }
-        } else {
-            Ok(None)
         }
     }
 
     /// 发送任务结果(四帧格式)
-    pub fn send_response(&self, response: &Response) -> Result<(), NetworkError> {
-        let mut buf = Vec::new();
-        response.encode(&mut buf)?;
-        println!("[DRONE NET] 发送响应: {}字节", buf.len());
-        
-        // 三帧格式: 两空帧 + 数据帧 (ROUTER会自动添加身份帧)
-        self.socket.send("", zmq::SNDMORE)?; // 空帧1
-        self.socket.send("", zmq::SNDMORE)?; // 空帧2
-        self.socket.send(&buf, 0)?; // 数据帧
+    pub fn send_response(&mut self, response: &Response) -> Result<(), NetworkError> {
+        let bytes = self.send_message(ProcessMessage::TaskResponse(response.clone()))?;
+        tracing::debug!(worker_id = %response.worker_id, task_id = %response.task_id, bytes, "已发送任务结果");
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,315 @@
+//! Drone运行时
+//!
+//! 将网络连接、心跳与任务执行整合在同一个事件循环中：
+//! 单一连接收发所有消息，执行中的任务数实时反映到心跳上报。
+//! 任务元数据中的`traceparent`作为执行span的父上下文，结果回传在其下的回传span中完成
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use thiserror::Error;
+use tracing::Span;
+
+use crate::config::{ConfigError, DroneConfig};
+use crate::drone::network::{DroneNetwork, NetworkError};
+use crate::exporter::{self, ExporterError, MetricsServer};
+use crate::proto::zergpool::{response, Response, Task};
+use crate::telemetry::{self, TelemetryError};
+use crate::ProcessMessage;
+
+/// 每轮事件循环等待queen消息的时间(ms)
+const POLL_TIMEOUT_MS: i64 = 50;
+
+/// 运行时错误类型
+#[derive(Error, Debug)]
+pub enum DroneError {
+    #[error("网络错误: {0}")]
+    Network(#[from] NetworkError),
+    #[error("配置错误: {0}")]
+    Config(#[from] ConfigError),
+    #[error("任务线程池创建失败: {0}")]
+    ThreadPool(String),
+    #[error("未设置任务执行器")]
+    MissingExecutor,
+    #[error("执行器配置无效: {0}")]
+    InvalidExecutor(String),
+    #[error("运行时线程异常退出: {0}")]
+    Join(String),
+    #[error("指标导出错误: {0}")]
+    Exporter(#[from] ExporterError),
+    #[error("追踪导出错误: {0}")]
+    Telemetry(#[from] TelemetryError),
+}
+
+/// 任务执行器
+///
+/// 返回`Ok`时作为任务输出回传queen，返回`Err`或panic时作为错误信息回传并触发queen端重试；
+/// 执行时处于`task.execute` span中，任务元数据的`traceparent`已替换为该span的上下文
+pub trait TaskExecutor: Send + Sync + 'static {
+    fn execute(&self, task: &Task) -> Result<Vec<u8>, String>;
+}
+
+impl<F> TaskExecutor for F
+where
+    F: Fn(&Task) -> Result<Vec<u8>, String> + Send + Sync + 'static,
+{
+    fn execute(&self, task: &Task) -> Result<Vec<u8>, String> {
+        self(task)
+    }
+}
+
+/// Drone运行时构建器
+pub struct DroneBuilder {
+    endpoints: Vec<String>,
+    config: DroneConfig,
+    capabilities: Vec<String>,
+    executor: Option<Arc<dyn TaskExecutor>>,
+}
+
+impl DroneBuilder {
+    /// 追加备用queen地址(用于高可用故障转移)
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoints.push(endpoint.into());
+        self
+    }
+
+    /// 设置drone配置
+    pub fn config(mut self, config: DroneConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// 设置节点能力标签
+    pub fn capabilities(mut self, capabilities: Vec<String>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// 设置任务执行器
+    pub fn executor(mut self, executor: impl TaskExecutor) -> Self {
+        self.executor = Some(Arc::new(executor));
+        self
+    }
+
+    /// 设置共享的任务执行器(如`executor::builtin`按名称创建的执行器)
+    pub fn shared_executor(mut self, executor: Arc<dyn TaskExecutor>) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// 建立连接并生成运行时(尚未注册)
+    pub fn build(self) -> Result<Drone, DroneError> {
+        let executor = self.executor.ok_or(DroneError::MissingExecutor)?;
+        self.config.validate()?;
+        let worker_id = self.config.resolve_worker_id()?;
+
+        let network = DroneNetwork::connect_config(&self.config, worker_id, self.endpoints)?;
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.config.worker_threads)
+            .build()
+            .map_err(|e| DroneError::ThreadPool(e.to_string()))?;
+        let (result_sender, result_receiver) = unbounded();
+        let metrics_server = self.config.metrics_addr.as_deref().map(exporter::serve).transpose()?;
+        if let Some(endpoint) = self.config.otlp_endpoint.as_deref() {
+            telemetry::export_to("zerg-drone", endpoint)?;
+        }
+
+        Ok(Drone {
+            network,
+            pool,
+            executor,
+            capabilities: self.capabilities,
+            active_tasks: Arc::new(AtomicU32::new(0)),
+            stop: Arc::new(AtomicBool::new(false)),
+            result_sender,
+            result_receiver,
+            metrics_server,
+        })
+    }
+
+    /// 建立连接并在当前线程运行，直到通过`DroneHandle::stop`停止
+    pub fn run(self) -> Result<(), DroneError> {
+        self.build()?.run()
+    }
+}
+
+/// 运行时停止句柄
+#[derive(Debug, Clone)]
+pub struct DroneHandle {
+    stop: Arc<AtomicBool>,
+    active_tasks: Arc<AtomicU32>,
+}
+
+impl DroneHandle {
+    /// 请求停止事件循环(执行中的任务结果仍会发送)
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Release);
+    }
+
+    /// 当前执行中的任务数
+    pub fn active_tasks(&self) -> u32 {
+        self.active_tasks.load(Ordering::Acquire)
+    }
+}
+
+/// Drone运行时
+pub struct Drone {
+    network: DroneNetwork,
+    pool: rayon::ThreadPool,
+    executor: Arc<dyn TaskExecutor>,
+    capabilities: Vec<String>,
+    active_tasks: Arc<AtomicU32>,
+    stop: Arc<AtomicBool>,
+    result_sender: Sender<(Response, Span)>,
+    result_receiver: Receiver<(Response, Span)>,
+    metrics_server: Option<MetricsServer>,
+}
+
+impl Drone {
+    /// 创建运行时构建器
+    pub fn builder(queen_endpoint: impl Into<String>) -> DroneBuilder {
+        DroneBuilder {
+            endpoints: vec![queen_endpoint.into()],
+            config: DroneConfig::default(),
+            capabilities: Vec::new(),
+            executor: None,
+        }
+    }
+
+    /// 节点ID
+    pub fn worker_id(&self) -> &str {
+        self.network.worker_id()
+    }
+
+    /// Prometheus指标服务实际绑定的地址(配置了`metrics_addr`时存在)
+    pub fn metrics_addr(&self) -> Option<std::net::SocketAddr> {
+        self.metrics_server.as_ref().map(|server| server.local_addr())
+    }
+
+    /// 获取停止句柄
+    pub fn handle(&self) -> DroneHandle {
+        DroneHandle {
+            stop: Arc::clone(&self.stop),
+            active_tasks: Arc::clone(&self.active_tasks),
+        }
+    }
+
+    /// 注册并运行事件循环：接收任务、定时心跳、回传结果
+    pub fn run(mut self) -> Result<(), DroneError> {
+        let worker_id = self.worker_id().to_string();
+        self.network.register(&worker_id, self.capabilities.clone())?;
+        tracing::info!(worker_id = %worker_id, endpoint = %self.network.current_endpoint(), "drone已启动");
+
+        while !self.stop.load(Ordering::Acquire) {
+            self.network.set_current_tasks(self.active_tasks.load(Ordering::Acquire));
+            self.network.send_heartbeat()?;
+
+            if let Some(ProcessMessage::Task(task)) = self.network.poll_message(POLL_TIMEOUT_MS)? {
+                self.spawn_task(task);
+            }
+            self.flush_results()?;
+            self.record_gauges();
+        }
+
+        // 等待执行中的任务结束后发送剩余结果
+        while self.active_tasks.load(Ordering::Acquire) > 0 {
+            self.flush_results()?;
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        self.flush_results()?;
+        self.record_gauges();
+        tracing::info!(worker_id = %worker_id, "drone已停止");
+        Ok(())
+    }
+
+    /// 在tokio阻塞线程池中运行事件循环，不占用异步运行时线程
+    pub async fn run_async(self) -> Result<(), DroneError> {
+        tokio::task::spawn_blocking(move || self.run())
+            .await
+            .map_err(|e| DroneError::Join(e.to_string()))?
+    }
+
+    fn spawn_task(&self, mut task: Task) {
+        let executor = Arc::clone(&self.executor);
+        let active_tasks = Arc::clone(&self.active_tasks);
+        let sender = self.result_sender.clone();
+        let worker_id = self.worker_id().to_string();
+        let span = tracing::info_span!(
+            parent: None,
+            "task.execute",
+            task_id = %task.id,
+            worker = %worker_id,
+            outcome = tracing::field::Empty,
+            traceparent = task.metadata.get(telemetry::TRACEPARENT_KEY).map(String::as_str),
+        );
+        let context = telemetry::propagation_context(&span, telemetry::extract(&task.metadata));
+        telemetry::inject(&mut task.metadata, &context);
+        let guard = ActiveTaskGuard::new(active_tasks);
+        self.pool.spawn(move || {
+            // 任何情况下(包括回传失败)离开闭包时都递减执行中任务数，避免关闭时无限等待
+            let _guard = guard;
+            let started = Instant::now();
+            let executed = span.in_scope(|| panic::catch_unwind(AssertUnwindSafe(|| executor.execute(&task))));
+            let (result, outcome) = match executed {
+                Ok(Ok(output)) => (response::Result::Output(output), "success"),
+                Ok(Err(err)) => (response::Result::Error(err), "failure"),
+                Err(payload) => {
+                    let message = panic_message(payload.as_ref());
+                    tracing::error!(parent: &span, task_id = %task.id, error = %message, "任务执行器panic");
+                    (response::Result::Error(format!("executor panicked: {}", message)), "failure")
+                }
+            };
+            span.record("outcome", outcome);
+            metrics::histogram!("zergpool.drone.task_duration_seconds", "outcome" => outcome)
+                .record(started.elapsed().as_secs_f64());
+            let response = Response {
+                worker_id,
+                task_id: task.id,
+                result: Some(result),
+            };
+            let _ = sender.send((response, span));
+        });
+    }
+
+    /// 记录执行中任务数与待回传结果数
+    fn record_gauges(&self) {
+        let labels = [("worker", self.worker_id().to_string())];
+        metrics::gauge!("zergpool.drone.active_tasks", &labels).set(self.active_tasks.load(Ordering::Acquire) as f64);
+        metrics::gauge!("zergpool.drone.pending_results", &labels).set(self.result_receiver.len() as f64);
+    }
+
+    fn flush_results(&mut self) -> Result<(), DroneError> {
+        while let Ok((response, execute_span)) = self.result_receiver.try_recv() {
+            let _entered = tracing::info_span!(parent: &execute_span, "task.respond", task_id = %response.task_id)
+                .entered();
+            self.network.send_response(&response)?;
+        }
+        Ok(())
+    }
+}
+
+/// 执行中任务计数守卫：创建时加一，释放时减一
+struct ActiveTaskGuard(Arc<AtomicU32>);
+
+impl ActiveTaskGuard {
+    fn new(active_tasks: Arc<AtomicU32>) -> Self {
+        active_tasks.fetch_add(1, Ordering::AcqRel);
+        Self(active_tasks)
+    }
+}
+
+impl Drop for ActiveTaskGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// 提取panic信息
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
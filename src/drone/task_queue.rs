@@ -1,149 +1,276 @@
 //! Drone任务队列模块 - 基于crossbeam-channel实现
+//!
+//! 结果回调挂在各自的`TaskQueue`实例上，同一进程中的多个队列互不影响
 
-use crossbeam_channel::{bounded, Receiver, Sender};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
-use std::thread;
+use crossbeam_channel::{bounded, Sender};
+use futures::future::BoxFuture;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
 use uuid::Uuid;
 use crate::proto::zergpool::{Task, Response};
 use crate::drone::network::NetworkError;
+use crate::config::DroneConfig;
 
-/// 任务队列配置
-const QUEUE_CAPACITY: usize = 1000;
+/// 任务分发延迟告警阈值
 const TIMEOUT_THRESHOLD: Duration = Duration::from_millis(50);
 
 /// 任务队列结构体
+///
+/// 每个队列拥有自己的分发线程与结果处理线程，结果只通过本队列注册的回调交付；
+/// 队列释放时停止接收任务，等待已提交任务的结果处理完毕后回收线程
 pub struct TaskQueue {
-    sender: Sender<Task>,
-    receiver: Receiver<Response>,
+    sender: Option<Sender<Task>>,
     task_count: Arc<AtomicUsize>,
     resp_count: Arc<AtomicUsize>,
+    capacity: usize,
+    worker_id: String,
+    callbacks: Arc<RwLock<Vec<(CallbackId, Callback)>>>,
+    next_callback_id: AtomicU64,
+    dispatcher: Option<JoinHandle<()>>,
+    responder: Option<JoinHandle<()>>,
 }
 
 impl TaskQueue {
-    /// 创建新任务队列
+    /// 创建新任务队列(使用默认配置，结果中的节点ID为`unknown`)
     pub fn new() -> Arc<Self> {
-        let (task_sender, task_receiver) = bounded::<crate::proto::zergpool::Task>(QUEUE_CAPACITY);
-        let (resp_sender, resp_receiver) = bounded(QUEUE_CAPACITY);
+        Self::with_config("unknown", &DroneConfig::default())
+    }
+
+    /// 使用指定配置创建任务队列，`worker_id`写入任务结果与指标标签
+    pub fn with_config(worker_id: impl Into<String>, config: &DroneConfig) -> Arc<Self> {
+        let capacity = config.queue_capacity;
+        let worker_id = worker_id.into();
+        let (task_sender, task_receiver) = bounded::<crate::proto::zergpool::Task>(capacity);
+        let (resp_sender, resp_receiver) = bounded(capacity);
 
         // 创建工作线程池
         let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(num_cpus::get())
+            .num_threads(config.worker_threads)
             .build()
             .unwrap();
 
-        // 创建Arc实例
-        let instance = Arc::new(Self {
-            sender: task_sender,
-            receiver: resp_receiver.clone(), // 克隆接收器
-            task_count: Arc::new(AtomicUsize::new(0)),
-            resp_count: Arc::new(AtomicUsize::new(0)),
-        });
+        let task_count = Arc::new(AtomicUsize::new(0));
+        let resp_count = Arc::new(AtomicUsize::new(0));
+        let callbacks: Arc<RwLock<Vec<(CallbackId, Callback)>>> = Arc::new(RwLock::new(Vec::new()));
 
-        // 任务分发线程
-        let task_count = Arc::clone(&instance.task_count);
-        let resp_count = Arc::clone(&instance.resp_count);
-        thread::spawn(move || {
-            while let Ok(task) = task_receiver.recv() {
-                let start_time = Instant::now();
-                let resp_sender = resp_sender.clone();
-                let task_count = Arc::clone(&task_count);
-                pool.spawn(move || {
-                    task_count.fetch_add(1, Ordering::Relaxed);
-                    // 执行任务逻辑
-                    // 执行任务并生成响应
-                    let response = Response {
-                        worker_id: crate::drone::get_worker_id()
-                            .map(|id| id.as_str())
-                            .unwrap_or("unknown")
-                            .to_string(),
-                        result: Some(crate::proto::zergpool::response::Result::Output(Vec::new())),
-                    };
-                    
-                    // 发送结果
-                    if let Err(e) = resp_sender.send(response) {
-                        log::error!("Failed to send task result: {}", e);
-                    }
-                });
+        // 任务分发线程(任务发送端释放后退出)
+        let dispatcher = {
+            let task_count = Arc::clone(&task_count);
+            let resp_count = Arc::clone(&resp_count);
+            let worker_id = worker_id.clone();
+            thread::spawn(move || {
+                while let Ok(task) = task_receiver.recv() {
+                    let start_time = Instant::now();
+                    let resp_sender = resp_sender.clone();
+                    let task_count = Arc::clone(&task_count);
+                    let resp_count = Arc::clone(&resp_count);
+                    let task_id = task.id.clone();
+                    let worker_id = worker_id.clone();
+                    pool.spawn(move || {
+                        // 执行任务并生成响应
+                        let response = Response {
+                            worker_id: worker_id.clone(),
+                            task_id,
+                            result: Some(crate::proto::zergpool::response::Result::Output(Vec::new())),
+                        };
 
-                // 检查分发延迟
-                if start_time.elapsed() > TIMEOUT_THRESHOLD {
-                    log::warn!("Task {} dispatch exceeded P99 latency", task.id);
+                        // 发送结果(先计入响应数，保证处理线程递减时不会下溢)
+                        resp_count.fetch_add(1, Ordering::Relaxed);
+                        if let Err(e) = resp_sender.send(response) {
+                            resp_count.fetch_sub(1, Ordering::Relaxed);
+                            log::error!("Failed to send task result: {}", e);
+                        }
+                        let pending = task_count.fetch_sub(1, Ordering::Relaxed) - 1;
+                        record_queue_gauges(&worker_id, pending, resp_count.load(Ordering::Relaxed));
+                    });
+
+                    // 检查分发延迟
+                    if start_time.elapsed() > TIMEOUT_THRESHOLD {
+                        log::warn!("Task {} dispatch exceeded P99 latency", task.id);
+                    }
                 }
-            }
-        });
+            })
+        };
+
+        // 结果处理线程(所有结果发送端释放后退出)
+        let responder = {
+            let resp_count = Arc::clone(&resp_count);
+            let callbacks = Arc::clone(&callbacks);
+            let worker_id = worker_id.clone();
+            thread::spawn(move || {
+                while let Ok(resp) = resp_receiver.recv() {
+                    // 更新响应计数
+                    let pending = resp_count.fetch_sub(1, Ordering::Relaxed) - 1;
+                    metrics::gauge!("zergpool.drone.response_queue_depth", "worker" => worker_id.clone())
+                        .set(pending as f64);
 
-        // 克隆接收器用于线程
-        let resp_receiver_thread = resp_receiver.clone();
-
-        // 启动结果处理线程
-        thread::spawn(move || {
-            while let Ok(resp) = resp_receiver_thread.recv() {
-                // 更新响应计数
-                resp_count.fetch_sub(1, Ordering::Relaxed);
-                
-                // 安全处理回调逻辑
-                if let Some(handler) = CALLBACK_HANDLER.get() {
-                    let worker_id = resp.worker_id.clone();
-                    match handler.handle(&resp) {
-                        Ok(_) => (),
-                        Err(e) => log::error!("Failed to handle response from worker {}: {}", worker_id, e),
+                    // 回调期间不持有锁，回调中可以增删回调
+                    let handlers: Vec<Callback> = callbacks.read().unwrap()
+                        .iter()
+                        .map(|(_, callback)| callback.clone())
+                        .collect();
+                    for handler in handlers {
+                        handler.invoke(&resp);
                     }
                 }
-            }
-        });
+            })
+        };
+
+        Arc::new(Self {
+            sender: Some(task_sender),
+            task_count,
+            resp_count,
+            capacity,
+            worker_id,
+            callbacks,
+            next_callback_id: AtomicU64::new(0),
+            dispatcher: Some(dispatcher),
+            responder: Some(responder),
+        })
+    }
+
+    /// 注册结果回调，在结果处理线程中按注册顺序调用
+    pub fn add_callback(&self, handler: Arc<dyn CallbackHandler>) -> CallbackId {
+        self.insert_callback(Callback::Sync(handler))
+    }
+
+    /// 注册异步结果回调，每个结果在指定的tokio运行时中各自运行
+    pub fn add_async_callback(&self, runtime: Handle, handler: impl AsyncCallbackHandler) -> CallbackId {
+        self.insert_callback(Callback::Async(runtime, Arc::new(handler)))
+    }
+
+    /// 移除回调，返回该回调是否存在(正在执行的回调不受影响)
+    pub fn remove_callback(&self, id: CallbackId) -> bool {
+        let mut callbacks = self.callbacks.write().unwrap();
+        let before = callbacks.len();
+        callbacks.retain(|(callback_id, _)| *callback_id != id);
+        callbacks.len() != before
+    }
+
+    /// 当前注册的回调数量
+    pub fn callback_count(&self) -> usize {
+        self.callbacks.read().unwrap().len()
+    }
 
-        instance
+    fn insert_callback(&self, callback: Callback) -> CallbackId {
+        let id = CallbackId(self.next_callback_id.fetch_add(1, Ordering::Relaxed));
+        self.callbacks.write().unwrap().push((id, callback));
+        id
     }
 
     /// 提交新任务
     pub fn submit(&self, task: Task) -> Result<(), NetworkError> {
+        let Some(sender) = self.sender.as_ref() else {
+            return Err(NetworkError::Zmq(zmq::Error::EAGAIN));
+        };
         self.task_count.fetch_add(1, Ordering::Relaxed);
-        self.sender.send(task).map_err(|_| {
+        let result = sender.send(task).map_err(|_| {
             self.task_count.fetch_sub(1, Ordering::Relaxed);
             NetworkError::Zmq(zmq::Error::EAGAIN)
-        })
-    }
-
-    /// 获取结果接收器
-    pub fn response_receiver(&self) -> &Receiver<Response> {
-        &self.receiver
+        });
+        let (tasks, _, responses, _) = self.queue_usage();
+        record_queue_gauges(&self.worker_id, tasks, responses);
+        result
     }
 
     /// 获取队列使用情况 (任务队列长度/容量, 响应队列长度/容量)
+    ///
+    /// 任务数为已提交但尚未执行完的任务，响应数为已生成但尚未被处理线程取走的响应
     pub fn queue_usage(&self) -> (usize, usize, usize, usize) {
         (
             self.task_count.load(Ordering::Relaxed),
-            QUEUE_CAPACITY,
+            self.capacity,
             self.resp_count.load(Ordering::Relaxed),
-            QUEUE_CAPACITY,
+            self.capacity,
         )
     }
 
+    /// 响应中使用的节点ID
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
     /// 获取任务ID
     pub fn generate_task_id() -> String {
         Uuid::new_v4().to_string()
     }
 }
 
-use std::fmt;
+/// 记录任务队列与响应队列深度
+fn record_queue_gauges(worker_id: &str, tasks: usize, responses: usize) {
+    let labels = [("worker", worker_id.to_string())];
+    metrics::gauge!("zergpool.drone.task_queue_depth", &labels).set(tasks as f64);
+    metrics::gauge!("zergpool.drone.response_queue_depth", &labels).set(responses as f64);
+}
+
+impl Drop for TaskQueue {
+    fn drop(&mut self) {
+        // 释放任务发送端使分发线程退出，随后等待剩余结果交给回调
+        self.sender = None;
+        for handle in [self.dispatcher.take(), self.responder.take()].into_iter().flatten() {
+            // 最后一个引用在回调中释放时不能等待自身
+            if handle.thread().id() != thread::current().id() && handle.join().is_err() {
+                log::error!("任务队列线程异常退出: {}", self.worker_id);
+            }
+        }
+    }
+}
 
 /// 回调处理器trait
 pub trait CallbackHandler: Send + Sync + fmt::Debug {
     fn handle(&self, response: &Response) -> Result<(), NetworkError>;
 }
 
-use std::sync::OnceLock;
+/// 异步回调处理器
+///
+/// 返回的future在注册时指定的tokio运行时中运行；`Fn(Response) -> Future`闭包自动实现
+pub trait AsyncCallbackHandler: Send + Sync + 'static {
+    fn handle(&self, response: Response) -> BoxFuture<'static, Result<(), NetworkError>>;
+}
+
+impl<F, Fut> AsyncCallbackHandler for F
+where
+    F: Fn(Response) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), NetworkError>> + Send + 'static,
+{
+    fn handle(&self, response: Response) -> BoxFuture<'static, Result<(), NetworkError>> {
+        Box::pin(self(response))
+    }
+}
 
-static CALLBACK_HANDLER: OnceLock<Box<dyn CallbackHandler>> = OnceLock::new();
+/// 回调注册ID(用于移除回调)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallbackId(u64);
 
-/// 设置全局回调处理器
-pub fn set_callback_handler(handler: Box<dyn CallbackHandler>) {
-    CALLBACK_HANDLER.set(handler).expect("Global handler already set");
+/// 已注册的回调
+#[derive(Clone)]
+enum Callback {
+    Sync(Arc<dyn CallbackHandler>),
+    Async(Handle, Arc<dyn AsyncCallbackHandler>),
 }
 
-/// 获取全局回调处理器
-pub fn get_callback_handler() -> &'static dyn CallbackHandler {
-    &**CALLBACK_HANDLER.get().expect("Global handler not set")
+impl Callback {
+    fn invoke(&self, response: &Response) {
+        match self {
+            Callback::Sync(handler) => {
+                if let Err(e) = handler.handle(response) {
+                    log::error!("Failed to handle response from worker {}: {}", response.worker_id, e);
+                }
+            }
+            Callback::Async(runtime, handler) => {
+                let worker_id = response.worker_id.clone();
+                let future = handler.handle(response.clone());
+                runtime.spawn(async move {
+                    if let Err(e) = future.await {
+                        log::error!("Failed to handle response from worker {}: {}", worker_id, e);
+                    }
+                });
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ use tokio::time::{timeout, Duration, Instant};
 use log::error;
 
 use crate::balancer::ZergRushSelector;
+use crate::config::PoolConfig;
 
 type Task = Box<dyn FnOnce() + Send + 'static>;
 
@@ -18,7 +19,20 @@ pub struct TaskEngine {
 impl TaskEngine {
     /// 创建新引擎实例
     pub fn new(balancer: Arc<Mutex<ZergRushSelector>>, worker_count: usize) -> Self {
-        let (task_sender, task_receiver) = mpsc::channel(1024);
+        let config = PoolConfig {
+            engine_workers: worker_count,
+            ..PoolConfig::default()
+        };
+        Self::with_config(balancer, &config)
+    }
+
+    /// 使用指定配置创建引擎实例
+    pub fn with_config(balancer: Arc<Mutex<ZergRushSelector>>, config: &PoolConfig) -> Self {
+        Self::build(balancer, config.engine_workers, config.engine_queue_capacity)
+    }
+
+    fn build(balancer: Arc<Mutex<ZergRushSelector>>, worker_count: usize, queue_capacity: usize) -> Self {
+        let (task_sender, task_receiver) = mpsc::channel(queue_capacity);
         let shared_receiver = Arc::new(Mutex::new(task_receiver));
         let shutdown_notify = Arc::new(Notify::new());
         
@@ -33,7 +47,9 @@ impl TaskEngine {
                     tokio::select! {
                         task = async {
                             let mut guard = receiver.lock().await;
-                            guard.recv().await
+                            let task = guard.recv().await;
+                            record_queue_depth(guard.len());
+                            task
                         } => {
                             if let Some(task) = task {
                                 let start_time = Instant::now();
@@ -65,6 +81,7 @@ impl TaskEngine {
         if let Err(e) = self.task_sender.send(task).await {
             error!("任务提交失败: {}", e);
         }
+        record_queue_depth(self.task_sender.max_capacity() - self.task_sender.capacity());
         
         if let Ok(mut selector) = timeout(Duration::from_millis(100), self.balancer.lock()).await {
             selector.on_task_submitted();
@@ -87,4 +104,9 @@ impl TaskEngine {
             tokio::time::sleep(Duration::from_millis(100)).await;
         }
     }
+}
+
+/// 记录执行队列中等待的任务数
+fn record_queue_depth(depth: usize) {
+    metrics::gauge!("zergpool.engine.queue_depth").set(depth as f64);
 }
\ No newline at end of file
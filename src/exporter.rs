@@ -0,0 +1,425 @@
+//! Prometheus指标导出
+//!
+//! 代码中通过`metrics`门面记录指标(`metrics::counter!`等)，本模块提供对应的记录器：
+//! 指标写入`prometheus::Registry`，由内置HTTP服务在`/metrics`以文本格式导出。
+//!
+//! 命名规则：`zergpool.task.latency_seconds`导出为`zergpool_task_latency_seconds`，
+//! 计数器追加`_total`后缀；标签原样保留(如`zergpool_worker_cpu_usage{worker="drone-1"}`)。
+//! 全部指标及其类型、标签见[`CATALOGUE`]
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use metrics::{Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use prometheus::{Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use thiserror::Error;
+
+/// 指标导出错误类型
+#[derive(Error, Debug)]
+pub enum ExporterError {
+    #[error("进程中已安装其他metrics记录器")]
+    RecorderInstalled,
+    #[error("指标服务启动失败: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// 已注册的指标族(同名指标的标签名必须一致)
+enum Family {
+    Counter(IntCounterVec),
+    Gauge(GaugeVec),
+    Histogram(HistogramVec),
+}
+
+struct Inner {
+    registry: Registry,
+    descriptions: Mutex<HashMap<String, String>>,
+    families: Mutex<HashMap<String, (Family, Vec<String>)>>,
+}
+
+impl Inner {
+    /// 查找或创建指标族，返回按族标签名顺序排列的标签值
+    fn family<T>(
+        &self,
+        key: &Key,
+        create: impl FnOnce(Opts, &[&str]) -> prometheus::Result<Family>,
+        select: impl FnOnce(&Family, &[&str]) -> Option<T>,
+    ) -> Option<T> {
+        let labels: HashMap<&str, &str> = key.labels().map(|l| (l.key(), l.value())).collect();
+        let mut families = self.families.lock().unwrap();
+        if !families.contains_key(key.name()) {
+            let mut names: Vec<String> = labels.keys().map(|k| k.to_string()).collect();
+            names.sort();
+            let help = self.descriptions.lock().unwrap().get(key.name()).cloned()
+                .unwrap_or_else(|| key.name().to_string());
+            let opts = Opts::new(prometheus_name(key.name()), help);
+            let name_refs: Vec<&str> = names.iter().map(String::as_str).collect();
+            let family = match create(opts, &name_refs) {
+                Ok(family) => family,
+                Err(e) => {
+                    log::warn!("指标 {} 创建失败: {}", key.name(), e);
+                    return None;
+                }
+            };
+            let collector: Box<dyn prometheus::core::Collector> = match &family {
+                Family::Counter(c) => Box::new(c.clone()),
+                Family::Gauge(g) => Box::new(g.clone()),
+                Family::Histogram(h) => Box::new(h.clone()),
+            };
+            if let Err(e) = self.registry.register(collector) {
+                log::warn!("指标 {} 注册失败: {}", key.name(), e);
+                return None;
+            }
+            families.insert(key.name().to_string(), (family, names));
+        }
+
+        let (family, names) = &families[key.name()];
+        if names.len() != labels.len() {
+            log::warn!("指标 {} 的标签与已注册的不一致: {:?}", key.name(), names);
+            return None;
+        }
+        let values: Option<Vec<&str>> = names.iter().map(|n| labels.get(n.as_str()).copied()).collect();
+        let Some(values) = values else {
+            log::warn!("指标 {} 的标签与已注册的不一致: {:?}", key.name(), names);
+            return None;
+        };
+        let selected = select(family, &values);
+        if selected.is_none() {
+            log::warn!("指标 {} 已注册为其他类型", key.name());
+        }
+        selected
+    }
+}
+
+/// 转为Prometheus指标名(非法字符替换为`_`)
+fn prometheus_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect()
+}
+
+struct CounterHandle(prometheus::IntCounter);
+
+impl CounterFn for CounterHandle {
+    fn increment(&self, value: u64) {
+        self.0.inc_by(value);
+    }
+
+    fn absolute(&self, value: u64) {
+        let current = self.0.get();
+        if value > current {
+            self.0.inc_by(value - current);
+        }
+    }
+}
+
+struct GaugeHandle(prometheus::Gauge);
+
+impl GaugeFn for GaugeHandle {
+    fn increment(&self, value: f64) {
+        self.0.add(value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.0.sub(value);
+    }
+
+    fn set(&self, value: f64) {
+        self.0.set(value);
+    }
+}
+
+struct HistogramHandle(prometheus::Histogram);
+
+impl HistogramFn for HistogramHandle {
+    fn record(&self, value: f64) {
+        self.0.observe(value);
+    }
+}
+
+/// 写入Prometheus注册表的`metrics`记录器
+pub struct PrometheusRecorder {
+    inner: Arc<Inner>,
+}
+
+impl Default for PrometheusRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrometheusRecorder {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                registry: Registry::new(),
+                descriptions: Mutex::new(HashMap::new()),
+                families: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// 用于读取导出内容的句柄
+    pub fn handle(&self) -> PrometheusHandle {
+        PrometheusHandle { inner: Arc::clone(&self.inner) }
+    }
+
+    fn describe(&self, key: KeyName, description: SharedString) {
+        self.inner.descriptions.lock().unwrap().insert(key.as_str().to_string(), description.to_string());
+    }
+}
+
+impl Recorder for PrometheusRecorder {
+    fn describe_counter(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_gauge(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn describe_histogram(&self, key: KeyName, _unit: Option<Unit>, description: SharedString) {
+        self.describe(key, description);
+    }
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        self.inner.family(
+            key,
+            |opts, labels| {
+                let opts = Opts { name: format!("{}_total", opts.name.trim_end_matches("_total")), ..opts };
+                IntCounterVec::new(opts, labels).map(Family::Counter)
+            },
+            |family, values| match family {
+                Family::Counter(c) => Some(c.with_label_values(values)),
+                _ => None,
+            },
+        )
+        .map(|c| Counter::from_arc(Arc::new(CounterHandle(c))))
+        .unwrap_or_else(Counter::noop)
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        self.inner.family(
+            key,
+            |opts, labels| GaugeVec::new(opts, labels).map(Family::Gauge),
+            |family, values| match family {
+                Family::Gauge(g) => Some(g.with_label_values(values)),
+                _ => None,
+            },
+        )
+        .map(|g| Gauge::from_arc(Arc::new(GaugeHandle(g))))
+        .unwrap_or_else(Gauge::noop)
+    }
+
+    fn register_histogram(&self, key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        self.inner.family(
+            key,
+            |opts, labels| {
+                // 1ms ~ 32s，覆盖任务执行与派发延迟
+                let buckets = prometheus::exponential_buckets(0.001, 2.0, 16)?;
+                HistogramVec::new(HistogramOpts::from(opts).buckets(buckets), labels).map(Family::Histogram)
+            },
+            |family, values| match family {
+                Family::Histogram(h) => Some(h.with_label_values(values)),
+                _ => None,
+            },
+        )
+        .map(|h| Histogram::from_arc(Arc::new(HistogramHandle(h))))
+        .unwrap_or_else(Histogram::noop)
+    }
+}
+
+/// 读取导出内容的句柄
+#[derive(Clone)]
+pub struct PrometheusHandle {
+    inner: Arc<Inner>,
+}
+
+impl PrometheusHandle {
+    /// Prometheus文本格式的全部指标
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.inner.registry.gather(), &mut buffer) {
+            log::error!("指标编码失败: {}", e);
+        }
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+/// 已安装为全局记录器的句柄
+static INSTALLED: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+
+/// 安装全局记录器并登记指标说明；已由本模块安装时返回同一句柄
+pub fn install_recorder() -> Result<PrometheusHandle, ExporterError> {
+    let mut installed = INSTALLED.lock().unwrap();
+    if let Some(handle) = installed.as_ref() {
+        return Ok(handle.clone());
+    }
+    let recorder = PrometheusRecorder::new();
+    let handle = recorder.handle();
+    metrics::set_global_recorder(recorder).map_err(|_| ExporterError::RecorderInstalled)?;
+    describe_metrics();
+    *installed = Some(handle.clone());
+    Ok(handle)
+}
+
+/// 安装全局记录器并在指定地址提供`/metrics`
+pub fn serve(addr: impl ToSocketAddrs) -> Result<MetricsServer, ExporterError> {
+    let handle = install_recorder()?;
+    Ok(MetricsServer::bind(addr, handle)?)
+}
+
+/// 指标类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+/// 指标目录项
+#[derive(Debug, Clone, Copy)]
+pub struct MetricDef {
+    /// `metrics`门面中使用的名称
+    pub name: &'static str,
+    pub kind: MetricKind,
+    /// 标签名(不含直方图的`le`)
+    pub labels: &'static [&'static str],
+    pub help: &'static str,
+}
+
+impl MetricDef {
+    /// 导出的Prometheus指标名
+    pub fn exported_name(&self) -> String {
+        let name = prometheus_name(self.name);
+        match self.kind {
+            MetricKind::Counter => format!("{}_total", name),
+            _ => name,
+        }
+    }
+}
+
+const fn counter(name: &'static str, help: &'static str) -> MetricDef {
+    MetricDef { name, kind: MetricKind::Counter, labels: &[], help }
+}
+
+const fn gauge(name: &'static str, labels: &'static [&'static str], help: &'static str) -> MetricDef {
+    MetricDef { name, kind: MetricKind::Gauge, labels, help }
+}
+
+const fn histogram(name: &'static str, labels: &'static [&'static str], help: &'static str) -> MetricDef {
+    MetricDef { name, kind: MetricKind::Histogram, labels, help }
+}
+
+/// zerg_pool记录的全部指标
+///
+/// 新增指标时须同时登记在此，`describe_metrics`据此生成HELP文本
+pub const CATALOGUE: &[MetricDef] = &[
+    // queen: 进程池与任务队列
+    gauge("zergpool.workers", &[], "主工作池节点数"),
+    gauge("zergpool.backups", &[], "备用池节点数"),
+    gauge("zergpool.pending_tasks", &[], "queen待派发队列深度"),
+    gauge("zergpool.in_flight_tasks", &[], "已派发未完成的任务数"),
+    gauge("zergpool.is_leader", &[], "是否为leader(1/0)"),
+    counter("zergpool.tasks_submitted", "提交的任务数"),
+    counter("zergpool.tasks_dispatched", "派发给drone的次数(含重试)"),
+    counter("zergpool.tasks_completed", "执行成功的任务数"),
+    counter("zergpool.tasks_failed", "drone返回失败的次数(含重试前的失败)"),
+    counter("zergpool.tasks_retried", "失败后按重试策略重新入队的次数"),
+    counter("zergpool.tasks_timed_out", "所在节点心跳超时而重新入队的在途任务数"),
+    counter("zergpool.tasks_dead_lettered", "超过重试次数进入死信队列的任务数"),
+    counter("zergpool.workers_reaped", "心跳超时被移出的节点数"),
+    counter("zergpool.workers_evicted", "被管理员移出的节点数"),
+    counter("zergpool.admin_unauthorized", "令牌无效被拒绝的控制请求数"),
+    counter("zergpool.leader_elected", "当选leader的次数"),
+    counter("zergpool.drone_restarts", "drone子进程重启次数"),
+    counter("zergpool.spans_dropped", "span导出队列已满而丢弃的span数"),
+    counter("zergpool.events_dropped", "订阅通道已满而丢弃的事件数(按订阅者计)"),
+    histogram("zergpool.task.latency_seconds", &["outcome"], "任务从派发到收到结果的时间(秒)"),
+    // queen: 单个节点(`worker`为节点ID)
+    gauge("zergpool.worker.up", &["worker"], "节点是否在进程池中(1/0)"),
+    gauge("zergpool.worker.cpu_usage", &["worker"], "节点CPU使用率(0.0-1.0)"),
+    gauge("zergpool.worker.mem_usage", &["worker"], "节点内存使用率(0.0-1.0)"),
+    gauge("zergpool.worker.net_latency_ms", &["worker"], "节点网络延迟(ms)"),
+    gauge("zergpool.worker.current_tasks", &["worker"], "节点上报的当前任务数"),
+    gauge("zergpool.worker.max_tasks", &["worker"], "节点最大任务数"),
+    gauge("zergpool.worker.health", &["worker"], "节点健康状态(0健康/1不健康/2熔断)"),
+    gauge("zergpool.worker.in_flight", &["worker"], "派发给该节点且未完成的任务数"),
+    gauge("zergpool.warmup_factor", &["worker"], "节点预热系数(1.0为预热完成)"),
+    // 负载均衡器
+    counter("zergpool.selections", "负载均衡选择次数"),
+    counter("zergpool.selection_errors", "负载均衡选择失败次数"),
+    counter("zergpool.backup_used", "从备用池激活节点的次数"),
+    counter("zergpool.backup_empty", "备用池耗尽的次数"),
+    counter("zergpool.scale_out", "扩容次数"),
+    counter("zergpool.scale_in", "缩容次数"),
+    gauge("zergpool.worker_count", &[], "负载均衡时的主池节点数"),
+    gauge("zergpool.backup_nodes", &[], "负载均衡器备用池节点数"),
+    gauge("zergpool.healthcheck_interval", &[], "健康检查间隔(秒)"),
+    // 任务执行引擎
+    gauge("zergpool.engine.queue_depth", &[], "执行引擎队列中等待的任务数"),
+    // drone
+    gauge("zergpool.drone.active_tasks", &["worker"], "drone执行中的任务数"),
+    gauge("zergpool.drone.pending_results", &["worker"], "drone已完成但尚未回传的结果数"),
+    gauge("zergpool.drone.task_queue_depth", &["worker"], "TaskQueue中已提交未完成的任务数"),
+    gauge("zergpool.drone.response_queue_depth", &["worker"], "TaskQueue中未处理的响应数"),
+    histogram("zergpool.drone.task_duration_seconds", &["outcome"], "drone执行单个任务的时间(秒)"),
+];
+
+/// 登记指标说明(作为导出的HELP文本)
+pub fn describe_metrics() {
+    for def in CATALOGUE {
+        match def.kind {
+            MetricKind::Counter => metrics::describe_counter!(def.name, def.help),
+            MetricKind::Gauge => metrics::describe_gauge!(def.name, def.help),
+            MetricKind::Histogram => metrics::describe_histogram!(def.name, def.help),
+        }
+    }
+}
+
+/// 指标HTTP服务(后台线程，进程退出时随之结束)
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+}
+
+impl MetricsServer {
+    /// 绑定地址并在`/metrics`导出句柄中的指标
+    pub fn bind(addr: impl ToSocketAddrs, handle: PrometheusHandle) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        thread::Builder::new()
+            .name("zerg-metrics".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    if let Err(e) = handle_request(stream, &handle) {
+                        log::debug!("指标请求处理失败: {}", e);
+                    }
+                }
+            })?;
+        log::info!("指标服务已启动: http://{}/metrics", local_addr);
+        Ok(Self { local_addr })
+    }
+
+    /// 实际绑定的地址(端口为0时返回系统分配的端口)
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+fn handle_request(mut stream: TcpStream, handle: &PrometheusHandle) -> io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    if path != "/metrics" {
+        return stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    }
+    let body = handle.render();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        prometheus::TEXT_FORMAT,
+        body.len(),
+        body
+    )
+}
@@ -1,10 +1,15 @@
 //! ZergPool 核心库入口 - 严格遵循docs/架构设计.md规范
 
 pub mod balancer;
+pub mod config;
 pub mod drone;
 pub mod engine;
+pub mod exporter;
 pub mod proto;
 pub mod queen;
+pub mod telemetry;
+pub mod transport;
+pub mod version;
 
 /// 进程标识类型
 pub type ProcessId = String;
@@ -33,7 +38,7 @@ impl Process {
 }
 
 /// 进程间通信消息类型(严格匹配proto/task.proto定义)
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum ProcessMessage {
     /// 工作节点注册消息(对应proto Registration消息)
     Registration(proto::zergpool::Registration),
@@ -46,9 +51,72 @@ pub enum ProcessMessage {
     
     /// 任务响应消息(对应proto Response消息)
     TaskResponse(proto::zergpool::Response),
+
+    /// queen控制消息(对应proto Control消息)
+    Control(proto::zergpool::Control),
+
+    /// queen拒绝注册的回复(对应proto RegistrationRejected消息)
+    RegistrationRejected(proto::zergpool::RegistrationRejected),
+
+    /// queen接受注册的回复(对应proto RegistrationAccepted消息)
+    RegistrationAccepted(proto::zergpool::RegistrationAccepted),
+}
+
+impl ProcessMessage {
+    /// 编码为信封格式的数据帧
+    pub fn encode_envelope(&self) -> Vec<u8> {
+        use prost::Message;
+        proto::zergpool::Envelope::from(self.clone()).encode_to_vec()
+    }
+
+    /// 从信封格式的数据帧解码
+    pub fn decode_envelope(data: &[u8]) -> std::result::Result<Self, prost::DecodeError> {
+        use prost::Message;
+        let envelope = proto::zergpool::Envelope::decode(data)?;
+        Self::try_from(envelope)
+    }
+}
+
+impl From<ProcessMessage> for proto::zergpool::Envelope {
+    fn from(msg: ProcessMessage) -> Self {
+        use proto::zergpool::envelope::Body;
+        let body = match msg {
+            ProcessMessage::Registration(reg) => Body::Registration(reg),
+            ProcessMessage::Heartbeat(hb) => Body::Heartbeat(hb),
+            ProcessMessage::Task(task) => Body::Task(task),
+            ProcessMessage::TaskResponse(resp) => Body::Response(resp),
+            ProcessMessage::Control(ctrl) => Body::Control(ctrl),
+            ProcessMessage::RegistrationRejected(rejected) => Body::RegistrationRejected(rejected),
+            ProcessMessage::RegistrationAccepted(accepted) => Body::RegistrationAccepted(accepted),
+        };
+        Self { body: Some(body) }
+    }
+}
+
+impl TryFrom<proto::zergpool::Envelope> for ProcessMessage {
+    type Error = prost::DecodeError;
+
+    fn try_from(envelope: proto::zergpool::Envelope) -> std::result::Result<Self, Self::Error> {
+        use proto::zergpool::envelope::Body;
+        match envelope.body {
+            Some(Body::Registration(reg)) => Ok(ProcessMessage::Registration(reg)),
+            Some(Body::Heartbeat(hb)) => Ok(ProcessMessage::Heartbeat(hb)),
+            Some(Body::Task(task)) => Ok(ProcessMessage::Task(task)),
+            Some(Body::Response(resp)) => Ok(ProcessMessage::TaskResponse(resp)),
+            Some(Body::Control(ctrl)) => Ok(ProcessMessage::Control(ctrl)),
+            Some(Body::RegistrationRejected(rejected)) => Ok(ProcessMessage::RegistrationRejected(rejected)),
+            Some(Body::RegistrationAccepted(accepted)) => Ok(ProcessMessage::RegistrationAccepted(accepted)),
+            None => Err(prost::DecodeError::new("Empty envelope")),
+        }
+    }
 }
 
 use crate::queen::network::NetworkError;
+use crate::queen::journal::JournalError;
+use crate::queen::election::ElectionError;
+use crate::queen::supervisor::SupervisorError;
+use crate::exporter::ExporterError;
+use crate::telemetry::TelemetryError;
 
 /// 通用错误类型
 #[derive(thiserror::Error, Debug)]
@@ -57,6 +125,34 @@ pub enum RegistrationError {
     InvalidEndpoint,
     #[error("工作池已满")]
     PoolFull,
+    #[error("节点ID不能为空")]
+    InvalidWorkerId,
+    #[error("注册令牌无效")]
+    InvalidToken,
+    #[error("节点版本{drone}与queen版本{queen}不兼容")]
+    IncompatibleVersion { drone: String, queen: String },
+    #[error("节点ID {0} 已被在线节点使用")]
+    DuplicateId(ProcessId),
+    #[error("节点协议版本{drone}与queen协议版本{queen}不兼容")]
+    IncompatibleProtocol { drone: String, queen: String },
+    #[error("节点 {0} 已被移出进程池，需管理员解除后才能重新注册")]
+    Evicted(ProcessId),
+}
+
+impl RegistrationError {
+    /// 回复drone时使用的拒绝原因
+    pub fn reject_reason(&self) -> proto::zergpool::RejectReason {
+        use proto::zergpool::RejectReason;
+        match self {
+            Self::InvalidEndpoint | Self::InvalidWorkerId => RejectReason::InvalidRegistration,
+            Self::PoolFull => RejectReason::PoolFull,
+            Self::InvalidToken => RejectReason::InvalidToken,
+            Self::IncompatibleVersion { .. } => RejectReason::IncompatibleVersion,
+            Self::DuplicateId(_) => RejectReason::DuplicateId,
+            Self::IncompatibleProtocol { .. } => RejectReason::IncompatibleProtocol,
+            Self::Evicted(_) => RejectReason::WorkerEvicted,
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -75,15 +171,47 @@ pub enum PoolError {
     
     #[error("内部系统错误")]
     InternalError,
+
+    #[error("配置错误: {0}")]
+    Config(#[from] config::ConfigError),
+
+    #[error("任务日志错误: {0}")]
+    Journal(#[from] JournalError),
+
+    #[error("选主错误: {0}")]
+    Election(#[from] ElectionError),
+
+    #[error("当前queen不是leader(leader地址: {0})")]
+    NotLeader(String),
+
+    #[error("drone子进程监管错误: {0}")]
+    Supervisor(#[from] SupervisorError),
+
+    #[error("进程池事件循环已停止")]
+    Stopped,
+
+    #[error("不能在tokio运行时中阻塞等待事件循环，请使用AsyncDronePool")]
+    BlockingInRuntime,
+
+    #[error("未知的工作节点: {0}")]
+    UnknownWorker(String),
+
+    #[error("指标导出错误: {0}")]
+    Exporter(#[from] ExporterError),
+
+    #[error("追踪导出错误: {0}")]
+    Telemetry(#[from] TelemetryError),
 }
 
 pub type Result<T> = std::result::Result<T, PoolError>;
 
 // 公开导出模块的公共接口
-pub use queen::DronePool;
+pub use queen::{AsyncDronePool, DronePool};
 pub use drone::heartbeat::HeartbeatManager;
 pub use drone::network::DroneNetwork;
+pub use drone::{AsyncDroneNetwork, Drone, DroneError};
 pub use engine::TaskEngine;
 pub use queen::network::HiveNetwork;
+pub use config::{PoolConfig, DroneConfig, ConfigError, ConfigWatcher};
 
 
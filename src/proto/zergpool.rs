@@ -30,6 +30,9 @@ pub struct Response {
     /// 工作节点ID
     #[prost(string, tag = "1")]
     pub worker_id: ::prost::alloc::string::String,
+    /// 对应的任务ID
+    #[prost(string, tag = "4")]
+    pub task_id: ::prost::alloc::string::String,
     #[prost(oneof = "response::Result", tags = "2, 3")]
     pub result: ::core::option::Option<response::Result>,
 }
@@ -64,6 +67,41 @@ pub struct Registration {
     /// 扩展能力列表
     #[prost(string, repeated, tag = "4")]
     pub capabilities: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// 注册令牌(queen启用准入令牌时必填)
+    #[prost(string, tag = "5")]
+    pub token: ::prost::alloc::string::String,
+    /// 线协议版本(为空视为协商机制之前的1.x协议，不兼容)
+    #[prost(string, tag = "6")]
+    pub protocol_version: ::prost::alloc::string::String,
+}
+/// queen拒绝注册的回复
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegistrationRejected {
+    #[prost(enumeration = "RejectReason", tag = "1")]
+    pub reason: i32,
+    /// 附加说明
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    /// queen版本(便于排查版本不兼容)
+    #[prost(string, tag = "3")]
+    pub queen_version: ::prost::alloc::string::String,
+    /// queen线协议版本
+    #[prost(string, tag = "4")]
+    pub protocol_version: ::prost::alloc::string::String,
+}
+/// queen接受注册的回复(仅发给上报了协议版本的drone)
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegistrationAccepted {
+    /// queen线协议版本
+    #[prost(string, tag = "1")]
+    pub protocol_version: ::prost::alloc::string::String,
+    /// queen版本
+    #[prost(string, tag = "2")]
+    pub queen_version: ::prost::alloc::string::String,
 }
 /// 心跳消息
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -95,6 +133,50 @@ pub struct Heartbeat {
     #[prost(uint32, tag = "8")]
     pub max_tasks: u32,
 }
+/// queen发往drone的控制消息
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Control {
+    #[prost(enumeration = "ControlKind", tag = "1")]
+    pub kind: i32,
+    /// 附加说明
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
+    /// NOT_LEADER时当前leader的地址(未知时为空)
+    #[prost(string, tag = "3")]
+    pub leader_endpoint: ::prost::alloc::string::String,
+}
+/// 消息信封(数据帧统一使用信封编码，用于区分消息类型)
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Envelope {
+    #[prost(oneof = "envelope::Body", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub body: ::core::option::Option<envelope::Body>,
+}
+/// Nested message and enum types in `Envelope`.
+pub mod envelope {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Body {
+        #[prost(message, tag = "1")]
+        Registration(super::Registration),
+        #[prost(message, tag = "2")]
+        Heartbeat(super::Heartbeat),
+        #[prost(message, tag = "3")]
+        Task(super::Task),
+        #[prost(message, tag = "4")]
+        Response(super::Response),
+        #[prost(message, tag = "5")]
+        Control(super::Control),
+        #[prost(message, tag = "6")]
+        RegistrationRejected(super::RegistrationRejected),
+        #[prost(message, tag = "7")]
+        RegistrationAccepted(super::RegistrationAccepted),
+    }
+}
 /// 响应状态枚举
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
@@ -164,3 +246,91 @@ impl HealthState {
         }
     }
 }
+/// 控制消息类型
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ControlKind {
+    /// 心跳确认
+    HeartbeatAck = 0,
+    /// 当前queen不是leader
+    NotLeader = 1,
+    /// queen没有该节点的注册记录(需重新注册)
+    UnknownWorker = 2,
+    /// 节点已被管理员移出进程池(drone应退出)
+    Evicted = 3,
+}
+impl ControlKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ControlKind::HeartbeatAck => "HEARTBEAT_ACK",
+            ControlKind::NotLeader => "NOT_LEADER",
+            ControlKind::UnknownWorker => "UNKNOWN_WORKER",
+            ControlKind::Evicted => "EVICTED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "HEARTBEAT_ACK" => Some(Self::HeartbeatAck),
+            "NOT_LEADER" => Some(Self::NotLeader),
+            "UNKNOWN_WORKER" => Some(Self::UnknownWorker),
+            "EVICTED" => Some(Self::Evicted),
+            _ => None,
+        }
+    }
+}
+/// 注册被拒绝的原因
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RejectReason {
+    /// 注册信息不完整(如节点ID为空)
+    InvalidRegistration = 0,
+    /// 注册令牌缺失或不匹配
+    InvalidToken = 1,
+    /// 节点版本与queen不兼容
+    IncompatibleVersion = 2,
+    /// 进程池已达到节点数上限
+    PoolFull = 3,
+    /// 相同ID的节点仍在线
+    DuplicateId = 4,
+    /// 线协议版本与queen不兼容
+    IncompatibleProtocol = 5,
+    /// 节点已被管理员移出，解除前不可注册
+    WorkerEvicted = 6,
+}
+impl RejectReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            RejectReason::InvalidRegistration => "INVALID_REGISTRATION",
+            RejectReason::InvalidToken => "INVALID_TOKEN",
+            RejectReason::IncompatibleVersion => "INCOMPATIBLE_VERSION",
+            RejectReason::PoolFull => "POOL_FULL",
+            RejectReason::DuplicateId => "DUPLICATE_ID",
+            RejectReason::IncompatibleProtocol => "INCOMPATIBLE_PROTOCOL",
+            RejectReason::WorkerEvicted => "WORKER_EVICTED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "INVALID_REGISTRATION" => Some(Self::InvalidRegistration),
+            "INVALID_TOKEN" => Some(Self::InvalidToken),
+            "INCOMPATIBLE_VERSION" => Some(Self::IncompatibleVersion),
+            "POOL_FULL" => Some(Self::PoolFull),
+            "DUPLICATE_ID" => Some(Self::DuplicateId),
+            "INCOMPATIBLE_PROTOCOL" => Some(Self::IncompatibleProtocol),
+            "WORKER_EVICTED" => Some(Self::WorkerEvicted),
+            _ => None,
+        }
+    }
+}
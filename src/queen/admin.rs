@@ -0,0 +1,464 @@
+//! 运维管理接口
+//!
+//! `DronePool`的运维操作：查看节点与队列、排空/移除/晋升节点、暂停派发、查询死信任务。
+//! 被移除的节点ID记入黑名单，解除(`unevict`)前拒绝其重新注册。
+//! `AdminServer`在TCP地址上提供控制端点(供`zerg-ctl`使用)：每行一个携带共享令牌的JSON请求，
+//! 令牌校验通过后经`PoolCommand::Admin`交给事件循环处理，回复一行JSON
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+use super::admission::constant_time_eq;
+use super::events::{PoolEvent, RemovalReason};
+use super::{DeadLetter, DronePool, PoolCommand};
+use crate::config::PoolConfig;
+use crate::proto::zergpool::{ControlKind, HealthState};
+use crate::{PoolError, ProcessId};
+
+/// 控制端点共享令牌环境变量(zerg-queen与zerg-ctl共用)
+pub const CONTROL_TOKEN_ENV: &str = "ZERG_CONTROL_TOKEN";
+
+/// 控制端点请求
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum AdminRequest {
+    /// 列出主池与备用池节点
+    ListWorkers,
+    /// 只列出备用池节点
+    ListBackups,
+    /// 排空节点：不再派发新任务，在途任务照常完成
+    Drain { worker_id: ProcessId },
+    /// 移除节点：在途任务重新入队，通知drone退出，解除前拒绝其重新注册
+    Evict { worker_id: ProcessId },
+    /// 解除移除，允许节点重新注册
+    Unevict { worker_id: ProcessId },
+    /// 将备用节点晋升到主池(主池已满时末尾节点降级)
+    Promote { worker_id: ProcessId },
+    /// 暂停派发(仍接受任务提交)
+    PauseDispatch,
+    /// 恢复派发
+    ResumeDispatch,
+    /// 队列深度
+    Queue,
+    /// 死信任务
+    DeadLetters,
+    /// 当前配置
+    Config,
+}
+
+/// 控制端点请求行：共享令牌与请求字段位于同一JSON对象中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdminEnvelope {
+    pub token: String,
+    #[serde(flatten)]
+    pub request: AdminRequest,
+}
+
+/// 控制端点回复
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AdminResponse {
+    Workers { workers: Vec<WorkerInfo> },
+    Queue(QueueInfo),
+    DeadLetters { tasks: Vec<DeadLetter> },
+    Config { config: Box<PoolConfig> },
+    Ok { message: String },
+    Error { message: String },
+}
+
+/// 节点所在的池
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerRole {
+    Main,
+    Backup,
+}
+
+/// 节点信息快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerInfo {
+    pub id: ProcessId,
+    pub role: WorkerRole,
+    pub health_state: HealthState,
+    pub cpu_usage: f32,
+    pub mem_usage: f32,
+    pub net_latency: u32,
+    pub current_tasks: u32,
+    pub max_tasks: u32,
+    /// queen记录的在途任务数
+    pub in_flight: usize,
+    pub draining: bool,
+    /// 预热权重系数(1.0表示已完成预热)
+    pub warmup: f64,
+    /// 距上次心跳的时间(ms)
+    pub last_heartbeat_ms: u64,
+    pub capabilities: Vec<String>,
+}
+
+/// 队列状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueInfo {
+    pub pending: usize,
+    pub in_flight: usize,
+    pub dead_letters: usize,
+    pub dispatch_paused: bool,
+    pub is_leader: bool,
+}
+
+impl DronePool {
+    /// 主池与备用池节点快照(主池在前)
+    pub fn workers(&self) -> Vec<WorkerInfo> {
+        let warmup = self.selector.warmup();
+        self.with_state(|state| {
+            let main = state.workers.iter().map(|w| (w, WorkerRole::Main));
+            let backup = state.backup_drones.iter().map(|w| (w, WorkerRole::Backup));
+            main.chain(backup)
+                .filter_map(|(worker, role)| {
+                    let status = state.status.get(&worker.id)?;
+                    Some(WorkerInfo {
+                        id: worker.id.clone(),
+                        role,
+                        health_state: status.health_state,
+                        cpu_usage: status.cpu_usage,
+                        mem_usage: status.mem_usage,
+                        net_latency: status.net_latency,
+                        current_tasks: status.current_tasks,
+                        max_tasks: status.max_tasks,
+                        in_flight: state.in_flight.values().filter(|t| t.worker_id == worker.id).count(),
+                        draining: state.draining.contains(&worker.id),
+                        warmup: warmup.factor(&worker.id),
+                        last_heartbeat_ms: status.last_heartbeat.elapsed().as_millis() as u64,
+                        capabilities: status.capability.clone(),
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// 排空节点：不再向其派发新任务，在途任务照常完成；节点移除后标记随之清除
+    pub fn drain_worker(&mut self, worker_id: &ProcessId) -> crate::Result<()> {
+        self.with_state_mut(|state| {
+            if !state.status.contains_key(worker_id) {
+                return Err(PoolError::UnknownWorker(worker_id.clone()));
+            }
+            state.draining.insert(worker_id.clone());
+            Ok(())
+        })?;
+        log::info!("节点 {} 开始排空", worker_id);
+        Ok(())
+    }
+
+    /// 移除节点：在途任务重新入队，主池空缺由备用节点补足，并通知drone退出；返回重新入队的任务数
+    ///
+    /// 节点ID在`unevict_worker`解除前不能重新注册(包括监管器重启的drone子进程)
+    pub fn evict_worker(&mut self, worker_id: &ProcessId) -> crate::Result<usize> {
+        let max_main_pool_size = self.config.max_main_pool_size;
+        let (identity, promoted) = self.with_state_mut(|state| {
+            if state.status.remove(worker_id).is_none() {
+                return Err(PoolError::UnknownWorker(worker_id.clone()));
+            }
+            state.evicted.insert(worker_id.clone());
+            state.draining.remove(worker_id);
+            state.workers.retain(|w| &w.id != worker_id);
+            state.backup_drones.retain(|w| &w.id != worker_id);
+            let mut promoted = Vec::new();
+            while state.workers.len() < max_main_pool_size && !state.backup_drones.is_empty() {
+                let backup = state.backup_drones.remove(0);
+                promoted.push(backup.id.clone());
+                state.workers.push(backup);
+            }
+            Ok((state.identities.remove(worker_id), promoted))
+        })?;
+
+        log::warn!("节点 {} 已被移出进程池", worker_id);
+        metrics::counter!("zergpool.workers_evicted").increment(1);
+        super::clear_worker_gauges(worker_id);
+        self.selector.end_warmup(worker_id);
+        let requeued = self.requeue_worker_tasks(worker_id);
+        self.events.publish(PoolEvent::WorkerRemoved { worker_id: worker_id.clone(), reason: RemovalReason::Evicted });
+        for id in promoted {
+            log::info!("节点晋升到主池替补: {}", id);
+            self.selector.begin_warmup(&id);
+            self.events.publish(PoolEvent::ScaledOut { worker_id: id });
+        }
+        if let Some(identity) = identity {
+            self.send_control(&identity, ControlKind::Evicted, "evicted by administrator")?;
+        }
+        Ok(requeued)
+    }
+
+    /// 解除移除：允许节点重新注册，监管器中对应的drone子进程随之重新启动
+    pub fn unevict_worker(&mut self, worker_id: &ProcessId) -> crate::Result<()> {
+        if !self.with_state_mut(|state| state.evicted.remove(worker_id)) {
+            return Err(PoolError::UnknownWorker(worker_id.clone()));
+        }
+        if let Some(supervisor) = self.supervisor.as_mut() {
+            supervisor.resume(worker_id);
+        }
+        log::info!("节点 {} 已解除移除，可以重新注册", worker_id);
+        Ok(())
+    }
+
+    /// 已被移除、等待解除的节点
+    pub fn evicted_workers(&self) -> Vec<ProcessId> {
+        let mut evicted: Vec<ProcessId> = self.with_state(|state| state.evicted.iter().cloned().collect());
+        evicted.sort();
+        evicted
+    }
+
+    /// 将备用节点晋升到主池；主池已满时主池末尾节点降级到备用池头部
+    pub fn promote_worker(&mut self, worker_id: &ProcessId) -> crate::Result<()> {
+        let max_main_pool_size = self.config.max_main_pool_size;
+        let demoted = self.with_state_mut(|state| {
+            if state.workers.iter().any(|w| &w.id == worker_id) {
+                return Ok(None);
+            }
+            let index = state.backup_drones.iter().position(|w| &w.id == worker_id)
+                .ok_or_else(|| PoolError::UnknownWorker(worker_id.clone()))?;
+            let demoted = if state.workers.len() >= max_main_pool_size {
+                state.workers.pop()
+            } else {
+                None
+            };
+            let backup = state.backup_drones.remove(index);
+            state.workers.push(backup);
+            if let Some(demoted) = &demoted {
+                state.backup_drones.insert(0, Arc::clone(demoted));
+            }
+            Ok::<_, PoolError>(Some(demoted.map(|w| w.id.clone())))
+        })?;
+
+        let Some(demoted) = demoted else {
+            return Ok(());
+        };
+        log::info!("节点 {} 已晋升到主池", worker_id);
+        self.selector.begin_warmup(worker_id);
+        if let Some(id) = demoted {
+            log::info!("主池已满，节点降级到备用池: {}", id);
+            self.selector.end_warmup(&id);
+            self.events.publish(PoolEvent::ScaledIn { worker_id: id });
+        }
+        self.events.publish(PoolEvent::ScaledOut { worker_id: worker_id.clone() });
+        Ok(())
+    }
+
+    /// 暂停派发，已提交的任务留在待派发队列中
+    pub fn pause_dispatch(&mut self) {
+        if !self.dispatch_paused {
+            log::warn!("任务派发已暂停");
+        }
+        self.dispatch_paused = true;
+    }
+
+    /// 恢复派发
+    pub fn resume_dispatch(&mut self) {
+        if self.dispatch_paused {
+            log::info!("任务派发已恢复");
+        }
+        self.dispatch_paused = false;
+    }
+
+    /// 派发是否已暂停
+    pub fn is_dispatch_paused(&self) -> bool {
+        self.dispatch_paused
+    }
+
+    /// 死信任务(按进入时间排序)
+    pub fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.with_state(|state| state.dead_letters.iter().cloned().collect())
+    }
+
+    /// 队列状态
+    pub fn queue_info(&self) -> QueueInfo {
+        self.with_state(|state| QueueInfo {
+            pending: state.pending.len(),
+            in_flight: state.in_flight.len(),
+            dead_letters: state.dead_letters.len(),
+            dispatch_paused: self.dispatch_paused,
+            is_leader: self.is_leader(),
+        })
+    }
+
+    /// 处理控制端点请求
+    pub fn handle_admin(&mut self, request: AdminRequest) -> AdminResponse {
+        let result = match request {
+            AdminRequest::ListWorkers => Ok(AdminResponse::Workers { workers: self.workers() }),
+            AdminRequest::ListBackups => {
+                let mut workers = self.workers();
+                workers.retain(|w| w.role == WorkerRole::Backup);
+                Ok(AdminResponse::Workers { workers })
+            }
+            AdminRequest::Drain { worker_id } => self.drain_worker(&worker_id)
+                .map(|_| ok(format!("节点 {} 开始排空", worker_id))),
+            AdminRequest::Evict { worker_id } => self.evict_worker(&worker_id)
+                .map(|requeued| ok(format!("节点 {} 已移除，{} 个任务重新入队", worker_id, requeued))),
+            AdminRequest::Unevict { worker_id } => self.unevict_worker(&worker_id)
+                .map(|_| ok(format!("节点 {} 已解除移除", worker_id))),
+            AdminRequest::Promote { worker_id } => self.promote_worker(&worker_id)
+                .map(|_| ok(format!("节点 {} 已在主池中", worker_id))),
+            AdminRequest::PauseDispatch => {
+                self.pause_dispatch();
+                Ok(ok("任务派发已暂停".to_string()))
+            }
+            AdminRequest::ResumeDispatch => {
+                self.resume_dispatch();
+                Ok(ok("任务派发已恢复".to_string()))
+            }
+            AdminRequest::Queue => Ok(AdminResponse::Queue(self.queue_info())),
+            AdminRequest::DeadLetters => Ok(AdminResponse::DeadLetters { tasks: self.dead_letters() }),
+            AdminRequest::Config => Ok(AdminResponse::Config { config: Box::new(self.config.clone()) }),
+        };
+        result.unwrap_or_else(|e| AdminResponse::Error { message: e.to_string() })
+    }
+}
+
+fn ok(message: String) -> AdminResponse {
+    AdminResponse::Ok { message }
+}
+
+/// 控制端点客户端错误类型
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("IO错误: {0}")]
+    Io(#[from] io::Error),
+    #[error("JSON编解码错误: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("控制端点已关闭连接")]
+    Closed,
+    #[error("请求被拒绝: {0}")]
+    Rejected(String),
+}
+
+/// 控制端点服务(每个连接一个线程，进程退出时随之结束)
+pub struct AdminServer {
+    local_addr: SocketAddr,
+}
+
+impl AdminServer {
+    /// 绑定地址并在后台线程接受连接，携带`token`的请求转发到事件循环的命令通道
+    ///
+    /// 控制端点可以移除节点、导出配置，必须配置非空的共享令牌
+    pub fn bind(addr: impl ToSocketAddrs, token: impl Into<String>, commands: Sender<PoolCommand>) -> io::Result<Self> {
+        let token: Arc<str> = token.into().into();
+        if token.trim().is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "控制端点令牌不能为空"));
+        }
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        thread::Builder::new()
+            .name("zerg-queen-admin".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            log::warn!("接受控制连接失败: {}", e);
+                            continue;
+                        }
+                    };
+                    let commands = commands.clone();
+                    let token = Arc::clone(&token);
+                    let spawned = thread::Builder::new()
+                        .name("zerg-queen-admin-conn".to_string())
+                        .spawn(move || {
+                            if let Err(e) = serve_connection(stream, &token, &commands) {
+                                log::debug!("控制连接异常断开: {}", e);
+                            }
+                        });
+                    if let Err(e) = spawned {
+                        log::error!("启动控制连接线程失败: {}", e);
+                    }
+                }
+            })?;
+        log::info!("控制端点已启动: {}", local_addr);
+        Ok(Self { local_addr })
+    }
+
+    /// 实际绑定的地址(端口为0时返回系统分配的端口)
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+fn serve_connection(stream: TcpStream, token: &str, commands: &Sender<PoolCommand>) -> io::Result<()> {
+    let peer = stream.peer_addr()?;
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<AdminEnvelope>(&line) {
+            Ok(envelope) if !constant_time_eq(envelope.token.as_bytes(), token.as_bytes()) => {
+                log::warn!("拒绝未授权的控制请求: {}", peer);
+                metrics::counter!("zergpool.admin_unauthorized").increment(1);
+                AdminResponse::Error { message: "未授权: 控制端点令牌无效".to_string() }
+            }
+            Ok(AdminEnvelope { request, .. }) => {
+                log::info!("收到控制请求: {:?}", request);
+                let (reply, result) = oneshot::channel();
+                commands.send(PoolCommand::Admin(request, reply)).ok()
+                    .and_then(|_| result.blocking_recv().ok())
+                    .unwrap_or_else(|| AdminResponse::Error { message: PoolError::Stopped.to_string() })
+            }
+            Err(e) => AdminResponse::Error { message: format!("无效的请求: {}", e) },
+        };
+        let mut encoded = serde_json::to_string(&response)?;
+        encoded.push('\n');
+        writer.write_all(encoded.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// 控制端点客户端
+pub struct AdminClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    token: String,
+}
+
+impl AdminClient {
+    /// 连接控制端点，每个请求携带`token`；`timeout`同时作为连接与等待回复的超时
+    pub fn connect(addr: impl ToSocketAddrs, token: impl Into<String>, timeout: Duration) -> Result<Self, AdminError> {
+        let token = token.into();
+        let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "地址解析结果为空");
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    return Ok(Self {
+                        reader: BufReader::new(stream.try_clone()?),
+                        writer: stream,
+                        token,
+                    });
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error.into())
+    }
+
+    /// 发送请求并等待回复，`Error`回复转为`AdminError::Rejected`
+    pub fn request(&mut self, request: &AdminRequest) -> Result<AdminResponse, AdminError> {
+        let envelope = AdminEnvelope { token: self.token.clone(), request: request.clone() };
+        let mut encoded = serde_json::to_string(&envelope)?;
+        encoded.push('\n');
+        self.writer.write_all(encoded.as_bytes())?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(AdminError::Closed);
+        }
+        match serde_json::from_str(&line)? {
+            AdminResponse::Error { message } => Err(AdminError::Rejected(message)),
+            response => Ok(response),
+        }
+    }
+}
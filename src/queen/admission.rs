@@ -0,0 +1,121 @@
+//! drone注册准入控制
+//!
+//! 注册消息依次检查节点ID、线协议版本、注册令牌、crate版本兼容性、是否已被移出、重复ID与节点数上限，
+//! 未通过时回复`RegistrationRejected`说明原因，不写入进程池；
+//! 通过后向上报了协议版本的drone回复`RegistrationAccepted`完成协议协商。
+//! 同一连接重复注册(重连、故障转移)视为刷新，不受重复ID与节点数上限限制。
+//! 注册通过后节点ID与连接身份绑定，心跳与任务结果只接受来自绑定连接的消息
+
+use super::events::PoolEvent;
+use super::DronePool;
+use crate::config::DuplicatePolicy;
+use crate::proto::zergpool::{Registration, RegistrationAccepted, RegistrationRejected};
+use crate::version::{self, Version, CRATE_VERSION, PROTOCOL_VERSION};
+use crate::{ProcessMessage, RegistrationError};
+
+/// 比较令牌(耗时与不同字节的位置无关)
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl DronePool {
+    /// 消息是否来自节点注册时绑定的连接
+    pub(super) fn is_admitted(&self, identity: &str, worker_id: &str) -> bool {
+        self.with_state(|state| state.identities.get(worker_id).is_some_and(|bound| bound == identity))
+    }
+
+    /// 按准入策略检查注册消息
+    ///
+    /// 通过时返回需要被接管的原连接身份(仅`DuplicatePolicy::Replace`)
+    pub(super) fn admit(&self, identity: &str, reg: &Registration) -> Result<Option<String>, RegistrationError> {
+        let admission = &self.config.admission;
+        if reg.worker_id.trim().is_empty() {
+            return Err(RegistrationError::InvalidWorkerId);
+        }
+        let protocol_ok = version::parse_protocol(&reg.protocol_version)
+            .is_ok_and(|protocol| version::protocol_compatible(&Version::protocol(), &protocol));
+        if !protocol_ok {
+            return Err(RegistrationError::IncompatibleProtocol {
+                drone: reg.protocol_version.clone(),
+                queen: PROTOCOL_VERSION.to_string(),
+            });
+        }
+        if let Some(token) = &admission.token {
+            if !constant_time_eq(reg.token.as_bytes(), token.as_bytes()) {
+                return Err(RegistrationError::InvalidToken);
+            }
+        }
+        if admission.check_version {
+            let compatible = reg.version.parse::<Version>()
+                .is_ok_and(|version| version.is_compatible(&Version::current()));
+            if !compatible {
+                return Err(RegistrationError::IncompatibleVersion {
+                    drone: reg.version.clone(),
+                    queen: CRATE_VERSION.to_string(),
+                });
+            }
+        }
+
+        let heartbeat_timeout = self.config.heartbeat_timeout();
+        self.with_state(|state| {
+            if state.evicted.contains(&reg.worker_id) {
+                return Err(RegistrationError::Evicted(reg.worker_id.clone()));
+            }
+            let known = state.workers.iter().chain(state.backup_drones.iter())
+                .any(|p| p.id == reg.worker_id);
+            // 原连接仍在发送心跳时才视为ID冲突，失联后的新连接按重连处理
+            let online = state.status.get(&reg.worker_id)
+                .is_some_and(|s| s.last_heartbeat.elapsed() < heartbeat_timeout);
+            let previous = state.identities.get(&reg.worker_id).filter(|previous| *previous != identity);
+            if let (Some(previous), true) = (previous, online) {
+                return match admission.duplicate_ids {
+                    DuplicatePolicy::Reject => Err(RegistrationError::DuplicateId(reg.worker_id.clone())),
+                    DuplicatePolicy::Replace => Ok(Some(previous.clone())),
+                };
+            }
+            let total = state.workers.len() + state.backup_drones.len();
+            if !known && admission.max_workers.is_some_and(|max| total >= max) {
+                return Err(RegistrationError::PoolFull);
+            }
+            Ok(None)
+        })
+    }
+
+    /// 拒绝注册并回复原因
+    ///
+    /// 回复失败(如对端已断开)只记录日志，不影响其他消息的处理
+    pub(super) fn reject_registration(&mut self, identity: &str, reg: &Registration, error: RegistrationError) {
+        let reason = error.reject_reason();
+        tracing::warn!(
+            identity = %identity,
+            worker_id = %reg.worker_id,
+            version = %reg.version,
+            protocol_version = %reg.protocol_version,
+            reason = reason.as_str_name(),
+            error = %error,
+            "拒绝节点注册"
+        );
+        metrics::counter!("zergpool.registrations_rejected", "reason" => reason.as_str_name()).increment(1);
+        self.events.publish(PoolEvent::RegistrationRejected { worker_id: reg.worker_id.clone(), reason });
+        let rejected = RegistrationRejected {
+            reason: reason as i32,
+            message: error.to_string(),
+            queen_version: CRATE_VERSION.to_string(),
+            protocol_version: PROTOCOL_VERSION.to_string(),
+        };
+        if let Err(e) = self.network.send_message(identity, &ProcessMessage::RegistrationRejected(rejected)) {
+            tracing::warn!(identity = %identity, error = %e, "注册拒绝回复发送失败");
+        }
+    }
+
+    /// 回复注册成功并告知queen协议版本
+    pub(super) fn accept_registration(&mut self, identity: &str) {
+        let accepted = RegistrationAccepted {
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            queen_version: CRATE_VERSION.to_string(),
+        };
+        if let Err(e) = self.network.send_message(identity, &ProcessMessage::RegistrationAccepted(accepted)) {
+            tracing::warn!(identity = %identity, error = %e, "注册成功回复发送失败");
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Queen异步接口
+//!
+//! 进程池在独立IO线程中运行事件循环，异步调用方通过命令通道提交请求，
+//! 回复经`oneshot`送回，不会阻塞tokio运行时线程
+
+use crossbeam_channel::{Receiver, Sender};
+use tokio::sync::oneshot;
+
+use super::admin::{AdminRequest, AdminResponse};
+use super::events::PoolEvent;
+use super::{DronePool, PoolCommand, PoolHandle, PoolStats};
+use crate::config::PoolConfig;
+use crate::proto::zergpool::Task;
+use crate::PoolError;
+
+/// 异步进程池
+pub struct AsyncDronePool {
+    sender: Sender<PoolCommand>,
+    handle: Option<PoolHandle>,
+}
+
+impl AsyncDronePool {
+    /// 创建进程池并在IO线程中启动事件循环
+    pub fn bind(bind_addr: &str, port: u16, config: PoolConfig) -> crate::Result<Self> {
+        Ok(Self::spawn(DronePool::with_config(bind_addr, port, config)?))
+    }
+
+    /// 在IO线程中运行已创建的进程池
+    pub fn spawn(pool: DronePool) -> Self {
+        let handle = pool.spawn();
+        Self {
+            sender: handle.sender(),
+            handle: Some(handle),
+        }
+    }
+
+    /// 提交任务，事件循环确认入队后返回
+    ///
+    /// 配置任务日志时，返回`Ok`表示提交记录已落盘；未配置时仅表示已进入内存队列
+    pub async fn submit(&self, task: Task) -> crate::Result<()> {
+        let (reply, result) = oneshot::channel();
+        self.sender.send(PoolCommand::Submit(task, reply)).map_err(|_| PoolError::Stopped)?;
+        result.await.map_err(|_| PoolError::Stopped)?
+    }
+
+    /// 查询进程池状态
+    pub async fn stats(&self) -> crate::Result<PoolStats> {
+        let (reply, result) = oneshot::channel();
+        self.sender.send(PoolCommand::Stats(reply)).map_err(|_| PoolError::Stopped)?;
+        result.await.map_err(|_| PoolError::Stopped)
+    }
+
+    /// 执行运维管理请求
+    pub async fn admin(&self, request: AdminRequest) -> crate::Result<AdminResponse> {
+        let (reply, result) = oneshot::channel();
+        self.sender.send(PoolCommand::Admin(request, reply)).map_err(|_| PoolError::Stopped)?;
+        result.await.map_err(|_| PoolError::Stopped)
+    }
+
+    /// 订阅进程池生命周期事件(接收端为同步通道，可在独立线程或`spawn_blocking`中消费)
+    pub async fn subscribe(&self) -> crate::Result<Receiver<PoolEvent>> {
+        let (reply, result) = oneshot::channel();
+        self.sender.send(PoolCommand::Subscribe(reply)).map_err(|_| PoolError::Stopped)?;
+        result.await.map_err(|_| PoolError::Stopped)
+    }
+
+    /// 停止事件循环，在阻塞线程池中等待IO线程退出
+    pub async fn shutdown(mut self) -> crate::Result<()> {
+        let Some(handle) = self.handle.take() else {
+            return Ok(());
+        };
+        tokio::task::spawn_blocking(move || handle.shutdown())
+            .await
+            .map_err(|_| PoolError::Stopped)?
+    }
+}
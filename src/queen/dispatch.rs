@@ -0,0 +1,344 @@
+//! Queen端任务队列与派发
+//!
+//! 维护待派发队列和在途任务表，负责任务的提交、派发、完成与失败重试，
+//! 启用任务日志时所有状态变化先写入日志(确认提交前及每轮事件循环结束时统一落盘)；超过重试次数的任务进入内存中的死信队列。
+//! 各状态变化同时发布为`PoolEvent`。
+//!
+//! 提交、派发与完成各自创建span：提交时把trace-context写入队列中的任务，
+//! 派发时在发往drone的副本上写入派发span的上下文，使drone端的执行span挂在派发span之下
+
+use std::collections::HashSet;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+use super::events::PoolEvent;
+use super::journal::JournalRecord;
+use super::DronePool;
+use crate::proto::zergpool::{response, HealthState, Response, Task};
+use crate::telemetry;
+use crate::ProcessId;
+
+/// 待派发任务
+#[derive(Debug, Clone)]
+pub(crate) struct QueuedTask {
+    pub(crate) task: Task,
+    /// 已重试次数
+    pub(crate) attempts: u32,
+    /// 最早可派发时间(重试退避)
+    pub(crate) ready_at: Instant,
+}
+
+impl QueuedTask {
+    pub(crate) fn new(task: Task) -> Self {
+        Self {
+            task,
+            attempts: 0,
+            ready_at: Instant::now(),
+        }
+    }
+}
+
+/// 在途任务
+#[derive(Debug, Clone)]
+pub(crate) struct InFlightTask {
+    pub(crate) task: Task,
+    pub(crate) worker_id: ProcessId,
+    pub(crate) attempts: u32,
+    pub(crate) dispatched_at: Instant,
+}
+
+/// 死信任务(超过重试次数仍失败，不再派发)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub task: Task,
+    /// 最后一次执行的节点
+    pub worker_id: ProcessId,
+    /// 已重试次数
+    pub attempts: u32,
+    /// 最后一次失败的错误信息
+    pub error: String,
+    /// 进入死信队列的时间(Unix毫秒)
+    pub failed_at_ms: u64,
+}
+
+impl DronePool {
+    /// 提交任务到待派发队列
+    ///
+    /// 任务元数据中已有`traceparent`时作为提交span的父上下文，随后替换为提交span的上下文
+    pub fn submit_task(&mut self, mut task: Task) -> crate::Result<()> {
+        let upstream = telemetry::extract(&task.metadata);
+        let span = tracing::info_span!(
+            "task.submit",
+            task_id = %task.id,
+            traceparent = task.metadata.get(telemetry::TRACEPARENT_KEY).map(String::as_str),
+        );
+        let _entered = span.enter();
+        if !self.is_leader() {
+            return Err(crate::PoolError::NotLeader(self.leader_endpoint().unwrap_or_default()));
+        }
+        telemetry::inject(&mut task.metadata, &telemetry::propagation_context(&span, upstream));
+        self.append_journal(&JournalRecord::Submit { task: task.clone() })?;
+        tracing::debug!(task_id = %task.id, "任务已入队");
+        self.events.publish(PoolEvent::TaskQueued { task_id: task.id.clone() });
+        self.with_state_mut(|state| state.pending.push_back(QueuedTask::new(task)));
+        self.selector.on_task_submitted();
+        Ok(())
+    }
+
+    /// 将待派发任务派发给已连接的最优工作节点，返回本次派发数量
+    pub fn dispatch_pending(&mut self) -> crate::Result<usize> {
+        // 只有已建立连接(已知身份帧)的节点才能接收任务
+        let identities = self.with_state(|state| state.identities.clone());
+        // 本轮发送失败的节点不再参与选择，任务改派给其他节点
+        let mut unreachable = HashSet::new();
+        let mut dispatched = 0;
+        // 每次派发前确认租约仍然有效，租约临近到期时停止派发
+        while self.is_leader() {
+            let now = Instant::now();
+            let Some(queued) = self.with_state_mut(|state| {
+                let index = state.pending.iter().position(|q| q.ready_at <= now)?;
+                state.pending.remove(index)
+            }) else {
+                break;
+            };
+
+            let span = tracing::info_span!(
+                "task.dispatch",
+                task_id = %queued.task.id,
+                attempt = queued.attempts,
+                worker = tracing::field::Empty,
+                traceparent = queued.task.metadata.get(telemetry::TRACEPARENT_KEY).map(String::as_str),
+            );
+            let _entered = span.enter();
+            let selected = tracing::info_span!("task.select")
+                .in_scope(|| self.select_worker(|id| identities.contains_key(id) && !unreachable.contains(id)));
+            let Some(worker_id) = selected else {
+                self.with_state_mut(|state| state.pending.push_front(queued));
+                break;
+            };
+            span.record("worker", worker_id.as_str());
+            let identity = &identities[&worker_id];
+            // 队列中的任务保留提交span的上下文，重试时的派发span仍挂在提交span之下
+            let mut outgoing = queued.task.clone();
+            let context = telemetry::propagation_context(&span, telemetry::extract(&queued.task.metadata));
+            telemetry::inject(&mut outgoing.metadata, &context);
+
+            let record = JournalRecord::Dispatch {
+                task_id: queued.task.id.clone(),
+                worker_id: worker_id.clone(),
+            };
+            if let Err(e) = self.append_journal(&record) {
+                self.with_state_mut(|state| state.pending.push_front(queued));
+                return Err(e);
+            }
+            if let Err(e) = self.network.send_task(identity, &outgoing) {
+                // 单个节点发送失败不影响其他任务：标记为不健康，等待下次心跳恢复或被清理
+                log::warn!("向节点 {} 派发任务 {} 失败: {}", worker_id, queued.task.id, e);
+                self.with_state_mut(|state| {
+                    state.pending.push_front(queued);
+                    if let Some(status) = state.status.get_mut(&worker_id) {
+                        status.health_state = HealthState::Unhealthy;
+                    }
+                });
+                unreachable.insert(worker_id);
+                continue;
+            }
+
+            self.events.publish(PoolEvent::TaskDispatched {
+                task_id: queued.task.id.clone(),
+                worker_id: worker_id.clone(),
+                attempt: queued.attempts,
+            });
+            self.with_state_mut(|state| {
+                // 在下次心跳前先行计入任务数，避免同一节点被连续选中
+                if let Some(status) = state.status.get_mut(&worker_id) {
+                    status.current_tasks += 1;
+                }
+                state.in_flight.insert(queued.task.id.clone(), InFlightTask {
+                    task: queued.task,
+                    worker_id,
+                    attempts: queued.attempts,
+                    dispatched_at: Instant::now(),
+                });
+            });
+            dispatched += 1;
+        }
+        metrics::counter!("zergpool.tasks_dispatched").increment(dispatched as u64);
+        Ok(dispatched)
+    }
+
+    /// 处理drone返回的任务结果
+    ///
+    /// 失败的任务按重试策略延迟后重新入队，超过重试次数或成功时记为完成；
+    /// 结果的`worker_id`与任务派发的节点不一致时忽略
+    pub fn complete_task(&mut self, response: &Response) -> crate::Result<()> {
+        let Some(in_flight) = self.with_state_mut(|state| {
+            // 只接受任务派发目标节点回传的结果
+            if state.in_flight.get(&response.task_id)?.worker_id != response.worker_id {
+                return None;
+            }
+            let in_flight = state.in_flight.remove(&response.task_id)?;
+            if let Some(status) = state.status.get_mut(&in_flight.worker_id) {
+                status.current_tasks = status.current_tasks.saturating_sub(1);
+            }
+            Some(in_flight)
+        }) else {
+            tracing::warn!(task_id = %response.task_id, worker_id = %response.worker_id, "收到未知任务或非派发节点的结果");
+            return Ok(());
+        };
+        let span = tracing::info_span!(
+            "task.complete",
+            task_id = %in_flight.task.id,
+            worker = %in_flight.worker_id,
+            outcome = tracing::field::Empty,
+            traceparent = in_flight.task.metadata.get(telemetry::TRACEPARENT_KEY).map(String::as_str),
+        );
+        let _entered = span.enter();
+
+        let latency = in_flight.dispatched_at.elapsed();
+        self.selector.on_task_completed(latency);
+        let outcome = if matches!(response.result, Some(response::Result::Error(_))) { "failure" } else { "success" };
+        metrics::histogram!("zergpool.task.latency_seconds", "outcome" => outcome).record(latency.as_secs_f64());
+        span.record("outcome", outcome);
+
+        if let Some(response::Result::Error(err)) = &response.result {
+            metrics::counter!("zergpool.tasks_failed").increment(1);
+            let retry = self.config.retry;
+            if in_flight.attempts < retry.max_retries {
+                let attempts = in_flight.attempts + 1;
+                tracing::warn!(task_id = %in_flight.task.id, worker_id = %in_flight.worker_id, attempts, error = %err, "任务执行失败，等待重试");
+                metrics::counter!("zergpool.tasks_retried").increment(1);
+                self.events.publish(PoolEvent::TaskRetried {
+                    task_id: in_flight.task.id.clone(),
+                    worker_id: in_flight.worker_id.clone(),
+                    attempt: attempts,
+                    error: err.clone(),
+                });
+                // 记录已重试次数，崩溃恢复后不会重新计数
+                self.append_journal(&JournalRecord::Retry { task_id: in_flight.task.id.clone(), attempts })?;
+                self.with_state_mut(|state| {
+                    state.pending.push_back(QueuedTask {
+                        task: in_flight.task,
+                        attempts,
+                        ready_at: Instant::now() + retry.backoff(attempts),
+                    });
+                });
+                return Ok(());
+            }
+            tracing::error!(
+                task_id = %in_flight.task.id,
+                worker_id = %in_flight.worker_id,
+                attempts = in_flight.attempts,
+                error = %err,
+                "任务超过重试次数仍失败，进入死信队列"
+            );
+            metrics::counter!("zergpool.tasks_dead_lettered").increment(1);
+            self.events.publish(PoolEvent::TaskFailed {
+                task_id: in_flight.task.id.clone(),
+                worker_id: in_flight.worker_id.clone(),
+                attempts: in_flight.attempts,
+                error: err.clone(),
+            });
+            let capacity = self.config.dead_letter_capacity;
+            let failed_at_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+            self.with_state_mut(|state| {
+                if capacity == 0 {
+                    return;
+                }
+                while state.dead_letters.len() >= capacity {
+                    state.dead_letters.pop_front();
+                }
+                state.dead_letters.push_back(DeadLetter {
+                    task: in_flight.task.clone(),
+                    worker_id: in_flight.worker_id.clone(),
+                    attempts: in_flight.attempts,
+                    error: err.clone(),
+                    failed_at_ms,
+                });
+            });
+        } else {
+            metrics::counter!("zergpool.tasks_completed").increment(1);
+            self.events.publish(PoolEvent::TaskCompleted {
+                task_id: in_flight.task.id.clone(),
+                worker_id: in_flight.worker_id.clone(),
+                latency_ms: latency.as_millis() as u64,
+            });
+        }
+
+        self.append_journal(&JournalRecord::Complete { task_id: in_flight.task.id.clone() })?;
+        self.maybe_compact_journal()?;
+        Ok(())
+    }
+
+    /// 将指定节点的在途任务放回待派发队列头部(节点失联时调用)
+    pub(crate) fn requeue_worker_tasks(&mut self, worker_id: &ProcessId) -> usize {
+        let requeued = self.with_state_mut(|state| {
+            let ids: Vec<String> = state.in_flight.iter()
+                .filter(|(_, t)| &t.worker_id == worker_id)
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in &ids {
+                let in_flight = state.in_flight.remove(id).unwrap();
+                tracing::warn!(task_id = %id, worker_id = %worker_id, "任务所在节点失联，重新入队");
+                state.pending.push_front(QueuedTask {
+                    task: in_flight.task,
+                    attempts: in_flight.attempts,
+                    ready_at: Instant::now(),
+                });
+            }
+            ids
+        });
+        let count = requeued.len();
+        self.events.publish_all(requeued.into_iter().map(|task_id| PoolEvent::TaskRequeued {
+            task_id,
+            worker_id: worker_id.clone(),
+        }));
+        count
+    }
+
+    /// 待派发任务数量
+    pub fn pending_task_count(&self) -> usize {
+        self.with_state(|state| state.pending.len())
+    }
+
+    /// 在途任务数量
+    pub fn in_flight_task_count(&self) -> usize {
+        self.with_state(|state| state.in_flight.len())
+    }
+
+    /// 追加任务日志记录
+    ///
+    /// 租约临近到期时拒绝写入，避免与接管的新leader同时修改共享日志
+    fn append_journal(&mut self, record: &JournalRecord) -> crate::Result<()> {
+        if self.journal.is_some() && !self.is_leader() {
+            return Err(crate::PoolError::NotLeader(self.leader_endpoint().unwrap_or_default()));
+        }
+        if let Some(journal) = self.journal.as_mut() {
+            journal.append(record)?;
+        }
+        Ok(())
+    }
+
+    /// 追加记录数超过阈值时压缩任务日志
+    fn maybe_compact_journal(&mut self) -> crate::Result<()> {
+        if !self.is_leader() {
+            return Ok(());
+        }
+        let threshold = self.config.journal_compact_threshold;
+        let Some(journal) = self.journal.as_mut() else {
+            return Ok(());
+        };
+        if journal.appended_since_compact() < threshold {
+            return Ok(());
+        }
+        let live: Vec<(Task, u32)> = {
+            let state = self.state.lock().unwrap();
+            state.in_flight.values().map(|t| (t.task.clone(), t.attempts))
+                .chain(state.pending.iter().map(|q| (q.task.clone(), q.attempts)))
+                .collect()
+        };
+        journal.compact(live.iter().map(|(task, attempts)| (task, *attempts)))?;
+        log::info!("任务日志已压缩，保留 {} 个未完成任务", live.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+//! Queen高可用选主模块
+//!
+//! 多个queen通过共享的本地租约文件选举leader：
+//! leader按TTL的1/3周期续约，租约过期后由其他queen接管。
+//! leader身份以本节点最后写入的租约到期时间为准，剩余有效期不足TTL的1/4时即视为失去领导权，
+//! 为时钟误差和续约耗时留出余量，避免新旧leader同时派发任务或写入任务日志
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 选主错误类型
+#[derive(Error, Debug)]
+pub enum ElectionError {
+    #[error("租约文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("租约文件格式错误: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+/// 租约内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lease {
+    /// 持有者queen ID
+    pub holder: String,
+    /// 持有者对drone公布的地址
+    pub endpoint: String,
+    /// 任期(每次易主递增)
+    pub term: u64,
+    /// 过期时间(Unix毫秒)
+    pub expires_at_ms: u64,
+}
+
+impl Lease {
+    /// 租约是否已过期
+    pub fn is_expired(&self) -> bool {
+        now_ms() >= self.expires_at_ms
+    }
+}
+
+/// 领导权变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeadershipChange {
+    Unchanged,
+    Acquired,
+    Lost,
+}
+
+/// 基于租约文件的leader选举
+#[derive(Debug)]
+pub struct LeaderElection {
+    lease_path: PathBuf,
+    node_id: String,
+    endpoint: String,
+    ttl: Duration,
+    is_leader: bool,
+    /// 本节点最后写入的租约到期时间(Unix毫秒)
+    expires_at_ms: u64,
+    term: u64,
+    last_attempt: Option<Instant>,
+}
+
+impl LeaderElection {
+    /// 创建选举实例(尚未参与选举)
+    pub fn new(lease_path: impl Into<PathBuf>, node_id: impl Into<String>, endpoint: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            lease_path: lease_path.into(),
+            node_id: node_id.into(),
+            endpoint: endpoint.into(),
+            ttl,
+            is_leader: false,
+            expires_at_ms: 0,
+            term: 0,
+            last_attempt: None,
+        }
+    }
+
+    /// 当前是否为leader(持有的租约距到期不足安全余量时返回false)
+    pub fn is_leader(&self) -> bool {
+        let deadline = self.expires_at_ms.saturating_sub(self.margin().as_millis() as u64);
+        self.is_leader && now_ms() < deadline
+    }
+
+    /// 租约到期前停止行使leader职责的安全余量
+    fn margin(&self) -> Duration {
+        self.ttl / 4
+    }
+
+    /// 当前节点ID
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// 当前任期
+    pub fn term(&self) -> u64 {
+        self.term
+    }
+
+    /// 读取当前租约
+    pub fn current_lease(&self) -> Result<Option<Lease>, ElectionError> {
+        read_lease(&self.lease_path)
+    }
+
+    /// 周期驱动选举：leader续约，follower在租约过期后尝试接管
+    ///
+    /// 两次尝试间隔不小于TTL的1/3
+    pub fn tick(&mut self) -> Result<LeadershipChange, ElectionError> {
+        if let Some(last) = self.last_attempt {
+            if last.elapsed() < self.ttl / 3 {
+                return Ok(LeadershipChange::Unchanged);
+            }
+        }
+        self.last_attempt = Some(Instant::now());
+
+        let was_leader = self.is_leader;
+        let acquired = match self.try_acquire() {
+            Ok(acquired) => acquired,
+            // 续约失败时保守地放弃领导权，避免出现双主
+            Err(e) if was_leader => {
+                log::error!("租约续约失败，放弃leader身份: {}", e);
+                false
+            }
+            Err(e) => return Err(e),
+        };
+        self.is_leader = acquired;
+
+        Ok(match (was_leader, acquired) {
+            (false, true) => LeadershipChange::Acquired,
+            (true, false) => LeadershipChange::Lost,
+            _ => LeadershipChange::Unchanged,
+        })
+    }
+
+    /// 主动释放租约(正常关闭时调用)
+    pub fn release(&mut self) -> Result<(), ElectionError> {
+        if !self.is_leader {
+            return Ok(());
+        }
+        self.is_leader = false;
+        self.expires_at_ms = 0;
+        let _guard = match LockGuard::acquire(&self.lease_path, self.ttl)? {
+            Some(guard) => guard,
+            None => return Ok(()),
+        };
+        if let Some(mut lease) = read_lease(&self.lease_path)? {
+            if lease.holder == self.node_id {
+                lease.expires_at_ms = 0;
+                write_lease(&self.lease_path, &lease)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 尝试获取或续约租约，返回是否持有租约
+    fn try_acquire(&mut self) -> Result<bool, ElectionError> {
+        let Some(_guard) = LockGuard::acquire(&self.lease_path, self.ttl)? else {
+            // 其他queen正在修改租约，本轮无法续约，按已持有租约的剩余有效期判断
+            return Ok(self.is_leader());
+        };
+
+        let current = read_lease(&self.lease_path)?;
+        let term = match &current {
+            Some(lease) if lease.holder == self.node_id => lease.term,
+            Some(lease) if !lease.is_expired() => return Ok(false),
+            Some(lease) => lease.term + 1,
+            None => 1,
+        };
+
+        let lease = Lease {
+            holder: self.node_id.clone(),
+            endpoint: self.endpoint.clone(),
+            term,
+            expires_at_ms: now_ms() + self.ttl.as_millis() as u64,
+        };
+        write_lease(&self.lease_path, &lease)?;
+        self.expires_at_ms = lease.expires_at_ms;
+        self.term = term;
+        Ok(true)
+    }
+}
+
+/// 租约文件互斥锁(create_new创建锁文件，释放时删除)
+struct LockGuard {
+    path: PathBuf,
+}
+
+impl LockGuard {
+    fn acquire(lease_path: &Path, ttl: Duration) -> Result<Option<Self>, ElectionError> {
+        let path = lease_path.with_extension("lock");
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => Ok(Some(Self { path })),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                // 持锁进程崩溃遗留的锁文件，超过TTL后清理
+                let stale = std::fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .map(|modified| modified.elapsed().unwrap_or_default() > ttl)
+                    .unwrap_or(false);
+                if stale {
+                    let _ = std::fs::remove_file(&path);
+                }
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn read_lease(path: &Path) -> Result<Option<Lease>, ElectionError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) if content.trim().is_empty() => Ok(None),
+        Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_lease(path: &Path, lease: &Lease) -> Result<(), ElectionError> {
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(&serde_json::to_vec(lease)?)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
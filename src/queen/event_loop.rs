@@ -0,0 +1,261 @@
+//! Queen事件循环
+//!
+//! `DronePool::spawn`在后台线程中运行进程池：处理网络消息、
+//! 定时执行节点清理与负载均衡，并通过命令通道响应其他线程的提交与查询
+
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use tokio::sync::oneshot;
+
+use super::admin::{AdminRequest, AdminResponse};
+use super::events::PoolEvent;
+use super::journal::JournalError;
+use super::DronePool;
+use crate::proto::zergpool::Task;
+use crate::PoolError;
+
+/// 事件循环中单次网络轮询的等待时间(ms)，决定命令的响应延迟
+const LOOP_POLL_TIMEOUT_MS: i64 = 10;
+
+/// 提交命令的回复通道
+type SubmitReply = oneshot::Sender<crate::Result<()>>;
+
+/// 发送给事件循环的命令
+///
+/// 回复通道使用`oneshot`，同步调用方`blocking_recv`(不可在tokio运行时中)，异步调用方直接`await`
+#[derive(Debug)]
+pub enum PoolCommand {
+    /// 提交任务
+    Submit(Task, SubmitReply),
+    /// 查询进程池状态
+    Stats(oneshot::Sender<PoolStats>),
+    /// 运维管理请求(控制端点)
+    Admin(AdminRequest, oneshot::Sender<AdminResponse>),
+    /// 订阅生命周期事件
+    Subscribe(oneshot::Sender<Receiver<PoolEvent>>),
+    /// 停止事件循环
+    Shutdown,
+}
+
+/// 进程池状态快照
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    pub workers: usize,
+    pub backups: usize,
+    pub pending_tasks: usize,
+    pub in_flight_tasks: usize,
+    pub is_leader: bool,
+}
+
+/// 后台事件循环句柄
+///
+/// 查询与提交方法阻塞等待回复，在tokio运行时中(包括`spawn_blocking`线程)调用时返回
+/// `PoolError::BlockingInRuntime`，异步调用方请使用`AsyncDronePool`
+pub struct PoolHandle {
+    sender: Sender<PoolCommand>,
+    thread: Option<JoinHandle<crate::Result<()>>>,
+}
+
+impl PoolHandle {
+    /// 提交任务并等待事件循环确认入队
+    ///
+    /// 配置任务日志时，返回`Ok`表示提交记录已落盘；未配置时仅表示已进入内存队列
+    pub fn submit(&self, task: Task) -> crate::Result<()> {
+        self.request(|reply| PoolCommand::Submit(task, reply))?
+    }
+
+    /// 查询进程池状态
+    pub fn stats(&self) -> crate::Result<PoolStats> {
+        self.request(PoolCommand::Stats)
+    }
+
+    /// 执行运维管理请求
+    pub fn admin(&self, request: AdminRequest) -> crate::Result<AdminResponse> {
+        self.request(|reply| PoolCommand::Admin(request, reply))
+    }
+
+    /// 订阅进程池生命周期事件
+    pub fn subscribe(&self) -> crate::Result<Receiver<PoolEvent>> {
+        self.request(PoolCommand::Subscribe)
+    }
+
+    /// 发送命令并阻塞等待回复
+    ///
+    /// `blocking_recv`在tokio运行时线程中会panic，因此先检查当前线程是否处于运行时中
+    fn request<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> PoolCommand) -> crate::Result<T> {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(PoolError::BlockingInRuntime);
+        }
+        let (reply, result) = oneshot::channel();
+        self.sender.send(command(reply)).map_err(|_| PoolError::Stopped)?;
+        result.blocking_recv().map_err(|_| PoolError::Stopped)
+    }
+
+    /// 命令发送端(可克隆给其他线程使用)
+    pub fn sender(&self) -> Sender<PoolCommand> {
+        self.sender.clone()
+    }
+
+    /// 停止事件循环并等待线程退出
+    pub fn shutdown(mut self) -> crate::Result<()> {
+        self.stop()
+    }
+
+    /// 事件循环是否已停止
+    pub fn is_stopped(&self) -> bool {
+        self.thread.as_ref().is_none_or(|t| t.is_finished())
+    }
+
+    fn stop(&mut self) -> crate::Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        let _ = self.sender.send(PoolCommand::Shutdown);
+        thread.join().map_err(|_| PoolError::Stopped)?
+    }
+}
+
+impl Drop for PoolHandle {
+    /// 在tokio运行时中释放时只通知事件循环停止，不等待线程退出，避免阻塞运行时线程；
+    /// 需要等待退出时使用`shutdown`或`AsyncDronePool::shutdown`
+    fn drop(&mut self) {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            if self.thread.take().is_some() {
+                let _ = self.sender.send(PoolCommand::Shutdown);
+                log::debug!("在异步上下文中释放进程池句柄，事件循环在后台退出");
+            }
+            return;
+        }
+        if let Err(e) = self.stop() {
+            log::error!("进程池事件循环退出异常: {}", e);
+        }
+    }
+}
+
+impl DronePool {
+    /// 在后台线程中运行事件循环，返回用于提交任务和停止的句柄
+    pub fn spawn(self) -> PoolHandle {
+        let (sender, receiver) = unbounded();
+        let mut pool = self;
+        let thread = thread::Builder::new()
+            .name("zerg-queen".to_string())
+            .spawn(move || pool.run(receiver))
+            .expect("failed to spawn queen event loop");
+        PoolHandle {
+            sender,
+            thread: Some(thread),
+        }
+    }
+
+    /// 在当前线程运行事件循环，直到收到`Shutdown`命令或命令通道关闭
+    ///
+    /// 每轮循环处理所有待处理命令、读空网络消息并派发任务，
+    /// 每隔`check_interval`执行一次节点清理和负载均衡
+    pub fn run(&mut self, commands: Receiver<PoolCommand>) -> crate::Result<()> {
+        self.network.set_poll_timeout(LOOP_POLL_TIMEOUT_MS);
+        let mut last_check = Instant::now();
+        log::info!("queen事件循环已启动");
+
+        loop {
+            let mut submitted = Vec::new();
+            let stop = loop {
+                match commands.try_recv() {
+                    Ok(PoolCommand::Shutdown) => break true,
+                    Ok(command) => self.handle_command(command, &mut submitted),
+                    Err(crossbeam_channel::TryRecvError::Empty) => break false,
+                    Err(crossbeam_channel::TryRecvError::Disconnected) => break true,
+                }
+            };
+            self.acknowledge_submits(submitted);
+            if stop {
+                return self.shutdown();
+            }
+
+            // 网络错误不终止事件循环，记录后继续
+            if let Err(e) = self.poll_events() {
+                log::error!("处理网络事件失败: {}", e);
+                thread::sleep(Duration::from_millis(LOOP_POLL_TIMEOUT_MS as u64));
+            }
+
+            if last_check.elapsed() >= self.config.check_interval() {
+                self.run_timers();
+                last_check = Instant::now();
+            }
+        }
+    }
+
+    /// 当前状态快照
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            workers: self.get_worker_count(),
+            backups: self.get_backup_count(),
+            pending_tasks: self.pending_task_count(),
+            in_flight_tasks: self.in_flight_task_count(),
+            is_leader: self.is_leader(),
+        }
+    }
+
+    /// 处理单条命令，入队成功的提交暂存到`submitted`，等待本轮日志落盘后统一确认
+    fn handle_command(&mut self, command: PoolCommand, submitted: &mut Vec<SubmitReply>) {
+        match command {
+            PoolCommand::Submit(task, reply) => match self.submit_task(task) {
+                Ok(()) => submitted.push(reply),
+                Err(e) => {
+                    let _ = reply.send(Err(e));
+                }
+            },
+            PoolCommand::Stats(reply) => {
+                let _ = reply.send(self.stats());
+            }
+            PoolCommand::Admin(request, reply) => {
+                let _ = reply.send(self.handle_admin(request));
+            }
+            PoolCommand::Subscribe(reply) => {
+                let _ = reply.send(self.subscribe());
+            }
+            PoolCommand::Shutdown => {}
+        }
+    }
+
+    /// 确认本轮入队的提交：配置任务日志时先落盘(组提交)，落盘失败则向调用方返回错误
+    fn acknowledge_submits(&mut self, submitted: Vec<SubmitReply>) {
+        if submitted.is_empty() {
+            return;
+        }
+        let synced = match self.journal.as_mut() {
+            Some(journal) => journal.sync().map_err(|e| e.to_string()),
+            None => Ok(()),
+        };
+        if let Err(message) = &synced {
+            log::error!("任务日志落盘失败，{} 个提交未确认: {}", submitted.len(), message);
+        }
+        for reply in submitted {
+            let result = synced.clone().map_err(|message| {
+                PoolError::Journal(JournalError::Io(std::io::Error::other(message)))
+            });
+            let _ = reply.send(result);
+        }
+    }
+
+    /// 定时任务：清理失联节点、更新负载均衡
+    fn run_timers(&mut self) {
+        let reaped = self.reap_stale_workers();
+        if !reaped.is_empty() {
+            log::warn!("已清理失联节点: {:?}", reaped);
+        }
+        if self.get_worker_count() > 0 || self.get_backup_count() > 0 {
+            self.update_balancer_strategy();
+        }
+    }
+
+    /// 停止事件循环：停止drone子进程并让出leader身份，未完成任务保留在任务日志中
+    fn shutdown(&mut self) -> crate::Result<()> {
+        log::info!("queen事件循环停止，待派发 {} 个，在途 {} 个",
+                   self.pending_task_count(), self.in_flight_task_count());
+        if let Some(supervisor) = self.supervisor.as_mut() {
+            supervisor.shutdown();
+        }
+        self.step_down()
+    }
+}
@@ -0,0 +1,130 @@
+//! Queen生命周期事件
+//!
+//! 节点注册/拒绝注册/健康变化/移除、主备池调整以及任务的入队、派发、完成、重试、失败都会发布为`PoolEvent`，
+//! 通过`DronePool::subscribe`获取的通道接收，可用于审计日志与看板；
+//! 订阅方断开(接收端被丢弃)后自动移除；每个订阅通道最多缓存`EVENT_CHANNEL_CAPACITY`个未消费的事件，
+//! 超出时丢弃新事件并计入`zergpool.events_dropped`，消费缓慢的订阅方不会拖慢事件循环或占满内存
+
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use serde::{Deserialize, Serialize};
+
+use crate::proto::zergpool::{HealthState, RejectReason};
+use crate::ProcessId;
+
+pub use super::admin::WorkerRole;
+
+/// 每个订阅通道缓存的事件上限
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 节点被移出进程池的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    /// 心跳超时达到熔断阈值
+    HeartbeatTimeout,
+    /// 管理员移除
+    Evicted,
+}
+
+/// 进程池事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PoolEvent {
+    /// 新节点注册(重复注册不发布)
+    WorkerRegistered {
+        worker_id: ProcessId,
+        role: WorkerRole,
+        capabilities: Vec<String>,
+    },
+    /// 注册未通过准入检查
+    RegistrationRejected {
+        worker_id: ProcessId,
+        reason: RejectReason,
+    },
+    /// 节点健康状态变化
+    WorkerHealthChanged {
+        worker_id: ProcessId,
+        from: HealthState,
+        to: HealthState,
+    },
+    /// 节点移出进程池
+    WorkerRemoved {
+        worker_id: ProcessId,
+        reason: RemovalReason,
+    },
+    /// 备用节点晋升到主池
+    ScaledOut { worker_id: ProcessId },
+    /// 主池节点降级到备用池
+    ScaledIn { worker_id: ProcessId },
+    /// 任务进入待派发队列
+    TaskQueued { task_id: String },
+    /// 任务派发给节点(`attempt`为已重试次数)
+    TaskDispatched {
+        task_id: String,
+        worker_id: ProcessId,
+        attempt: u32,
+    },
+    /// 任务执行成功
+    TaskCompleted {
+        task_id: String,
+        worker_id: ProcessId,
+        latency_ms: u64,
+    },
+    /// 任务执行失败，等待第`attempt`次重试
+    TaskRetried {
+        task_id: String,
+        worker_id: ProcessId,
+        attempt: u32,
+        error: String,
+    },
+    /// 任务超过重试次数仍失败，进入死信队列
+    TaskFailed {
+        task_id: String,
+        worker_id: ProcessId,
+        attempts: u32,
+        error: String,
+    },
+    /// 任务所在节点失联，任务重新入队
+    TaskRequeued {
+        task_id: String,
+        worker_id: ProcessId,
+    },
+}
+
+/// 事件订阅者列表
+#[derive(Debug, Default)]
+pub(crate) struct EventBus {
+    subscribers: Vec<Sender<PoolEvent>>,
+}
+
+impl EventBus {
+    /// 新增订阅者，返回接收端
+    pub(crate) fn subscribe(&mut self) -> Receiver<PoolEvent> {
+        let (sender, receiver) = bounded(EVENT_CHANNEL_CAPACITY);
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// 向所有订阅者发布事件，通道已满时丢弃该事件，移除已断开的订阅者
+    pub(crate) fn publish(&mut self, event: PoolEvent) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        tracing::trace!(event = ?event, "发布进程池事件");
+        self.subscribers.retain(|s| match s.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                metrics::counter!("zergpool.events_dropped").increment(1);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+
+    /// 依次发布多个事件
+    pub(crate) fn publish_all(&mut self, events: impl IntoIterator<Item = PoolEvent>) {
+        for event in events {
+            self.publish(event);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! 任务预写日志(WAL)模块
+//!
+//! 以JSON Lines格式追加记录任务的提交/派发/重试/完成事件，
+//! queen重启时回放日志，将未完成的任务连同已重试次数重新入队。
+//!
+//! 追加记录只写入操作系统缓冲，由queen在每轮事件循环结束时调用`sync`统一落盘(组提交)，
+//! 崩溃时最多丢失最近一轮循环内的记录
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ProcessId;
+use crate::proto::zergpool::Task;
+
+/// 日志错误类型
+#[derive(Error, Debug)]
+pub enum JournalError {
+    #[error("日志文件读写失败: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("日志记录序列化失败: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// 日志记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalRecord {
+    /// 任务提交
+    Submit { task: Task },
+    /// 任务派发到工作节点
+    Dispatch { task_id: String, worker_id: ProcessId },
+    /// 任务执行失败后重新入队(`attempts`为已重试次数)
+    Retry { task_id: String, attempts: u32 },
+    /// 任务完成(成功或最终失败)
+    Complete { task_id: String },
+}
+
+/// 回放得到的未完成任务
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredTask {
+    pub task: Task,
+    /// 崩溃前已重试次数
+    pub attempts: u32,
+}
+
+/// 任务日志
+pub struct TaskJournal {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    /// 上次压缩后追加的记录数
+    appended: usize,
+    /// 已写入但尚未落盘的记录数
+    unsynced: usize,
+}
+
+impl TaskJournal {
+    /// 打开(或创建)日志文件，返回日志实例与需要重新入队的任务
+    pub fn open(path: impl Into<PathBuf>) -> Result<(Self, Vec<RecoveredTask>), JournalError> {
+        let path = path.into();
+        let unfinished = Self::recover(&path)?;
+        let writer = Self::open_writer(&path)?;
+        let mut journal = Self {
+            path,
+            writer,
+            appended: 0,
+            unsynced: 0,
+        };
+        // 启动时压缩一次，丢弃已完成任务的历史记录
+        journal.compact(unfinished.iter().map(|r| (&r.task, r.attempts)))?;
+        log::info!("任务日志已加载: {}, 待恢复任务 {} 个", journal.path.display(), unfinished.len());
+        Ok((journal, unfinished))
+    }
+
+    /// 回放日志文件，按提交顺序返回未完成的任务
+    ///
+    /// 文件末尾因崩溃产生的不完整记录会被忽略
+    pub fn replay(path: &Path) -> Result<Vec<Task>, JournalError> {
+        Ok(Self::recover(path)?.into_iter().map(|r| r.task).collect())
+    }
+
+    /// 回放日志文件，按提交顺序返回未完成的任务及其已重试次数
+    pub fn recover(path: &Path) -> Result<Vec<RecoveredTask>, JournalError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut order: Vec<String> = Vec::new();
+        let mut tasks: HashMap<String, RecoveredTask> = HashMap::new();
+        for (line_no, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: JournalRecord = match serde_json::from_str(&line) {
+                Ok(record) => record,
+                Err(e) => {
+                    log::warn!("跳过损坏的日志记录(第{}行): {}", line_no + 1, e);
+                    continue;
+                }
+            };
+            match record {
+                JournalRecord::Submit { task } => {
+                    if !tasks.contains_key(&task.id) {
+                        order.push(task.id.clone());
+                    }
+                    tasks.insert(task.id.clone(), RecoveredTask { task, attempts: 0 });
+                }
+                // 崩溃时已派发但未完成的任务同样需要重新入队
+                JournalRecord::Dispatch { .. } => {}
+                JournalRecord::Retry { task_id, attempts } => {
+                    if let Some(recovered) = tasks.get_mut(&task_id) {
+                        recovered.attempts = attempts;
+                    }
+                }
+                JournalRecord::Complete { task_id } => {
+                    tasks.remove(&task_id);
+                }
+            }
+        }
+
+        Ok(order.into_iter().filter_map(|id| tasks.remove(&id)).collect())
+    }
+
+    /// 追加一条记录(写入操作系统缓冲，调用`sync`后落盘)
+    pub fn append(&mut self, record: &JournalRecord) -> Result<(), JournalError> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        self.appended += 1;
+        self.unsynced += 1;
+        Ok(())
+    }
+
+    /// 将上次落盘后追加的记录一次性同步到磁盘
+    pub fn sync(&mut self) -> Result<(), JournalError> {
+        if self.unsynced == 0 {
+            return Ok(());
+        }
+        self.writer.get_ref().sync_data()?;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    /// 压缩日志：仅保留仍未完成任务的提交记录(及其已重试次数)
+    ///
+    /// 先写入临时文件再原子替换，压缩过程中崩溃不会丢失记录
+    pub fn compact<'a>(&mut self, live: impl Iterator<Item = (&'a Task, u32)>) -> Result<(), JournalError> {
+        let tmp_path = self.path.with_extension("compact");
+        {
+            let mut tmp = BufWriter::new(File::create(&tmp_path)?);
+            for (task, attempts) in live {
+                serde_json::to_writer(&mut tmp, &JournalRecord::Submit { task: task.clone() })?;
+                tmp.write_all(b"\n")?;
+                if attempts > 0 {
+                    serde_json::to_writer(&mut tmp, &JournalRecord::Retry { task_id: task.id.clone(), attempts })?;
+                    tmp.write_all(b"\n")?;
+                }
+            }
+            tmp.flush()?;
+            tmp.get_ref().sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+        self.writer = Self::open_writer(&self.path)?;
+        self.appended = 0;
+        self.unsynced = 0;
+        Ok(())
+    }
+
+    /// 尚未落盘的记录数
+    pub fn unsynced(&self) -> usize {
+        self.unsynced
+    }
+
+    /// 上次压缩后追加的记录数
+    pub fn appended_since_compact(&self) -> usize {
+        self.appended
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open_writer(path: &Path) -> Result<BufWriter<File>, JournalError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(BufWriter::new(file))
+    }
+}
+
+impl Drop for TaskJournal {
+    fn drop(&mut self) {
+        if let Err(e) = self.sync() {
+            log::error!("任务日志落盘失败: {}", e);
+        }
+    }
+}
@@ -1,15 +1,34 @@
 //! Queen模块实现 - 严格遵循docs/架构设计.md规范
 
+mod admission;
+mod async_pool;
+pub mod admin;
+pub mod election;
+pub mod events;
+pub mod journal;
 pub mod network;
+pub mod supervisor;
+mod dispatch;
+mod event_loop;
 
-use std::collections::HashMap;
-use std::time::{Instant, Duration};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
 use super::Process;
-use crate::proto::zergpool::HealthState;
+use crate::proto::zergpool::{Control, ControlKind, HealthState};
 use crate::balancer::{ZergRushSelector, SelectorError};
+use crate::config::{ConfigChange, ConfigError, ConfigWatcher, PoolConfig};
+use crate::exporter::{self, MetricsServer};
+use crate::telemetry;
+use crate::transport;
+use dispatch::{InFlightTask, QueuedTask};
+pub use dispatch::DeadLetter;
+use election::{LeaderElection, LeadershipChange};
+use events::{EventBus, PoolEvent, RemovalReason, WorkerRole};
+use journal::TaskJournal;
+use supervisor::Supervisor;
 
-/// 主工作池最大容量
-const MAX_MAIN_POOL_SIZE: usize = 10;
+pub use async_pool::AsyncDronePool;
+pub use event_loop::{PoolCommand, PoolHandle, PoolStats};
 
 /// 进程池管理结构体
 use std::sync::{Arc, Mutex};
@@ -19,6 +38,18 @@ struct PoolState {
     workers: Vec<Arc<super::Process>>,
     backup_drones: Vec<Arc<super::Process>>,
     status: HashMap<super::ProcessId, WorkerStatus>,
+    /// 工作节点ID到ROUTER身份帧的映射
+    identities: HashMap<super::ProcessId, String>,
+    /// 待派发任务队列
+    pending: VecDeque<QueuedTask>,
+    /// 在途任务(任务ID -> 派发信息)
+    in_flight: HashMap<String, InFlightTask>,
+    /// 排空中的节点(不再派发新任务)
+    draining: HashSet<super::ProcessId>,
+    /// 被管理员移出的节点(解除前拒绝注册)
+    evicted: HashSet<super::ProcessId>,
+    /// 超过重试次数仍失败的任务
+    dead_letters: VecDeque<DeadLetter>,
 }
 
 impl PoolState {
@@ -27,6 +58,12 @@ impl PoolState {
             workers: Vec::new(),
             backup_drones: Vec::new(),
             status: HashMap::new(),
+            identities: HashMap::new(),
+            pending: VecDeque::new(),
+            in_flight: HashMap::new(),
+            draining: HashSet::new(),
+            evicted: HashSet::new(),
+            dead_letters: VecDeque::new(),
         }
     }
 }
@@ -34,6 +71,18 @@ impl PoolState {
 pub struct DronePool {
     state: Arc<Mutex<PoolState>>,
     network: network::HiveNetwork,
+    selector: ZergRushSelector,
+    config: PoolConfig,
+    config_watcher: Option<ConfigWatcher>,
+    journal: Option<TaskJournal>,
+    election: Option<LeaderElection>,
+    supervisor: Option<Supervisor>,
+    /// 管理员暂停派发时为true(任务仍可提交)
+    dispatch_paused: bool,
+    /// Prometheus指标服务(配置了`metrics_addr`时存在)
+    metrics_server: Option<MetricsServer>,
+    /// 生命周期事件订阅者
+    events: EventBus,
 }
 
 /// 工作节点状态(包含外部可访问的指标数据)
@@ -52,20 +101,293 @@ pub struct WorkerStatus {
     timeout_count: u32,    // 超时计数(用于熔断)
 }
 
+/// 记录节点的带标签指标(`worker`标签为节点ID)
+fn record_worker_gauges(id: &super::ProcessId, status: &WorkerStatus) {
+    let labels = [("worker", id.clone())];
+    metrics::gauge!("zergpool.worker.up", &labels).set(1.0);
+    metrics::gauge!("zergpool.worker.cpu_usage", &labels).set(status.cpu_usage as f64);
+    metrics::gauge!("zergpool.worker.mem_usage", &labels).set(status.mem_usage as f64);
+    metrics::gauge!("zergpool.worker.net_latency_ms", &labels).set(status.net_latency as f64);
+    metrics::gauge!("zergpool.worker.current_tasks", &labels).set(status.current_tasks as f64);
+    metrics::gauge!("zergpool.worker.max_tasks", &labels).set(status.max_tasks as f64);
+    metrics::gauge!("zergpool.worker.health", &labels).set(status.health_state as i32 as f64);
+}
+
+/// 节点移出进程池后标记为下线(已导出的序列无法删除)
+fn clear_worker_gauges(id: &super::ProcessId) {
+    metrics::gauge!("zergpool.worker.up", "worker" => id.clone()).set(0.0);
+    metrics::gauge!("zergpool.worker.in_flight", "worker" => id.clone()).set(0.0);
+}
+
 impl DronePool {
-    /// 创建新的进程池实例
+    /// 创建新的进程池实例(使用默认配置)
     pub fn new(bind_addr: &str, port: u16) -> Result<Self, network::NetworkError> {
-        println!("[DRONE POOL] 初始化网络层...");
-        let network = network::HiveNetwork::new(bind_addr, port)?;
-        let full_addr = format!("{}:{}", bind_addr, port);
-        println!("[DRONE POOL] 网络初始化完成: {}", full_addr);
+        Self::create(&format!("tcp://{}:{}", bind_addr, port), PoolConfig::default())
+    }
+
+    /// 使用指定配置创建进程池实例
+    ///
+    /// 配置了`journal_path`时会回放任务日志，未完成的任务重新进入待派发队列；
+    /// 配置了`lease_path`时进入高可用模式，只有成为leader后才加载任务日志并派发任务；
+    /// 配置了`drones`时启动并监管本地drone子进程；配置了`metrics_addr`时启动Prometheus指标服务；
+    /// 配置了`otlp_endpoint`时将span导出到该收集器(需由应用安装`telemetry::TraceLayer`)
+    pub fn with_config(bind_addr: &str, port: u16, config: PoolConfig) -> crate::Result<Self> {
+        Self::bind(&format!("tcp://{}:{}", bind_addr, port), config)
+    }
+
+    /// 绑定完整地址创建进程池实例(如`tcp://0.0.0.0:5555`、`ipc:///run/zerg/queen.sock`)
+    ///
+    /// ipc地址绑定前会清理遗留的socket文件，并按`ipc_mode`设置文件权限
+    pub fn bind(endpoint: &str, config: PoolConfig) -> crate::Result<Self> {
+        let mut pool = Self::create(endpoint, config)?;
+        if let Some(addr) = pool.config.metrics_addr.clone() {
+            pool.metrics_server = Some(exporter::serve(addr.as_str())?);
+        }
+        if let Some(endpoint) = pool.config.otlp_endpoint.as_deref() {
+            telemetry::export_to("zerg-queen", endpoint)?;
+        }
+        if let Some(lease_path) = pool.config.lease_path.clone() {
+            let endpoint = pool.network.current_endpoint()?;
+            let node_id = pool.config.queen_id.clone()
+                .unwrap_or_else(|| format!("queen-{}-{}", std::process::id(), endpoint));
+            pool.election = Some(LeaderElection::new(lease_path, node_id, endpoint, pool.config.lease_ttl()));
+            pool.tick_election()?;
+        } else {
+            pool.open_journal()?;
+        }
+        if let Some(drones) = pool.config.drones.clone() {
+            let endpoint = pool.network.current_endpoint()?;
+            let mut supervisor = Supervisor::for_pool(drones, endpoint, &pool.config)?;
+            supervisor.start()?;
+            pool.supervisor = Some(supervisor);
+        }
+        Ok(pool)
+    }
+
+    /// 实际绑定的地址(绑定`tcp://127.0.0.1:*`等系统分配端口时读取)
+    pub fn local_endpoint(&self) -> crate::Result<String> {
+        Ok(self.network.current_endpoint()?)
+    }
+
+    /// Prometheus指标服务实际绑定的地址
+    pub fn metrics_addr(&self) -> Option<std::net::SocketAddr> {
+        self.metrics_server.as_ref().map(|server| server.local_addr())
+    }
+
+    /// 本地drone子进程监管器(配置了`drones`时存在)
+    pub fn supervisor(&self) -> Option<&Supervisor> {
+        self.supervisor.as_ref()
+    }
+
+    /// 打开任务日志并将未完成任务加入待派发队列
+    fn open_journal(&mut self) -> crate::Result<()> {
+        let Some(path) = self.config.journal_path.clone() else {
+            return Ok(());
+        };
+        let (journal, recovered) = TaskJournal::open(path)?;
+        self.with_state_mut(|state| {
+            for recovered in recovered {
+                let task = recovered.task;
+                let known = state.in_flight.contains_key(&task.id) ||
+                            state.pending.iter().any(|q| q.task.id == task.id);
+                if !known {
+                    state.pending.push_back(QueuedTask { attempts: recovered.attempts, ..QueuedTask::new(task) });
+                }
+            }
+        });
+        self.journal = Some(journal);
+        Ok(())
+    }
+
+    /// 驱动leader选举，处理领导权变化
+    fn tick_election(&mut self) -> crate::Result<()> {
+        let Some(election) = self.election.as_mut() else {
+            return Ok(());
+        };
+        match election.tick()? {
+            LeadershipChange::Acquired => {
+                log::info!("queen {} 成为leader(任期{})", election.node_id(), election.term());
+                metrics::counter!("zergpool.leader_elected").increment(1);
+                // 接管前任leader的任务：回放共享的任务日志
+                self.open_journal()?;
+            }
+            LeadershipChange::Lost => {
+                log::warn!("queen {} 失去leader身份，停止派发任务", election.node_id());
+                // 任务归属随日志交给新leader，本地不再持有
+                self.journal = None;
+                self.with_state_mut(|state| {
+                    state.pending.clear();
+                    state.in_flight.clear();
+                });
+            }
+            LeadershipChange::Unchanged => {}
+        }
+        Ok(())
+    }
+
+    /// 当前queen是否为leader(单queen模式下始终为true)
+    pub fn is_leader(&self) -> bool {
+        self.election.as_ref().is_none_or(|e| e.is_leader())
+    }
+
+    /// 当前leader对外公布的地址
+    pub fn leader_endpoint(&self) -> Option<String> {
+        match &self.election {
+            Some(election) => election.current_lease().ok().flatten()
+                .filter(|lease| !lease.is_expired())
+                .map(|lease| lease.endpoint),
+            None => self.network.current_endpoint().ok(),
+        }
+    }
+
+    /// 主动让出leader身份(正常关闭前调用，使备用queen立即接管)
+    pub fn step_down(&mut self) -> crate::Result<()> {
+        if let Some(election) = self.election.as_mut() {
+            election.release()?;
+            self.journal = None;
+            self.with_state_mut(|state| {
+                state.pending.clear();
+                state.in_flight.clear();
+            });
+        }
+        Ok(())
+    }
+
+    /// 向drone发送控制消息
+    fn send_control(&mut self, identity: &str, kind: ControlKind, reason: &str) -> crate::Result<()> {
+        let control = Control {
+            kind: kind as i32,
+            reason: reason.to_string(),
+            leader_endpoint: if kind == ControlKind::NotLeader {
+                self.leader_endpoint().unwrap_or_default()
+            } else {
+                String::new()
+            },
+        };
+        self.network.send_message(identity, &crate::ProcessMessage::Control(control))?;
+        Ok(())
+    }
+
+    /// 回复控制消息，发送失败只记录日志，不影响同批次其他消息的处理
+    fn reply_control(&mut self, identity: &str, kind: ControlKind, reason: &str) {
+        if let Err(e) = self.send_control(identity, kind, reason) {
+            tracing::warn!(identity = %identity, kind = ?kind, error = %e, "控制消息发送失败");
+        }
+    }
+
+    fn create(endpoint: &str, config: PoolConfig) -> Result<Self, network::NetworkError> {
+        let curve = config.curve.as_ref().map(transport::curve::CurveServer::from_config).transpose()?;
+        let network = network::HiveNetwork::bind_secure(endpoint, config.transport, curve.as_ref())?;
+        if let (Some(path), Some(mode)) = (transport::ipc_path(endpoint), config.ipc_mode) {
+            transport::ipc::set_permissions(&path, mode)?;
+        }
+        tracing::info!(endpoint, transport = ?config.transport, "queen网络层已就绪");
         
         Ok(Self {
             state: Arc::new(Mutex::new(PoolState::new())),
             network,
+            selector: ZergRushSelector::new(
+                config.max_load_threshold,
+                config.check_interval(),
+                config.warmup_duration(),
+            ),
+            config,
+            config_watcher: None,
+            journal: None,
+            election: None,
+            supervisor: None,
+            dispatch_paused: false,
+            metrics_server: None,
+            events: EventBus::default(),
         })
     }
 
+    /// 订阅进程池生命周期事件(节点注册与健康变化、主备池调整、任务状态变化)
+    ///
+    /// 只接收订阅之后发生的事件；丢弃接收端即取消订阅。
+    /// 未消费的事件超过`events::EVENT_CHANNEL_CAPACITY`时丢弃新事件
+    pub fn subscribe(&mut self) -> crossbeam_channel::Receiver<PoolEvent> {
+        self.events.subscribe()
+    }
+
+    /// 获取当前配置
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// 热加载新配置(不影响已注册节点)
+    ///
+    /// 先校验新配置，再在状态锁内一次性应用；返回发生变化的字段。
+    /// 需重启才生效的字段(`ConfigChange::requires_restart`)只记录变更，仍沿用当前取值
+    pub fn reload_config(&mut self, new_config: PoolConfig) -> Result<Vec<ConfigChange>, ConfigError> {
+        new_config.validate()?;
+        let changes = self.config.diff(&new_config);
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+        for change in changes.iter().filter(|c| c.requires_restart()) {
+            log::warn!("{}变更需重启queen后生效，当前仍使用原值", change.field);
+        }
+        // 绑定时读取的字段保持运行中的取值，避免之后(如重新当选时打开任务日志)误用新值
+        let new_config = new_config.keep_restart_required(&self.config);
+
+        let (promoted, demoted) = {
+            let mut state = self.state.lock().unwrap();
+            let size = new_config.max_main_pool_size;
+
+            // 主池缩容：多出的节点降级到备用池头部
+            let mut demoted = Vec::new();
+            while state.workers.len() > size {
+                let worker = state.workers.pop().unwrap();
+                log::info!("配置变更，节点降级到备用池: {}", worker.id);
+                demoted.push(worker.id.clone());
+                state.backup_drones.insert(0, worker);
+            }
+            // 主池扩容：从备用池晋升节点
+            let mut promoted = Vec::new();
+            while state.workers.len() < size && !state.backup_drones.is_empty() {
+                let backup = state.backup_drones.remove(0);
+                log::info!("配置变更，节点晋升到主池: {}", backup.id);
+                promoted.push(backup.id.clone());
+                state.workers.push(backup);
+            }
+
+            self.selector.set_max_load_threshold(new_config.max_load_threshold);
+            self.selector.set_warmup_duration(new_config.warmup_duration());
+            if let Some(supervisor) = self.supervisor.as_mut() {
+                supervisor.set_token(new_config.admission.token.clone());
+            }
+            self.config = new_config;
+            (promoted, demoted)
+        };
+
+        for id in &promoted {
+            self.selector.begin_warmup(id);
+        }
+        self.events.publish_all(demoted.into_iter().map(|worker_id| PoolEvent::ScaledIn { worker_id }));
+        self.events.publish_all(promoted.into_iter().map(|worker_id| PoolEvent::ScaledOut { worker_id }));
+        for change in changes.iter().filter(|c| !c.requires_restart()) {
+            log::info!("配置已更新 {}", change);
+        }
+        Ok(changes)
+    }
+
+    /// 监视配置文件，变更后在`poll_events`中自动热加载
+    pub fn watch_config(&mut self, path: impl Into<std::path::PathBuf>, interval: std::time::Duration) {
+        self.config_watcher = Some(ConfigWatcher::spawn(path, interval));
+    }
+
+    /// 应用配置监视器检测到的变更
+    fn apply_watched_config(&mut self) {
+        let Some(result) = self.config_watcher.as_ref().and_then(|w| w.try_recv()) else {
+            return;
+        };
+        match result.and_then(|config| self.reload_config(config)) {
+            Ok(changes) => log::info!("配置文件热加载完成，{} 项变更", changes.len()),
+            Err(e) => log::error!("配置文件热加载失败，保留当前配置: {}", e),
+        }
+    }
+
     /// 辅助方法：获取状态锁
     fn with_state<F, R>(&self, f: F) -> R
     where
@@ -86,9 +408,11 @@ impl DronePool {
 
     /// 注册新的工作节点
     pub fn register_drone(&mut self, drone: super::Process) -> Result<(), network::NetworkError> {
-        println!("[REGISTER DRONE] 注册新工作节点: {}", drone.id);
-        let need_update = self.with_state_mut(|state| {
-            state.status.insert(drone.id.clone(), WorkerStatus {
+        let max_main_pool_size = self.config.max_main_pool_size;
+        let (need_update, role) = self.with_state_mut(|state| {
+            let known = state.workers.iter().chain(state.backup_drones.iter())
+                .any(|p| p.id == drone.id);
+            let status = WorkerStatus {
                 last_heartbeat: Instant::now(),
                 capability: drone.capability.clone(),
                 cpu_usage: 0.0,
@@ -98,18 +422,36 @@ impl DronePool {
                 max_tasks: drone.max_tasks.unwrap_or(10),
                 health_state: HealthState::Healthy,
                 timeout_count: 0,
-            });
+            };
+            record_worker_gauges(&drone.id, &status);
+            state.status.insert(drone.id.clone(), status);
 
-            if state.workers.len() < MAX_MAIN_POOL_SIZE {
-                state.workers.push(Arc::new(drone));
-                true
+            // 重复注册(如drone重连)只刷新状态，保持原有池位置
+            if known {
+                tracing::info!(worker_id = %drone.id, "工作节点重新注册");
+                (false, None)
+            } else if state.workers.len() < max_main_pool_size {
+                state.workers.push(Arc::new(drone.clone()));
+                tracing::info!(worker_id = %drone.id, pool = "main", "注册新工作节点");
+                (true, Some(WorkerRole::Main))
             } else {
-                state.backup_drones.push(Arc::new(drone));
-                false
+                state.backup_drones.push(Arc::new(drone.clone()));
+                tracing::info!(worker_id = %drone.id, pool = "backup", "注册新工作节点");
+                (false, Some(WorkerRole::Backup))
             }
         });
 
+        if let Some(role) = role {
+            self.events.publish(PoolEvent::WorkerRegistered {
+                worker_id: drone.id.clone(),
+                role,
+                capabilities: drone.capability.clone(),
+            });
+        }
+
         if need_update {
+            // 新节点进入主池后从低权重开始预热
+            self.selector.begin_warmup(&drone.id);
             self.update_balancer_strategy();
         }
         Ok(())
@@ -120,12 +462,20 @@ impl DronePool {
         self.with_state(|state| state.workers.len())
     }
 
+    /// 获取当前备用节点数量
+    pub fn get_backup_count(&self) -> usize {
+        self.with_state(|state| state.backup_drones.len())
+    }
+
+    /// 获取节点当前的预热权重系数(1.0表示已完成预热)
+    pub fn warmup_factor(&self, drone_id: &super::ProcessId) -> f64 {
+        self.selector.warmup().factor(drone_id)
+    }
+
     /// 更新负载均衡策略
     fn update_balancer_strategy(&mut self) {
-        // 初始化选择器(负载阈值80%，预热时间5秒)
-        let selector = ZergRushSelector::new(0.8, Duration::from_secs(5), Duration::from_secs(5));
-        
-        let result = self.with_state_mut(|state| {
+        let selector = &self.selector;
+        let result = self.with_state(|state| {
             // 收集节点负载数据
             let nodes: Vec<_> = state.workers.iter()
                 .map(|worker| {
@@ -148,8 +498,8 @@ impl DronePool {
                 .map(|(_, l)| *l)
                 .unwrap_or(0.0);
             
-            log::info!("选择工作节点: {}, 负载: {:.2}", selected_id, load);
-            Ok((selected_id, load))
+            tracing::debug!(worker_id = %selected_id, load, "选择工作节点");
+            Ok::<_, SelectorError>((selected_id, load))
         });
 
         match result {
@@ -157,21 +507,28 @@ impl DronePool {
                 metrics::counter!("zergpool.selections").increment(1);
             }
             Err(SelectorError::NoNodesAvailable) => {
-                log::warn!("没有可用工作节点，尝试使用备用节点");
-                self.with_state_mut(|state| {
+                tracing::warn!("没有可用工作节点，尝试使用备用节点");
+                let promoted = self.with_state_mut(|state| {
                     if !state.backup_drones.is_empty() {
                         let backup = state.backup_drones.remove(0);
                         metrics::counter!("zergpool.backup_used").increment(1);
-                        log::info!("已从备用池激活节点: {}", backup.id);
+                        tracing::info!(worker_id = %backup.id, "已从备用池激活节点");
+                        let id = backup.id.clone();
                         state.workers.push(backup);
+                        Some(id)
                     } else {
-                        log::error!("备用节点池已耗尽!");
+                        tracing::error!("备用节点池已耗尽");
                         metrics::counter!("zergpool.backup_empty").increment(1);
+                        None
                     }
                 });
+                if let Some(id) = promoted {
+                    self.selector.begin_warmup(&id);
+                    self.events.publish(PoolEvent::ScaledOut { worker_id: id });
+                }
             }
             Err(e) => {
-                log::error!("负载均衡选择失败: {}", e);
+                tracing::error!(error = %e, "负载均衡选择失败");
                 metrics::counter!("zergpool.selection_errors").increment(1);
             }
         }
@@ -186,52 +543,74 @@ impl DronePool {
         net_latency: u32,
         current_tasks: u32,
     ) {
-        self.with_state_mut(|state| {
-            if let Some(status) = state.status.get_mut(drone_id) {
-                status.cpu_usage = cpu_usage;
-                status.mem_usage = mem_usage;
-                status.net_latency = net_latency;
-                status.current_tasks = current_tasks;
-                status.last_heartbeat = Instant::now();
-
-                // 健康判断逻辑与drone端保持一致
-                if status.last_heartbeat.elapsed() > Duration::from_secs(9) {
-                    status.timeout_count += 1;
-                    if status.timeout_count >= 3 {
-                        status.health_state = HealthState::CircuitBreaker;
-                    } else {
-                        status.health_state = HealthState::Unhealthy;
-                    }
+        let heartbeat_timeout = self.config.heartbeat_timeout();
+        let breaker_threshold = self.config.circuit_breaker_threshold;
+        let cpu_limit = self.config.cpu_overload_threshold;
+        let mem_limit = self.config.mem_overload_threshold;
+        let change = self.with_state_mut(|state| {
+            let status = state.status.get_mut(drone_id)?;
+            let previous = status.health_state;
+            status.cpu_usage = cpu_usage;
+            status.mem_usage = mem_usage;
+            status.net_latency = net_latency;
+            status.current_tasks = current_tasks;
+            status.last_heartbeat = Instant::now();
+
+            // 健康判断逻辑与drone端保持一致
+            if status.last_heartbeat.elapsed() > heartbeat_timeout {
+                status.timeout_count += 1;
+                if status.timeout_count >= breaker_threshold {
+                    status.health_state = HealthState::CircuitBreaker;
                 } else {
-                    status.timeout_count = 0;
-                    let is_overloaded = cpu_usage > 0.9 || mem_usage > 0.9 ||
-                                      current_tasks >= status.max_tasks;
-                    status.health_state = if is_overloaded {
-                        HealthState::Unhealthy
-                    } else {
-                        HealthState::Healthy
-                    };
+                    status.health_state = HealthState::Unhealthy;
                 }
+            } else {
+                status.timeout_count = 0;
+                let is_overloaded = cpu_usage > cpu_limit || mem_usage > mem_limit ||
+                                  current_tasks >= status.max_tasks;
+                status.health_state = if is_overloaded {
+                    HealthState::Unhealthy
+                } else {
+                    HealthState::Healthy
+                };
             }
-        })
+            record_worker_gauges(drone_id, status);
+            (previous != status.health_state).then_some((previous, status.health_state))
+        });
+        if let Some((from, to)) = change {
+            self.events.publish(PoolEvent::WorkerHealthChanged { worker_id: drone_id.clone(), from, to });
+        }
     }
 
-    /// 获取最优工作节点(基于综合评分)
+    /// 获取工作节点(按有效权重随机选择)
     pub fn get_optimal_worker(&self) -> Option<super::ProcessId> {
+        self.select_worker(|_| true)
+    }
+
+    /// 在满足条件的健康节点中由选择器按有效权重随机选择(排空中的节点不参与)
+    ///
+    /// 节点负载为综合评分，选择器按 有效权重 × 剩余容量(1 - 综合评分) 分配流量，
+    /// 预热中的节点获得与预热系数成比例的流量，评分超过`max_load_threshold`的节点暂不派发
+    fn select_worker<F>(&self, filter: F) -> Option<super::ProcessId>
+    where
+        F: Fn(&super::ProcessId) -> bool,
+    {
+        let w = self.config.balance_weights;
         self.with_state(|state| {
-            state.status.iter()
-                .filter(|(_, status)| status.health_state == HealthState::Healthy)
-                .min_by(|(_, a), (_, b)| {
-                    // 评分算法(CPU 40%, 内存 30%, 延迟 20%, 任务负载 10%)
-                    let a_score = 0.4 * a.cpu_usage + 0.3 * a.mem_usage +
-                                 0.2 * (a.net_latency as f32 / 1000.0) +
-                                 0.1 * (a.current_tasks as f32 / a.max_tasks as f32);
-                    let b_score = 0.4 * b.cpu_usage + 0.3 * b.mem_usage +
-                                 0.2 * (b.net_latency as f32 / 1000.0) +
-                                 0.1 * (b.current_tasks as f32 / b.max_tasks as f32);
-                    a_score.partial_cmp(&b_score).unwrap_or(std::cmp::Ordering::Equal)
+            let nodes: Vec<(&Process, f64)> = state.workers.iter()
+                .chain(state.backup_drones.iter())
+                .filter(|p| !state.draining.contains(&p.id) && filter(&p.id))
+                .filter_map(|p| {
+                    let s = state.status.get(&p.id)
+                        .filter(|status| status.health_state == HealthState::Healthy)?;
+                    // 评分算法(默认CPU 40%, 内存 30%, 延迟 20%, 任务负载 10%)
+                    let score = w.cpu * s.cpu_usage + w.mem * s.mem_usage +
+                               w.latency * (s.net_latency as f32 / 1000.0) +
+                               w.tasks * (s.current_tasks as f32 / s.max_tasks as f32);
+                    Some((p.as_ref(), score as f64))
                 })
-                .map(|(id, _)| id.clone())
+                .collect();
+            self.selector.select(&nodes).ok().map(|p| p.id.clone())
         })
     }
 
@@ -247,23 +626,145 @@ impl DronePool {
         })
     }
 
+    /// 检查心跳超时的节点：超时标记为不健康，连续超时达到熔断阈值后移出进程池
+    ///
+    /// 被移除节点的在途任务重新入队，主池空缺由备用节点补足；返回被移除的节点ID
+    pub fn reap_stale_workers(&mut self) -> Vec<super::ProcessId> {
+        let timeout = self.config.heartbeat_timeout();
+        let breaker_threshold = self.config.circuit_breaker_threshold;
+        let max_main_pool_size = self.config.max_main_pool_size;
+        let (reaped, promoted, changes) = self.with_state_mut(|state| {
+            let mut reaped = Vec::new();
+            let mut changes = Vec::new();
+            for (id, status) in state.status.iter_mut() {
+                let elapsed = status.last_heartbeat.elapsed();
+                if elapsed <= timeout {
+                    continue;
+                }
+                let previous = status.health_state;
+                status.timeout_count = (elapsed.as_millis() / timeout.as_millis().max(1)) as u32;
+                if status.timeout_count >= breaker_threshold {
+                    status.health_state = HealthState::CircuitBreaker;
+                    reaped.push(id.clone());
+                } else {
+                    status.health_state = HealthState::Unhealthy;
+                }
+                if previous != status.health_state {
+                    changes.push(PoolEvent::WorkerHealthChanged {
+                        worker_id: id.clone(),
+                        from: previous,
+                        to: status.health_state,
+                    });
+                }
+            }
+
+            for id in &reaped {
+                state.status.remove(id);
+                state.identities.remove(id);
+                state.draining.remove(id);
+                state.workers.retain(|w| &w.id != id);
+                state.backup_drones.retain(|w| &w.id != id);
+            }
+            let mut promoted = Vec::new();
+            while state.workers.len() < max_main_pool_size && !state.backup_drones.is_empty() {
+                let backup = state.backup_drones.remove(0);
+                promoted.push(backup.id.clone());
+                state.workers.push(backup);
+            }
+            (reaped, promoted, changes)
+        });
+
+        self.events.publish_all(changes);
+        for id in &reaped {
+            tracing::warn!(worker_id = %id, "节点心跳超时，已移出进程池");
+            metrics::counter!("zergpool.workers_reaped").increment(1);
+            clear_worker_gauges(id);
+            self.selector.end_warmup(id);
+            let requeued = self.requeue_worker_tasks(id);
+            metrics::counter!("zergpool.tasks_timed_out").increment(requeued as u64);
+            self.events.publish(PoolEvent::WorkerRemoved { worker_id: id.clone(), reason: RemovalReason::HeartbeatTimeout });
+        }
+        for id in &promoted {
+            tracing::info!(worker_id = %id, "节点晋升到主池替补");
+            self.selector.begin_warmup(id);
+            self.events.publish(PoolEvent::ScaledOut { worker_id: id.clone() });
+        }
+        reaped
+    }
+
+    /// 记录进程池整体指标
+    fn record_pool_gauges(&self) {
+        let stats = self.stats();
+        metrics::gauge!("zergpool.workers").set(stats.workers as f64);
+        metrics::gauge!("zergpool.backups").set(stats.backups as f64);
+        metrics::gauge!("zergpool.pending_tasks").set(stats.pending_tasks as f64);
+        metrics::gauge!("zergpool.in_flight_tasks").set(stats.in_flight_tasks as f64);
+        metrics::gauge!("zergpool.is_leader").set(if stats.is_leader { 1.0 } else { 0.0 });
+
+        let in_flight = self.with_state(|state| {
+            let mut counts: HashMap<&super::ProcessId, usize> = state.workers.iter()
+                .chain(state.backup_drones.iter())
+                .map(|w| (&w.id, 0))
+                .collect();
+            for task in state.in_flight.values() {
+                if let Some(count) = counts.get_mut(&task.worker_id) {
+                    *count += 1;
+                }
+            }
+            counts.into_iter().map(|(id, count)| (id.clone(), count)).collect::<Vec<_>>()
+        });
+        for (id, count) in in_flight {
+            metrics::gauge!("zergpool.worker.in_flight", "worker" => id).set(count as f64);
+        }
+    }
+
     /// 获取工作节点指标数据(返回副本避免生命周期问题)
     pub fn get_worker_metrics(&self, drone_id: &super::ProcessId) -> Option<WorkerStatus> {
         self.with_state(|state| state.status.get(drone_id).cloned())
     }
 
-    /// 轮询并处理网络事件
-    pub fn poll_events(&mut self) -> Result<(), network::NetworkError> {
-        println!("[POLL EVENTS] 开始轮询网络事件...");
+    /// 轮询并处理网络事件，随后派发待处理任务
+    pub fn poll_events(&mut self) -> crate::Result<()> {
+        self.apply_watched_config();
+        self.tick_election()?;
+        if let Some(supervisor) = self.supervisor.as_mut() {
+            supervisor.tick();
+        }
         let messages = self.network.poll_events()?;
-        println!("[POLL EVENTS] 收到 {} 条消息", messages.len());
-        
+        if !messages.is_empty() {
+            tracing::trace!(count = messages.len(), "收到网络消息");
+        }
+
         for (identity, message) in messages {
-            println!("[POLL EVENTS] 处理消息 - 来源: {}, 类型: {:?}", identity, message);
-            log::debug!("收到消息 - 来源: {}, 类型: {:?}", identity, message);
+            tracing::trace!(identity = %identity, message = ?message, "处理消息");
+            if !self.is_leader() {
+                // 备用queen不接收drone，引导其切换到leader
+                if !matches!(message, crate::ProcessMessage::Control(_)) {
+                    self.reply_control(&identity, ControlKind::NotLeader, "standby queen");
+                }
+                continue;
+            }
             match message {
                 crate::ProcessMessage::Registration(reg) => {
-                    println!("[REGISTRATION] 处理注册消息: {:?}", reg);
+                    tracing::debug!(
+                        identity = %identity,
+                        worker_id = %reg.worker_id,
+                        version = %reg.version,
+                        protocol_version = %reg.protocol_version,
+                        max_threads = reg.max_threads,
+                        "收到注册消息"
+                    );
+                    let replaced = match self.admit(&identity, &reg) {
+                        Ok(replaced) => replaced,
+                        Err(e) => {
+                            self.reject_registration(&identity, &reg, e);
+                            continue;
+                        }
+                    };
+                    if let Some(previous) = replaced {
+                        tracing::warn!(worker_id = %reg.worker_id, from = %previous, to = %identity, "节点ID由新连接接管");
+                        self.reply_control(&previous, ControlKind::Evicted, "replaced by new registration");
+                    }
                     let mut process = Process::new(
                         reg.worker_id.clone(),
                         reg.capabilities.clone(),
@@ -272,9 +773,18 @@ impl DronePool {
                     process.weight = 1.0;  // 设置默认权重
                     process.current_load = 0.0;  // 初始化负载
                     self.register_drone(process)?;
-                    println!("[REGISTRATION] 已注册工作节点: {}", reg.worker_id);
+                    self.with_state_mut(|state| {
+                        state.identities.insert(reg.worker_id.clone(), identity.clone());
+                    });
+                    self.accept_registration(&identity);
                 }
                 crate::ProcessMessage::Heartbeat(hb) => {
+                    // queen重启后丢失了注册信息，或连接并非该节点注册时的连接，要求drone重新注册
+                    if !self.is_admitted(&identity, &hb.worker_id) {
+                        tracing::warn!(identity = %identity, worker_id = %hb.worker_id, "收到未注册节点的心跳");
+                        self.reply_control(&identity, ControlKind::UnknownWorker, "worker not registered");
+                        continue;
+                    }
                     self.update_worker_metrics(
                         &hb.worker_id,
                         hb.cpu_usage,
@@ -282,15 +792,33 @@ impl DronePool {
                         hb.net_latency,
                         hb.current_tasks,
                     );
+                    self.reply_control(&identity, ControlKind::HeartbeatAck, "");
                     
                     let unhealthy = self.get_unhealthy_drones();
                     if !unhealthy.is_empty() {
-                        log::warn!("发现不健康节点: {:?}", unhealthy);
+                        tracing::warn!(workers = ?unhealthy, "发现不健康节点");
+                    }
+                }
+                crate::ProcessMessage::TaskResponse(resp) => {
+                    if !self.is_admitted(&identity, &resp.worker_id) {
+                        tracing::warn!(identity = %identity, worker_id = %resp.worker_id, task_id = %resp.task_id, "丢弃未注册连接回传的任务结果");
+                        continue;
                     }
+                    self.complete_task(&resp)?;
                 }
                 _ => {} // 忽略其他消息类型
             }
         }
+
+        if self.is_leader() && !self.dispatch_paused {
+            self.dispatch_pending()?;
+        }
+        // 组提交：本轮循环追加的日志记录一次性落盘
+        if let Some(journal) = self.journal.as_mut() {
+            journal.sync()?;
+        }
+        self.selector.refresh_warmup();
+        self.record_pool_gauges();
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,325 @@
+//! 本地drone子进程监管
+//!
+//! queen按`SupervisorConfig`启动N个drone子进程：崩溃后按指数退避重启，
+//! 因被管理员移除(退出码`EVICTED_EXIT_CODE`)或注册被拒且重试无效(退出码`REJECTED_EXIT_CODE`)
+//! 而退出的子进程不再重启，直到`resume`；
+//! 关闭时先请求子进程退出，超时后强制结束；子进程的stdout/stderr逐行转发到`log`。
+//! 每个子进程通过环境变量获得queen地址、传输实现、注册令牌与固定的节点ID，
+//! 使用`DroneConfig::with_env_overrides`加载配置的drone无需额外参数即可接入
+
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::config::{ConfigError, PoolConfig, SupervisorConfig, DRONE_ENV_PREFIX};
+use crate::transport::TransportKind;
+
+/// 注入子进程的queen地址环境变量
+pub const QUEEN_ENDPOINT_ENV: &str = "ZERG_QUEEN_ENDPOINT";
+/// drone被queen移出进程池时的退出码，监管器不再重启该子进程
+pub const EVICTED_EXIT_CODE: i32 = 3;
+/// drone注册被queen拒绝且重试无效(令牌、版本不匹配等)时的退出码，监管器不再重启该子进程
+pub const REJECTED_EXIT_CODE: i32 = 4;
+/// 子进程输出转发使用的日志target
+const LOG_TARGET: &str = "zerg_pool::drone";
+
+/// 子进程监管错误类型
+#[derive(Error, Debug)]
+pub enum SupervisorError {
+    #[error("配置错误: {0}")]
+    Config(#[from] ConfigError),
+    #[error("drone子进程启动失败: {0}")]
+    Spawn(#[from] std::io::Error),
+}
+
+/// 单个子进程槽位(节点ID固定，子进程可多次重启)
+struct Slot {
+    worker_id: String,
+    child: Option<Child>,
+    started_at: Instant,
+    /// 连续崩溃次数
+    crashes: u32,
+    restart_at: Instant,
+    /// 已被移出进程池，`resume`前不重启
+    evicted: bool,
+}
+
+/// drone子进程监管器
+pub struct Supervisor {
+    config: SupervisorConfig,
+    program: PathBuf,
+    queen_endpoint: String,
+    transport: TransportKind,
+    /// 注入子进程的注册令牌
+    token: Option<String>,
+    slots: Vec<Slot>,
+    restarts: u64,
+    stopped: bool,
+}
+
+impl Supervisor {
+    /// 创建监管器(尚未启动子进程)
+    pub fn new(config: SupervisorConfig, queen_endpoint: impl Into<String>, transport: TransportKind) -> Result<Self, SupervisorError> {
+        config.validate()?;
+        if transport == TransportKind::Inproc {
+            return Err(ConfigError::Invalid {
+                field: "drones",
+                reason: "子进程无法连接进程内(inproc)地址".to_string(),
+            }.into());
+        }
+        let program = match &config.program {
+            Some(program) => program.clone(),
+            None => std::env::current_exe()?,
+        };
+        let now = Instant::now();
+        let slots = (0..config.instances)
+            .map(|i| Slot {
+                worker_id: format!("{}-{}", config.worker_id_prefix, i),
+                child: None,
+                started_at: now,
+                crashes: 0,
+                restart_at: now,
+                evicted: false,
+            })
+            .collect();
+        Ok(Self {
+            config,
+            program,
+            queen_endpoint: queen_endpoint.into(),
+            transport,
+            token: None,
+            slots,
+            restarts: 0,
+            // 调用`start`前不启动任何子进程
+            stopped: true,
+        })
+    }
+
+    /// 为queen创建监管器：子进程沿用queen的传输实现与注册令牌
+    ///
+    /// 启用CURVE时每个drone需要自己的密钥和queen公钥，无法由queen配置推导，
+    /// 必须通过`env`提供`ZERG_DRONE_CURVE`，否则子进程无法接入
+    pub fn for_pool(config: SupervisorConfig, queen_endpoint: impl Into<String>, pool: &PoolConfig) -> Result<Self, SupervisorError> {
+        let curve_env = format!("{}CURVE", DRONE_ENV_PREFIX);
+        if pool.curve.is_some() && !config.env.contains_key(&curve_env) {
+            return Err(ConfigError::Invalid {
+                field: "drones",
+                reason: format!("queen启用CURVE时需在env中为子进程提供{}", curve_env),
+            }.into());
+        }
+        let mut supervisor = Self::new(config, queen_endpoint, pool.transport)?;
+        supervisor.set_token(pool.admission.token.clone());
+        Ok(supervisor)
+    }
+
+    /// 设置注入子进程的注册令牌(此后启动的子进程生效)
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    /// 启动所有子进程
+    pub fn start(&mut self) -> Result<(), SupervisorError> {
+        self.stopped = false;
+        for index in 0..self.slots.len() {
+            if self.slots[index].child.is_none() {
+                self.spawn(index)?;
+            }
+        }
+        log::info!("已启动 {} 个drone子进程: {}", self.slots.len(), self.program.display());
+        Ok(())
+    }
+
+    /// 检查子进程状态：回收已退出的子进程，到期后重启；返回本次重启的数量
+    pub fn tick(&mut self) -> usize {
+        if self.stopped {
+            return 0;
+        }
+        let now = Instant::now();
+        let mut restarted = 0;
+        for index in 0..self.slots.len() {
+            let slot = &mut self.slots[index];
+            if let Some(child) = slot.child.as_mut() {
+                let status = match child.try_wait() {
+                    Ok(Some(status)) => status,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        log::error!("检查drone子进程 {} 状态失败: {}", slot.worker_id, e);
+                        continue;
+                    }
+                };
+                slot.child = None;
+                if status.code() == Some(EVICTED_EXIT_CODE) {
+                    log::warn!("drone子进程 {} 已被移出进程池，解除前不再重启", slot.worker_id);
+                    slot.evicted = true;
+                    continue;
+                }
+                if status.code() == Some(REJECTED_EXIT_CODE) {
+                    log::error!("drone子进程 {} 注册被queen拒绝，解除前不再重启", slot.worker_id);
+                    slot.evicted = true;
+                    continue;
+                }
+                // 稳定运行足够久后重新计算退避
+                if slot.started_at.elapsed() >= self.config.max_restart_backoff() {
+                    slot.crashes = 0;
+                }
+                slot.crashes += 1;
+                let delay = self.config.restart_backoff(slot.crashes);
+                log::warn!("drone子进程 {} 退出({})，{:?}后重启", slot.worker_id, status, delay);
+                slot.restart_at = now + delay;
+            }
+            if self.slots[index].evicted || self.slots[index].restart_at > now {
+                continue;
+            }
+            match self.spawn(index) {
+                Ok(()) => {
+                    self.restarts += 1;
+                    restarted += 1;
+                    metrics::counter!("zergpool.drone_restarts").increment(1);
+                }
+                Err(e) => {
+                    let slot = &mut self.slots[index];
+                    slot.crashes += 1;
+                    slot.restart_at = now + self.config.restart_backoff(slot.crashes);
+                    log::error!("重启drone子进程 {} 失败: {}", slot.worker_id, e);
+                }
+            }
+        }
+        restarted
+    }
+
+    /// 恢复被移出进程池的子进程槽位，下次`tick`时重新启动；返回是否存在该槽位
+    pub fn resume(&mut self, worker_id: &str) -> bool {
+        let Some(slot) = self.slots.iter_mut().find(|slot| slot.worker_id == worker_id) else {
+            return false;
+        };
+        if slot.evicted {
+            slot.evicted = false;
+            slot.crashes = 0;
+            slot.restart_at = Instant::now();
+        }
+        true
+    }
+
+    /// 停止所有子进程：先请求退出，超过`shutdown_timeout`后强制结束
+    pub fn shutdown(&mut self) {
+        if self.stopped {
+            return;
+        }
+        self.stopped = true;
+        for slot in &mut self.slots {
+            if let Some(child) = slot.child.as_mut() {
+                terminate(child);
+            }
+        }
+
+        let deadline = Instant::now() + self.config.shutdown_timeout();
+        loop {
+            for slot in &mut self.slots {
+                if slot.child.as_mut().is_some_and(|c| !matches!(c.try_wait(), Ok(None))) {
+                    slot.child = None;
+                }
+            }
+            if self.running() == 0 || Instant::now() >= deadline {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        for slot in &mut self.slots {
+            if let Some(mut child) = slot.child.take() {
+                log::warn!("drone子进程 {} 未在{:?}内退出，强制结束", slot.worker_id, self.config.shutdown_timeout());
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+        log::info!("drone子进程已全部停止");
+    }
+
+    /// 运行中的子进程数
+    pub fn running(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.child.is_some()).count()
+    }
+
+    /// 运行中子进程的(节点ID, 进程号)
+    pub fn pids(&self) -> Vec<(String, u32)> {
+        self.slots.iter()
+            .filter_map(|slot| slot.child.as_ref().map(|c| (slot.worker_id.clone(), c.id())))
+            .collect()
+    }
+
+    /// 累计重启次数
+    pub fn restarts(&self) -> u64 {
+        self.restarts
+    }
+
+    fn spawn(&mut self, index: usize) -> Result<(), SupervisorError> {
+        let worker_id = self.slots[index].worker_id.clone();
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.config.args)
+            .envs(&self.config.env)
+            .env(QUEEN_ENDPOINT_ENV, &self.queen_endpoint)
+            .env(format!("{}WORKER_ID", DRONE_ENV_PREFIX), &worker_id)
+            .env(format!("{}TRANSPORT", DRONE_ENV_PREFIX), self.transport.as_str())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(token) = &self.token {
+            // 以JSON字符串注入，避免纯数字令牌被解析为数字
+            let token = serde_json::Value::String(token.clone()).to_string();
+            command.env(format!("{}TOKEN", DRONE_ENV_PREFIX), token);
+        }
+        let mut child = command.spawn()?;
+        if let Some(stdout) = child.stdout.take() {
+            forward_output(&worker_id, stdout, log::Level::Info);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_output(&worker_id, stderr, log::Level::Warn);
+        }
+        log::info!("drone子进程 {} 已启动，pid {}", worker_id, child.id());
+
+        let slot = &mut self.slots[index];
+        slot.child = Some(child);
+        slot.started_at = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for Supervisor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// 将子进程输出逐行转发到日志(子进程退出后线程随管道关闭结束)
+fn forward_output(worker_id: &str, stream: impl Read + Send + 'static, level: log::Level) {
+    let worker_id = worker_id.to_string();
+    let result = thread::Builder::new()
+        .name(format!("zerg-drone-log-{}", worker_id))
+        .spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                log::log!(target: LOG_TARGET, level, "[{}] {}", worker_id, line);
+            }
+        });
+    if let Err(e) = result {
+        log::error!("启动drone输出转发线程失败: {}", e);
+    }
+}
+
+/// 请求子进程退出(Unix下发送SIGTERM，其他平台直接结束)
+#[cfg(unix)]
+fn terminate(child: &mut Child) {
+    // SAFETY: 仅向自身尚未回收的子进程发送信号
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) {
+    let _ = child.kill();
+}
@@ -0,0 +1,473 @@
+//! 分布式追踪
+//!
+//! 任务的提交、选择节点、派发、执行与回传均创建`tracing` span，
+//! 跨进程时以W3C trace-context(`traceparent`)写入`Task.metadata`传递：
+//! queen在提交与派发时注入，drone执行前提取并作为执行span的父节点。
+//!
+//! 库本身不安装全局订阅器：应用将`TraceLayer`组合进自己的订阅器
+//! (如`Registry::default().with(TraceLayer).with(fmt_layer)`)，
+//! 或在进程入口调用`install_subscriber`(zerg-queen/zerg-drone的做法)。
+//! 安装后span携带trace/span ID；配置`otlp_endpoint`时
+//! 结束的span按OTLP/HTTP JSON格式批量发送到收集器(`POST {endpoint}/v1/traces`)，
+//! 发送队列有上限，收集器跟不上时丢弃新的span并计入`zergpool.spans_dropped`
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender, TrySendError};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Span, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+
+/// W3C trace-context在`Task.metadata`中的键
+pub const TRACEPARENT_KEY: &str = "traceparent";
+
+/// 单次发送的最大span数
+const MAX_BATCH: usize = 512;
+/// 等待导出的span上限，超出时丢弃
+const EXPORT_QUEUE_CAPACITY: usize = MAX_BATCH * 8;
+/// 未满批次时的发送间隔
+const EXPORT_INTERVAL: Duration = Duration::from_secs(1);
+/// 连接与等待收集器回复的超时
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 追踪错误类型
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("进程中已安装其他tracing订阅器")]
+    SubscriberInstalled,
+    #[error("无效的OTLP地址: {0}")]
+    InvalidEndpoint(String),
+    #[error("导出线程启动失败: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// W3C trace-context(`00-{trace_id}-{span_id}-{flags}`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// 新的根上下文
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: non_zero(rand::random),
+            span_id: non_zero(rand::random),
+            sampled: true,
+        }
+    }
+
+    /// 同一trace下的子上下文
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: non_zero(rand::random),
+            ..*self
+        }
+    }
+
+    /// 解析`traceparent`头，格式不合法时返回`None`
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+        // 版本00必须恰好4段；更高版本允许追加字段
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id.len() != 32 || span_id.len() != 16 || flags.len() != 2 {
+            return None;
+        }
+        let is_hex = |s: &str| s.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if ![version, trace_id, span_id, flags].into_iter().all(is_hex) {
+            return None;
+        }
+        let context = Self {
+            trace_id: u128::from_str_radix(trace_id, 16).ok()?,
+            span_id: u64::from_str_radix(span_id, 16).ok()?,
+            sampled: u8::from_str_radix(flags, 16).ok()? & 1 == 1,
+        };
+        (context.trace_id != 0 && context.span_id != 0).then_some(context)
+    }
+
+    /// 32位十六进制trace ID
+    pub fn trace_id_hex(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+
+    /// 16位十六进制span ID
+    pub fn span_id_hex(&self) -> String {
+        format!("{:016x}", self.span_id)
+    }
+}
+
+impl fmt::Display for TraceContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "00-{:032x}-{:016x}-{:02x}", self.trace_id, self.span_id, self.sampled as u8)
+    }
+}
+
+fn non_zero<T: Default + PartialEq>(mut generate: impl FnMut() -> T) -> T {
+    loop {
+        let value = generate();
+        if value != T::default() {
+            return value;
+        }
+    }
+}
+
+/// 从任务元数据中提取上游的trace-context
+pub fn extract(metadata: &HashMap<String, String>) -> Option<TraceContext> {
+    metadata.get(TRACEPARENT_KEY).and_then(|value| TraceContext::parse(value))
+}
+
+/// 将trace-context写入任务元数据
+pub fn inject(metadata: &mut HashMap<String, String>, context: &TraceContext) {
+    metadata.insert(TRACEPARENT_KEY.to_string(), context.to_string());
+}
+
+/// span对应的trace-context(未安装本模块的订阅器时为`None`)
+pub fn span_context(span: &Span) -> Option<TraceContext> {
+    span.with_subscriber(|(id, dispatch)| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        let span = registry.span(id)?;
+        let context = span.extensions().get::<SpanState>().map(|state| state.context);
+        context
+    })
+    .flatten()
+}
+
+/// 当前span的trace-context
+pub fn current_context() -> Option<TraceContext> {
+    span_context(&Span::current())
+}
+
+/// 需要向下游传递的trace-context
+///
+/// 优先使用span自身的上下文；未安装订阅器时沿用上游上下文生成子上下文，
+/// 保证即使本进程不采集span，trace也能在上下游之间连通
+pub fn propagation_context(span: &Span, upstream: Option<TraceContext>) -> TraceContext {
+    span_context(span).unwrap_or_else(|| upstream.map_or_else(TraceContext::new_root, |parent| parent.child()))
+}
+
+/// 已结束的span
+#[derive(Debug, Clone)]
+struct SpanData {
+    name: &'static str,
+    context: TraceContext,
+    parent_span_id: Option<u64>,
+    start: SystemTime,
+    end: SystemTime,
+    attributes: Vec<(&'static str, String)>,
+}
+
+/// 保存在span扩展中的追踪状态
+struct SpanState {
+    context: TraceContext,
+    parent_span_id: Option<u64>,
+    start: SystemTime,
+    attributes: Vec<(&'static str, String)>,
+}
+
+/// 收集span字段；名为`traceparent`的字段作为跨进程的父上下文
+#[derive(Default)]
+struct FieldVisitor {
+    traceparent: Option<String>,
+    attributes: Vec<(&'static str, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == TRACEPARENT_KEY {
+            self.traceparent = Some(value.to_string());
+        } else {
+            self.attributes.push((field.name(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == TRACEPARENT_KEY {
+            self.traceparent = Some(format!("{:?}", value));
+        } else {
+            self.attributes.push((field.name(), format!("{:?}", value)));
+        }
+    }
+}
+
+/// 为span分配trace-context并在结束时交给导出器的订阅层
+///
+/// 需与`Registry`组合使用(`span_context`通过`Registry`读取span扩展)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TraceLayer;
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        let parent = visitor.traceparent.as_deref()
+            .and_then(TraceContext::parse)
+            .or_else(|| span.parent().and_then(|p| p.extensions().get::<SpanState>().map(|s| s.context)));
+        let context = parent.map_or_else(TraceContext::new_root, |p| p.child());
+        span.extensions_mut().insert(SpanState {
+            context,
+            parent_span_id: parent.map(|p| p.span_id),
+            start: SystemTime::now(),
+            attributes: visitor.attributes,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+        let mut extensions = span.extensions_mut();
+        if let Some(state) = extensions.get_mut::<SpanState>() {
+            for (name, value) in visitor.attributes {
+                state.attributes.retain(|(n, _)| *n != name);
+                state.attributes.push((name, value));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(state) = span.extensions_mut().remove::<SpanState>() else {
+            return;
+        };
+        if !state.context.sampled {
+            return;
+        }
+        if let Some(exporter) = EXPORTER.read().unwrap().as_ref() {
+            exporter.export(SpanData {
+                name: span.name(),
+                context: state.context,
+                parent_span_id: state.parent_span_id,
+                start: state.start,
+                end: SystemTime::now(),
+                attributes: state.attributes,
+            });
+        }
+    }
+}
+
+enum ExportCommand {
+    Span(SpanData),
+    Flush(Sender<()>),
+}
+
+/// OTLP/HTTP JSON导出器(后台线程批量发送)
+struct OtlpExporter {
+    endpoint: String,
+    sender: Sender<ExportCommand>,
+}
+
+impl OtlpExporter {
+    fn spawn(service_name: &str, endpoint: &str) -> Result<Self, TelemetryError> {
+        let target = HttpTarget::parse(endpoint)?;
+        let (sender, receiver) = bounded(EXPORT_QUEUE_CAPACITY);
+        let service_name = service_name.to_string();
+        thread::Builder::new()
+            .name("otlp-exporter".to_string())
+            .spawn(move || export_loop(&service_name, &target, receiver))?;
+        Ok(Self { endpoint: endpoint.to_string(), sender })
+    }
+
+    /// 加入发送队列；队列已满时丢弃，不阻塞产生span的线程
+    fn export(&self, span: SpanData) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(ExportCommand::Span(span)) {
+            metrics::counter!("zergpool.spans_dropped").increment(1);
+        }
+    }
+}
+
+fn export_loop(service_name: &str, target: &HttpTarget, receiver: Receiver<ExportCommand>) {
+    let mut batch = Vec::new();
+    loop {
+        let command = receiver.recv_timeout(EXPORT_INTERVAL);
+        let flushed = match command {
+            Ok(ExportCommand::Span(span)) => {
+                batch.push(span);
+                if batch.len() < MAX_BATCH {
+                    continue;
+                }
+                None
+            }
+            Ok(ExportCommand::Flush(done)) => Some(done),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if !batch.is_empty() {
+            let body = encode_spans(service_name, &batch);
+            batch.clear();
+            if let Err(e) = target.post(&body) {
+                log::warn!("span导出失败({}): {}", target.addr, e);
+            }
+        }
+        if let Some(done) = flushed {
+            let _ = done.send(());
+        }
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
+
+/// 编码为OTLP `ExportTraceServiceRequest`的JSON形式
+fn encode_spans(service_name: &str, spans: &[SpanData]) -> Vec<u8> {
+    let string_attribute = |key: &str, value: &str| json!({ "key": key, "value": { "stringValue": value } });
+    let spans: Vec<Value> = spans.iter()
+        .map(|span| {
+            let mut encoded = json!({
+                "traceId": span.context.trace_id_hex(),
+                "spanId": span.context.span_id_hex(),
+                "name": span.name,
+                "kind": 1,
+                "startTimeUnixNano": unix_nanos(span.start),
+                "endTimeUnixNano": unix_nanos(span.end),
+                "attributes": span.attributes.iter().map(|(k, v)| string_attribute(k, v)).collect::<Vec<_>>(),
+            });
+            if let Some(parent) = span.parent_span_id {
+                encoded["parentSpanId"] = json!(format!("{:016x}", parent));
+            }
+            encoded
+        })
+        .collect();
+    let request = json!({
+        "resourceSpans": [{
+            "resource": { "attributes": [string_attribute("service.name", service_name)] },
+            "scopeSpans": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    });
+    serde_json::to_vec(&request).unwrap_or_default()
+}
+
+/// 收集器HTTP地址(仅支持明文`http://`)
+struct HttpTarget {
+    addr: String,
+    path: String,
+}
+
+impl HttpTarget {
+    fn parse(endpoint: &str) -> Result<Self, TelemetryError> {
+        let invalid = || TelemetryError::InvalidEndpoint(endpoint.to_string());
+        let rest = endpoint.strip_prefix("http://").ok_or_else(invalid)?;
+        let (addr, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, ""),
+        };
+        if addr.to_socket_addrs().map(|mut addrs| addrs.next().is_none()).unwrap_or(true) {
+            return Err(invalid());
+        }
+        let path = path.trim_end_matches('/');
+        Ok(Self {
+            addr: addr.to_string(),
+            path: format!("{}/v1/traces", path),
+        })
+    }
+
+    fn post(&self, body: &[u8]) -> io::Result<()> {
+        let addr = self.addr.to_socket_addrs()?.next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "地址解析失败"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, EXPORT_TIMEOUT)?;
+        stream.set_read_timeout(Some(EXPORT_TIMEOUT))?;
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path, self.addr, body.len()
+        )?;
+        stream.write_all(body)?;
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status)?;
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => Err(io::Error::other(format!("收集器返回: {}", status.trim()))),
+        }
+    }
+}
+
+/// 已安装的订阅器
+static INSTALLED: Mutex<bool> = Mutex::new(false);
+/// 当前的span导出器
+static EXPORTER: RwLock<Option<OtlpExporter>> = RwLock::new(None);
+
+/// 将只含`TraceLayer`的订阅器安装为进程全局订阅器(重复调用无副作用)
+///
+/// 供可执行文件入口使用；需要同时输出日志等的应用应自行组合`TraceLayer`
+pub fn install_subscriber() -> Result<(), TelemetryError> {
+    let mut installed = INSTALLED.lock().unwrap();
+    if *installed {
+        return Ok(());
+    }
+    let subscriber = Registry::default().with(TraceLayer);
+    tracing::subscriber::set_global_default(subscriber).map_err(|_| TelemetryError::SubscriberInstalled)?;
+    *installed = true;
+    Ok(())
+}
+
+/// 将`TraceLayer`记录的span导出到OTLP收集器(如`http://127.0.0.1:4318`)
+///
+/// 不安装订阅器，未安装`TraceLayer`时不会产生可导出的span；
+/// 进程内只保留第一个导出器，之后以不同地址调用时忽略并给出警告
+pub fn export_to(service_name: &str, endpoint: &str) -> Result<(), TelemetryError> {
+    let mut exporter = EXPORTER.write().unwrap();
+    match exporter.as_ref() {
+        Some(current) if current.endpoint != endpoint => {
+            log::warn!("span已导出到 {}，忽略 {}", current.endpoint, endpoint);
+        }
+        Some(_) => {}
+        None => {
+            *exporter = Some(OtlpExporter::spawn(service_name, endpoint)?);
+            log::info!("span导出到: {}", endpoint);
+        }
+    }
+    Ok(())
+}
+
+/// 立即发送已结束但尚未导出的span(进程退出前调用)
+pub fn flush() {
+    let done = {
+        let exporter = EXPORTER.read().unwrap();
+        let Some(exporter) = exporter.as_ref() else {
+            return;
+        };
+        let (done, wait) = crossbeam_channel::bounded(1);
+        if exporter.sender.send_timeout(ExportCommand::Flush(done), EXPORT_TIMEOUT).is_err() {
+            return;
+        }
+        wait
+    };
+    if done.recv_timeout(EXPORT_TIMEOUT * 2).is_err() {
+        log::warn!("span导出超时");
+    }
+}
+
+/// 校验OTLP地址格式(配置校验使用)
+pub fn check_endpoint(endpoint: &str) -> Result<(), TelemetryError> {
+    HttpTarget::parse(endpoint).map(|_| ())
+}
@@ -0,0 +1,317 @@
+//! ZMQ CURVE加密与认证
+//!
+//! queen持有长期密钥对，drone连接时需提供queen公钥并使用自己的密钥对完成握手；
+//! 配置了允许列表时，queen在ZAP处理线程中只接受列表内的drone公钥。
+//!
+//! 密钥以Z85编码(40字符)保存在TOML文件中：
+//! - 密钥文件：`public_key`与`secret_key`(私钥文件在Unix上权限为0600)
+//! - 公钥文件：只含`public_key`，可分发给对端
+//! - 允许列表：一个公钥文件，或包含多个公钥文件的目录，也可以每行写一个Z85公钥(`#`开头为注释)
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use serde::{Deserialize, Serialize};
+use zmq::{Context, Socket};
+
+use super::TransportError;
+use crate::config::{CurveClientConfig, CurveServerConfig};
+
+/// libzmq约定的ZAP处理端点
+const ZAP_ENDPOINT: &str = "inproc://zeromq.zap.01";
+/// queen套接字使用的ZAP域
+const ZAP_DOMAIN: &str = "zerg";
+/// ZAP处理线程检查退出标志的间隔(ms)
+const ZAP_POLL_MS: i64 = 100;
+/// Z85字母表中的非字母数字字符(libzmq解码时不校验字符范围)
+const Z85_SYMBOLS: &str = ".-:+=^!/*?&<>()[]{}@%$#";
+
+/// 当前libzmq是否支持CURVE(需链接libsodium构建)
+pub fn is_available() -> bool {
+    zmq::has("curve").unwrap_or(false)
+}
+
+/// 解码Z85公钥/私钥
+pub fn decode_key(key: &str) -> Result<[u8; 32], TransportError> {
+    let key = key.trim();
+    let invalid = || TransportError::InvalidKey(format!("应为40字符的Z85编码: {}", key));
+    if key.len() != 40 || !key.chars().all(|c| c.is_ascii_alphanumeric() || Z85_SYMBOLS.contains(c)) {
+        return Err(invalid());
+    }
+    zmq::z85_decode(key).ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(invalid)
+}
+
+/// 编码为Z85
+pub fn encode_key(key: &[u8; 32]) -> String {
+    zmq::z85_encode(key).expect("32字节是4的倍数")
+}
+
+/// CURVE密钥对(Z85编码)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveKeyPair {
+    pub public_key: String,
+    pub secret_key: String,
+}
+
+/// 只含公钥的文件内容
+#[derive(Debug, Serialize, Deserialize)]
+struct PublicKeyFile {
+    public_key: String,
+}
+
+impl CurveKeyPair {
+    /// 生成新的密钥对
+    pub fn generate() -> Result<Self, TransportError> {
+        if !is_available() {
+            return Err(TransportError::CurveUnavailable);
+        }
+        let pair = zmq::CurveKeyPair::new()?;
+        Ok(Self {
+            public_key: encode_key(&pair.public_key),
+            secret_key: encode_key(&pair.secret_key),
+        })
+    }
+
+    /// 从密钥文件加载
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TransportError> {
+        let path = path.as_ref();
+        let pair: Self = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| TransportError::InvalidKey(format!("{}: {}", path.display(), e)))?;
+        decode_key(&pair.public_key)?;
+        decode_key(&pair.secret_key)?;
+        Ok(pair)
+    }
+
+    /// 写入密钥文件(Unix上权限为0600)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TransportError> {
+        let content = toml::to_string(self).expect("密钥对可序列化");
+        write_private(path.as_ref(), &content)?;
+        Ok(())
+    }
+
+    /// 写入公钥文件(可分发给对端或加入允许列表)
+    pub fn save_public(&self, path: impl AsRef<Path>) -> Result<(), TransportError> {
+        let content = toml::to_string(&PublicKeyFile { public_key: self.public_key.clone() })
+            .expect("公钥可序列化");
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    fs::write(path, content)
+}
+
+/// 读取公钥：公钥文件、密钥文件或只含Z85公钥的文本
+pub fn load_public_key(path: impl AsRef<Path>) -> Result<String, TransportError> {
+    let path = path.as_ref();
+    let keys = parse_public_keys(path, &fs::read_to_string(path)?)?;
+    match keys.as_slice() {
+        [key] => Ok(key.clone()),
+        _ => Err(TransportError::InvalidKey(format!("{}: 应只包含一个公钥", path.display()))),
+    }
+}
+
+/// 读取允许列表：单个文件或目录下的所有文件
+pub fn load_authorized_keys(path: impl AsRef<Path>) -> Result<HashSet<[u8; 32]>, TransportError> {
+    let path = path.as_ref();
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    let mut keys = HashSet::new();
+    for file in files {
+        for key in parse_public_keys(&file, &fs::read_to_string(&file)?)? {
+            keys.insert(decode_key(&key)?);
+        }
+    }
+    Ok(keys)
+}
+
+/// 解析公钥文本：TOML文件取`public_key`，否则每个非注释行为一个Z85公钥
+fn parse_public_keys(path: &Path, content: &str) -> Result<Vec<String>, TransportError> {
+    if let Ok(file) = toml::from_str::<PublicKeyFile>(content) {
+        decode_key(&file.public_key)?;
+        return Ok(vec![file.public_key]);
+    }
+    let keys: Vec<String> = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if keys.is_empty() {
+        return Err(TransportError::InvalidKey(format!("{}: 未找到公钥", path.display())));
+    }
+    for key in &keys {
+        decode_key(key)?;
+    }
+    Ok(keys)
+}
+
+/// queen端CURVE参数
+#[derive(Debug, Clone)]
+pub struct CurveServer {
+    pub keypair: CurveKeyPair,
+    /// 允许连接的drone公钥(为空时接受任何知道queen公钥的drone)
+    pub authorized_keys: Option<HashSet<[u8; 32]>>,
+}
+
+impl CurveServer {
+    /// 按配置加载密钥文件与允许列表
+    pub fn from_config(config: &CurveServerConfig) -> Result<Self, TransportError> {
+        Ok(Self {
+            keypair: CurveKeyPair::load(&config.key_file)?,
+            authorized_keys: config.authorized_keys.as_ref().map(load_authorized_keys).transpose()?,
+        })
+    }
+
+    /// 将套接字设为CURVE服务端
+    pub(crate) fn apply(&self, socket: &Socket) -> Result<(), TransportError> {
+        if !is_available() {
+            return Err(TransportError::CurveUnavailable);
+        }
+        socket.set_zap_domain(ZAP_DOMAIN)?;
+        socket.set_curve_server(true)?;
+        socket.set_curve_secretkey(&decode_key(&self.keypair.secret_key)?)?;
+        Ok(())
+    }
+}
+
+/// drone端CURVE参数
+#[derive(Debug, Clone)]
+pub struct CurveClient {
+    pub keypair: CurveKeyPair,
+    /// queen公钥(Z85)
+    pub server_key: String,
+}
+
+impl CurveClient {
+    /// 按配置加载drone密钥文件与queen公钥
+    pub fn from_config(config: &CurveClientConfig) -> Result<Self, TransportError> {
+        Ok(Self {
+            keypair: CurveKeyPair::load(&config.key_file)?,
+            server_key: load_public_key(&config.server_key_file)?,
+        })
+    }
+
+    /// 将套接字设为CURVE客户端
+    pub(crate) fn apply(&self, socket: &Socket) -> Result<(), TransportError> {
+        if !is_available() {
+            return Err(TransportError::CurveUnavailable);
+        }
+        socket.set_curve_serverkey(&decode_key(&self.server_key)?)?;
+        socket.set_curve_publickey(&decode_key(&self.keypair.public_key)?)?;
+        socket.set_curve_secretkey(&decode_key(&self.keypair.secret_key)?)?;
+        Ok(())
+    }
+}
+
+/// ZAP认证处理线程，只接受允许列表中的CURVE公钥
+///
+/// 须在queen套接字绑定前启动(同一`Context`内)，释放时停止线程
+pub(crate) struct ZapHandler {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ZapHandler {
+    pub(crate) fn start(ctx: &Context, authorized: HashSet<[u8; 32]>) -> Result<Self, TransportError> {
+        let socket = ctx.socket(zmq::REP)?;
+        socket.bind(ZAP_ENDPOINT)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let thread = thread::Builder::new()
+            .name("zerg-zap".to_string())
+            .spawn(move || {
+                while !flag.load(Ordering::Acquire) {
+                    match socket.poll(zmq::POLLIN, ZAP_POLL_MS) {
+                        Ok(0) => continue,
+                        Ok(_) => {}
+                        Err(zmq::Error::EINTR) => continue,
+                        Err(e) => {
+                            tracing::error!(error = %e, "ZAP处理线程退出");
+                            return;
+                        }
+                    }
+                    let request = match socket.recv_multipart(0) {
+                        Ok(request) => request,
+                        Err(e) => {
+                            tracing::warn!(error = %e, "读取ZAP请求失败");
+                            continue;
+                        }
+                    };
+                    let reply = Self::authenticate(&request, &authorized);
+                    if let Err(e) = socket.send_multipart(reply, 0) {
+                        tracing::warn!(error = %e, "回复ZAP请求失败");
+                    }
+                }
+            })?;
+        Ok(Self { stop, thread: Some(thread) })
+    }
+
+    /// 按ZAP协议(RFC 27)校验请求并生成回复帧
+    fn authenticate(request: &[Vec<u8>], authorized: &HashSet<[u8; 32]>) -> Vec<Vec<u8>> {
+        let request_id = request.get(1).cloned().unwrap_or_default();
+        let address = request.get(3).map(|a| String::from_utf8_lossy(a).to_string()).unwrap_or_default();
+        let key = match request {
+            [version, _, _, _, _, mechanism, key] if version == b"1.0" && mechanism == b"CURVE" => {
+                <[u8; 32]>::try_from(key.as_slice()).ok()
+            }
+            _ => None,
+        };
+        let (status, text) = match key {
+            Some(key) if authorized.contains(&key) => {
+                tracing::debug!(address = %address, key = %encode_key(&key), "drone通过CURVE认证");
+                ("200", "OK")
+            }
+            Some(key) => {
+                tracing::warn!(address = %address, key = %encode_key(&key), "拒绝未授权的drone公钥");
+                ("400", "unauthorized key")
+            }
+            None => {
+                tracing::warn!(address = %address, "拒绝格式错误的ZAP请求");
+                ("400", "invalid request")
+            }
+        };
+        vec![
+            b"1.0".to_vec(),
+            request_id,
+            status.as_bytes().to_vec(),
+            text.as_bytes().to_vec(),
+            Vec::new(),
+            Vec::new(),
+        ]
+    }
+}
+
+impl Drop for ZapHandler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
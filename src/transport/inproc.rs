@@ -0,0 +1,300 @@
+//! 进程内传输
+//!
+//! queen与drone位于同一进程时通过通道直接交换数据帧，地址形如`inproc://name`。
+//! queen绑定时在全局注册表中登记地址，drone连接时登记自身身份与回复通道；
+//! 与原生传输一致，queen不存在时drone按固定间隔重试，queen重启后自动重新连接。
+//! 每个连接有唯一编号，数据帧按连接而非自报的身份归属：身份已被仍在线的连接占用时，
+//! 新连接的数据帧被丢弃，直到原连接断开
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+
+use super::{ClientTransport, ServerTransport, TransportError};
+
+/// drone端重新查找queen的间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 连接编号分配
+static NEXT_CONNECTION: AtomicU64 = AtomicU64::new(1);
+
+/// drone发往queen的事件
+enum Event {
+    /// 建立连接：连接编号、身份、回复通道与存活标记(drone断开连接时释放)
+    Connect(u64, String, Sender<Vec<u8>>, Weak<()>),
+    /// 数据帧
+    Data(u64, Vec<u8>),
+}
+
+/// queen端记录的连接
+struct Connection {
+    identity: String,
+    reply: Sender<Vec<u8>>,
+    alive: Weak<()>,
+}
+
+impl Connection {
+    fn is_alive(&self) -> bool {
+        self.alive.strong_count() > 0
+    }
+}
+
+/// 已绑定的进程内地址
+fn registry() -> &'static Mutex<HashMap<String, Sender<Event>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, Sender<Event>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+fn parse_name(endpoint: &str) -> Result<&str, TransportError> {
+    endpoint.strip_prefix("inproc://")
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| TransportError::InvalidEndpoint(endpoint.to_string()))
+}
+
+/// 当前绑定在该地址上的queen
+fn lookup(name: &str) -> Option<Sender<Event>> {
+    registry().lock().unwrap().get(name).cloned()
+}
+
+/// queen端进程内监听
+pub struct InprocServer {
+    name: String,
+    sender: Sender<Event>,
+    incoming: Receiver<Event>,
+    connections: HashMap<u64, Connection>,
+    /// 身份当前归属的连接
+    peers: HashMap<String, u64>,
+}
+
+impl InprocServer {
+    /// 绑定地址(如`inproc://queen`)，同名地址已被绑定时返回`AddrInUse`
+    pub fn bind(endpoint: &str) -> Result<Self, TransportError> {
+        let name = parse_name(endpoint)?.to_string();
+        let (sender, incoming) = unbounded();
+        let mut registry = registry().lock().unwrap();
+        if registry.contains_key(&name) {
+            return Err(TransportError::Io(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("{} 已被绑定", endpoint),
+            )));
+        }
+        registry.insert(name.clone(), sender.clone());
+        tracing::info!(endpoint = %endpoint, "进程内传输已绑定");
+        Ok(Self { name, sender, incoming, connections: HashMap::new(), peers: HashMap::new() })
+    }
+
+    fn handle(&mut self, event: Event, messages: &mut Vec<(String, Vec<u8>)>) {
+        match event {
+            Event::Connect(id, identity, reply, alive) => {
+                self.connections.retain(|_, conn| conn.is_alive());
+                let connection = Connection { identity: identity.clone(), reply, alive };
+                self.connections.insert(id, connection);
+                if self.claim(id, &identity) {
+                    tracing::debug!(identity = %identity, "接受进程内连接");
+                } else {
+                    tracing::warn!(identity = %identity, "身份已被其他连接占用，忽略新连接的数据");
+                }
+            }
+            Event::Data(id, data) => {
+                let Some(identity) = self.connections.get(&id).map(|conn| conn.identity.clone()) else {
+                    return;
+                };
+                // 原连接断开后，同身份的新连接接管路由(与drone重连一致)
+                if self.claim(id, &identity) {
+                    messages.push((identity, data));
+                } else {
+                    tracing::debug!(identity = %identity, bytes = data.len(), "丢弃重复身份连接的数据");
+                }
+            }
+        }
+    }
+
+    /// 身份未被其他在线连接占用时归属到该连接，返回是否归属成功
+    fn claim(&mut self, id: u64, identity: &str) -> bool {
+        let owner = self.peers.get(identity).copied();
+        if owner.is_some_and(|owner| owner != id && self.connections.get(&owner).is_some_and(Connection::is_alive)) {
+            return false;
+        }
+        self.peers.insert(identity.to_string(), id);
+        true
+    }
+}
+
+impl Drop for InprocServer {
+    fn drop(&mut self) {
+        let mut registry = registry().lock().unwrap();
+        if registry.get(&self.name).is_some_and(|s| s.same_channel(&self.sender)) {
+            registry.remove(&self.name);
+        }
+    }
+}
+
+impl ServerTransport for InprocServer {
+    fn local_endpoint(&self) -> Result<String, TransportError> {
+        Ok(format!("inproc://{}", self.name))
+    }
+
+    fn recv(&mut self, timeout_ms: i64) -> Result<Vec<(String, Vec<u8>)>, TransportError> {
+        let mut messages = Vec::new();
+        // 自身持有发送端，通道不会断开
+        let first = if timeout_ms < 0 {
+            self.incoming.recv().ok()
+        } else {
+            self.incoming.recv_timeout(Duration::from_millis(timeout_ms as u64)).ok()
+        };
+        let Some(first) = first else {
+            return Ok(messages);
+        };
+        self.handle(first, &mut messages);
+        while let Ok(event) = self.incoming.try_recv() {
+            self.handle(event, &mut messages);
+        }
+        Ok(messages)
+    }
+
+    fn send(&mut self, identity: &str, data: &[u8]) -> Result<(), TransportError> {
+        let Some(conn) = self.peers.get(identity).and_then(|id| self.connections.get(id)) else {
+            tracing::debug!(identity = %identity, bytes = data.len(), "丢弃发往未连接节点的消息");
+            return Ok(());
+        };
+        if conn.reply.send(data.to_vec()).is_err() {
+            tracing::debug!(identity = %identity, "进程内连接已关闭");
+            if let Some(id) = self.peers.remove(identity) {
+                self.connections.remove(&id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// drone端进程内连接
+pub struct InprocClient {
+    identity: String,
+    endpoint: Option<String>,
+    server: Option<Sender<Event>>,
+    /// 当前连接的编号与存活标记
+    connection: Option<(u64, Arc<()>)>,
+    /// 连接建立前待发送的数据帧
+    pending: VecDeque<Vec<u8>>,
+    inbox_sender: Sender<Vec<u8>>,
+    inbox: Receiver<Vec<u8>>,
+    last_attempt: Option<Instant>,
+}
+
+impl InprocClient {
+    /// 创建未连接的客户端
+    pub fn new(identity: &str) -> Result<Self, TransportError> {
+        let (inbox_sender, inbox) = unbounded();
+        Ok(Self {
+            identity: identity.to_string(),
+            endpoint: None,
+            server: None,
+            connection: None,
+            pending: VecDeque::new(),
+            inbox_sender,
+            inbox,
+            last_attempt: None,
+        })
+    }
+
+    /// 按重连间隔检查queen是否仍绑定在该地址上，queen不存在或已重启时重新连接
+    fn ensure_connected(&mut self) {
+        let Some(endpoint) = self.endpoint.as_deref() else {
+            return;
+        };
+        if self.last_attempt.is_some_and(|t| t.elapsed() < RECONNECT_INTERVAL) {
+            return;
+        }
+        self.last_attempt = Some(Instant::now());
+
+        let current = parse_name(endpoint).ok().and_then(lookup);
+        let unchanged = match (&self.server, &current) {
+            (Some(server), Some(current)) => server.same_channel(current),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+        self.drop_connection();
+        let Some(server) = current else {
+            return;
+        };
+        let id = NEXT_CONNECTION.fetch_add(1, Ordering::Relaxed);
+        let alive = Arc::new(());
+        let connect = Event::Connect(id, self.identity.clone(), self.inbox_sender.clone(), Arc::downgrade(&alive));
+        if server.send(connect).is_err() {
+            return;
+        }
+        self.server = Some(server);
+        self.connection = Some((id, alive));
+        self.flush();
+    }
+
+    fn drop_connection(&mut self) {
+        self.connection = None;
+        if self.server.take().is_some() {
+            // 未送达的数据随连接丢弃，由上层重新注册/重试
+            self.pending.clear();
+        }
+    }
+
+    fn flush(&mut self) {
+        let (Some(server), Some((id, _))) = (self.server.as_ref(), self.connection.as_ref()) else {
+            return;
+        };
+        let id = *id;
+        while let Some(data) = self.pending.pop_front() {
+            if server.send(Event::Data(id, data)).is_err() {
+                self.drop_connection();
+                return;
+            }
+        }
+    }
+}
+
+impl ClientTransport for InprocClient {
+    fn connect(&mut self, endpoint: &str) -> Result<(), TransportError> {
+        parse_name(endpoint)?;
+        self.endpoint = Some(endpoint.to_string());
+        self.last_attempt = None;
+        self.ensure_connected();
+        Ok(())
+    }
+
+    fn disconnect(&mut self, endpoint: &str) -> Result<(), TransportError> {
+        if self.endpoint.as_deref() == Some(endpoint) {
+            self.endpoint = None;
+            self.drop_connection();
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.ensure_connected();
+        self.pending.push_back(data.to_vec());
+        self.flush();
+        Ok(())
+    }
+
+    fn recv(&mut self, timeout_ms: i64) -> Result<Option<Vec<u8>>, TransportError> {
+        let deadline = (timeout_ms >= 0).then(|| Instant::now() + Duration::from_millis(timeout_ms as u64));
+        loop {
+            self.ensure_connected();
+            // 以重连间隔为粒度等待，以便及时发现queen重启
+            let wait = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()).min(RECONNECT_INTERVAL),
+                None => RECONNECT_INTERVAL,
+            };
+            // 自身持有发送端，通道不会断开
+            if let Ok(frame) = self.inbox.recv_timeout(wait) {
+                return Ok(Some(frame));
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(None);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Unix域套接字文件管理
+//!
+//! 绑定前清理进程异常退出后遗留的socket文件，绑定后按配置设置文件权限，
+//! 监听端释放时删除socket文件
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::TransportError;
+
+/// queen端持有的socket文件，释放时删除
+#[derive(Debug)]
+pub struct SocketFile {
+    path: PathBuf,
+}
+
+impl SocketFile {
+    /// 为绑定做准备：创建父目录，清理无人监听的遗留socket文件
+    ///
+    /// 路径已被其他进程监听时返回`AddrInUse`，路径为普通文件时拒绝覆盖
+    pub fn prepare(path: impl Into<PathBuf>) -> Result<Self, TransportError> {
+        let path = path.into();
+        remove_stale(&path)?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(Self { path })
+    }
+
+    /// socket文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::warn!("删除socket文件 {} 失败: {}", self.path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn remove_stale(path: &Path) -> Result<(), TransportError> {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::net::UnixStream;
+
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(TransportError::InvalidEndpoint(format!(
+            "{} 已存在且不是socket文件", path.display()
+        )));
+    }
+    // 能连上说明仍有进程在监听，不能删除
+    if UnixStream::connect(path).is_ok() {
+        return Err(TransportError::Io(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} 已被其他进程监听", path.display()),
+        )));
+    }
+    log::warn!("清理遗留的socket文件: {}", path.display());
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn remove_stale(path: &Path) -> Result<(), TransportError> {
+    Err(TransportError::InvalidEndpoint(format!(
+        "当前平台不支持Unix域套接字: {}", path.display()
+    )))
+}
+
+/// 设置socket文件权限(如`0o660`仅允许同组用户连接)
+#[cfg(unix)]
+pub fn set_permissions(path: &Path, mode: u32) -> Result<(), TransportError> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+/// 设置socket文件权限(非Unix平台无效)
+#[cfg(not(unix))]
+pub fn set_permissions(_path: &Path, _mode: u32) -> Result<(), TransportError> {
+    Ok(())
+}
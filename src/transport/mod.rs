@@ -0,0 +1,188 @@
+//! 传输层抽象
+//!
+//! queen与drone之间只交换信封(`Envelope`)数据帧，传输层负责帧的收发与对端身份：
+//! - `Zmq`: ROUTER/DEALER套接字(默认)
+//! - `Native`: 基于mio的长度前缀帧，无需libzmq
+//! - `Inproc`: 进程内通道，queen与drone运行在同一进程(嵌入式部署与测试)
+//!
+//! `Zmq`与`Native`支持`tcp://host:port`与`ipc:///path/to.sock`(Unix域套接字，亦可写作`unix://`)地址，
+//! `Inproc`使用`inproc://name`地址
+//!
+//! `Zmq`传输可启用CURVE加密与认证(见`curve`模块)
+
+pub mod curve;
+pub mod inproc;
+pub mod ipc;
+pub mod native;
+pub mod zmq;
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 传输层错误类型
+#[derive(Error, Debug)]
+pub enum TransportError {
+    #[error("ZMQ error: {0}")]
+    Zmq(#[from] ::zmq::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid endpoint: {0}")]
+    InvalidEndpoint(String),
+    #[error("Frame too large: {0} bytes")]
+    FrameTooLarge(usize),
+    #[error("Unknown transport: {0}")]
+    UnknownKind(String),
+    #[error("CURVE is not supported by the {0} transport")]
+    CurveUnsupported(&'static str),
+    #[error("libzmq was built without CURVE support")]
+    CurveUnavailable,
+    #[error("Invalid CURVE key: {0}")]
+    InvalidKey(String),
+    #[error("Identity already connected: {0}")]
+    DuplicateIdentity(String),
+}
+
+/// 传输实现
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    /// ZeroMQ ROUTER/DEALER
+    #[default]
+    Zmq,
+    /// mio长度前缀帧(TCP/Unix域套接字)
+    Native,
+    /// 进程内通道
+    Inproc,
+}
+
+impl TransportKind {
+    /// 配置中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportKind::Zmq => "zmq",
+            TransportKind::Native => "native",
+            TransportKind::Inproc => "inproc",
+        }
+    }
+}
+
+impl std::str::FromStr for TransportKind {
+    type Err = TransportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zmq" => Ok(TransportKind::Zmq),
+            "native" => Ok(TransportKind::Native),
+            "inproc" => Ok(TransportKind::Inproc),
+            other => Err(TransportError::UnknownKind(other.to_string())),
+        }
+    }
+}
+
+/// queen端传输：接收带对端身份的数据帧，按身份回发
+pub trait ServerTransport: Send {
+    /// 实际绑定的地址(端口为0时返回系统分配的端口)
+    fn local_endpoint(&self) -> Result<String, TransportError>;
+
+    /// 等待至多`timeout_ms`(-1为阻塞)，返回期间到达的所有(身份, 数据帧)
+    fn recv(&mut self, timeout_ms: i64) -> Result<Vec<(String, Vec<u8>)>, TransportError>;
+
+    /// 向指定身份的对端发送数据帧(对端不存在时丢弃，与ROUTER行为一致)
+    fn send(&mut self, identity: &str, data: &[u8]) -> Result<(), TransportError>;
+}
+
+/// drone端传输：连接queen，断线后自动重连
+pub trait ClientTransport: Send {
+    /// 连接到queen地址
+    fn connect(&mut self, endpoint: &str) -> Result<(), TransportError>;
+
+    /// 断开queen地址
+    fn disconnect(&mut self, endpoint: &str) -> Result<(), TransportError>;
+
+    /// 发送数据帧
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError>;
+
+    /// 等待至多`timeout_ms`(-1为阻塞)，返回下一个数据帧
+    fn recv(&mut self, timeout_ms: i64) -> Result<Option<Vec<u8>>, TransportError>;
+}
+
+/// 按传输实现绑定queen地址
+pub fn bind(kind: TransportKind, endpoint: &str) -> Result<Box<dyn ServerTransport>, TransportError> {
+    Ok(match kind {
+        TransportKind::Zmq => Box::new(zmq::ZmqServer::bind(endpoint)?),
+        TransportKind::Native => Box::new(native::NativeServer::bind(endpoint)?),
+        TransportKind::Inproc => Box::new(inproc::InprocServer::bind(endpoint)?),
+    })
+}
+
+/// 按传输实现绑定queen地址，指定`curve`时启用CURVE加密与认证(仅`Zmq`传输支持)
+pub fn bind_secure(kind: TransportKind, endpoint: &str, curve: Option<&curve::CurveServer>) -> Result<Box<dyn ServerTransport>, TransportError> {
+    match (kind, curve) {
+        (_, None) => bind(kind, endpoint),
+        (TransportKind::Zmq, Some(curve)) => Ok(Box::new(zmq::ZmqServer::bind_curve(endpoint, curve)?)),
+        (other, Some(_)) => Err(TransportError::CurveUnsupported(other.as_str())),
+    }
+}
+
+/// 按传输实现创建drone端连接(身份在连接前设置)
+pub fn client(kind: TransportKind, identity: &str) -> Result<Box<dyn ClientTransport>, TransportError> {
+    Ok(match kind {
+        TransportKind::Zmq => Box::new(zmq::ZmqClient::new(identity)?),
+        TransportKind::Native => Box::new(native::NativeClient::new(identity)?),
+        TransportKind::Inproc => Box::new(inproc::InprocClient::new(identity)?),
+    })
+}
+
+/// 按传输实现创建drone端连接，指定`curve`时使用CURVE握手(仅`Zmq`传输支持)
+pub fn client_secure(kind: TransportKind, identity: &str, curve: Option<&curve::CurveClient>) -> Result<Box<dyn ClientTransport>, TransportError> {
+    match (kind, curve) {
+        (_, None) => client(kind, identity),
+        (TransportKind::Zmq, Some(curve)) => Ok(Box::new(zmq::ZmqClient::new_curve(identity, curve)?)),
+        (other, Some(_)) => Err(TransportError::CurveUnsupported(other.as_str())),
+    }
+}
+
+/// 解析后的queen地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `tcp://host:port`
+    Tcp(SocketAddr),
+    /// `ipc:///path/to.sock`或`unix:///path/to.sock`
+    Ipc(PathBuf),
+}
+
+impl Endpoint {
+    /// 解析地址(TCP主机名在此处解析为socket地址)
+    pub fn parse(endpoint: &str) -> Result<Self, TransportError> {
+        if let Some(path) = ipc_path(endpoint) {
+            return Ok(Endpoint::Ipc(path));
+        }
+        use std::net::ToSocketAddrs;
+        let invalid = || TransportError::InvalidEndpoint(endpoint.to_string());
+        let addr = endpoint.strip_prefix("tcp://").ok_or_else(invalid)?;
+        addr.to_socket_addrs()
+            .map_err(|_| invalid())?
+            .next()
+            .ok_or_else(invalid)
+            .map(Endpoint::Tcp)
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            Endpoint::Ipc(path) => write!(f, "ipc://{}", path.display()),
+        }
+    }
+}
+
+/// ipc地址对应的socket文件路径(非ipc地址或路径为空时返回`None`)
+pub fn ipc_path(endpoint: &str) -> Option<PathBuf> {
+    endpoint.strip_prefix("ipc://")
+        .or_else(|| endpoint.strip_prefix("unix://"))
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
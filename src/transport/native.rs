@@ -0,0 +1,526 @@
+//! 基于mio的原生传输(TCP/Unix域套接字)
+//!
+//! 每个数据帧以4字节大端长度前缀编码；drone连接建立后首帧为自身身份，
+//! queen据此将连接与身份关联。与ZMQ ROUTER一致，身份已被仍在线的连接占用时关闭新连接，
+//! 不会接管原连接的路由。drone端断线后按固定间隔自动重连并重新发送身份帧
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use mio::event::Source;
+use mio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use mio::net::{UnixListener, UnixStream};
+use mio::{Events, Interest, Poll, Registry, Token};
+
+use super::ipc::SocketFile;
+use super::{ClientTransport, Endpoint, ServerTransport, TransportError};
+
+/// 单帧最大长度，超过时视为协议错误并断开连接
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+/// drone端断线重连间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(100);
+const LISTENER: Token = Token(0);
+const CLIENT: Token = Token(0);
+
+fn encode_frame(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// 从读缓冲区中取出完整帧
+fn decode_frames(buf: &mut Vec<u8>) -> Result<Vec<Vec<u8>>, TransportError> {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while buf.len() - offset >= 4 {
+        let len = u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap()) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(TransportError::FrameTooLarge(len));
+        }
+        if buf.len() - offset - 4 < len {
+            break;
+        }
+        frames.push(buf[offset + 4..offset + 4 + len].to_vec());
+        offset += 4 + len;
+    }
+    buf.drain(..offset);
+    Ok(frames)
+}
+
+fn timeout_of(timeout_ms: i64) -> Option<Duration> {
+    (timeout_ms >= 0).then(|| Duration::from_millis(timeout_ms as u64))
+}
+
+/// 为TCP/Unix两种变体实现`Source`
+macro_rules! delegate_source {
+    ($ty:ident) => {
+        impl Source for $ty {
+            fn register(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+                match self {
+                    $ty::Tcp(inner) => inner.register(registry, token, interests),
+                    #[cfg(unix)]
+                    $ty::Unix(inner) => inner.register(registry, token, interests),
+                }
+            }
+
+            fn reregister(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+                match self {
+                    $ty::Tcp(inner) => inner.reregister(registry, token, interests),
+                    #[cfg(unix)]
+                    $ty::Unix(inner) => inner.reregister(registry, token, interests),
+                }
+            }
+
+            fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+                match self {
+                    $ty::Tcp(inner) => inner.deregister(registry),
+                    #[cfg(unix)]
+                    $ty::Unix(inner) => inner.deregister(registry),
+                }
+            }
+        }
+    };
+}
+
+/// 已建立的连接
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+delegate_source!(Stream);
+
+impl Stream {
+    /// 发起非阻塞连接
+    fn connect(endpoint: &Endpoint) -> io::Result<Self> {
+        match endpoint {
+            Endpoint::Tcp(addr) => {
+                let stream = TcpStream::connect(*addr)?;
+                let _ = stream.set_nodelay(true);
+                Ok(Stream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Endpoint::Ipc(path) => Ok(Stream::Unix(UnixStream::connect(path)?)),
+            #[cfg(not(unix))]
+            Endpoint::Ipc(_) => Err(io::ErrorKind::Unsupported.into()),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(inner) => inner.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(inner) => inner.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(inner) => inner.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(inner) => inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(inner) => inner.flush(),
+            #[cfg(unix)]
+            Stream::Unix(inner) => inner.flush(),
+        }
+    }
+}
+
+/// queen端监听套接字
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+delegate_source!(Listener);
+
+impl Listener {
+    /// 接受连接，返回连接与对端描述
+    fn accept(&self) -> io::Result<(Stream, String)> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, peer) = listener.accept()?;
+                let _ = stream.set_nodelay(true);
+                Ok((Stream::Tcp(stream), peer.to_string()))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept()?;
+                Ok((Stream::Unix(stream), "unix".to_string()))
+            }
+        }
+    }
+}
+
+/// 非阻塞读取全部可读数据，返回对端是否已关闭
+fn read_available(stream: &mut Stream, buf: &mut Vec<u8>) -> io::Result<bool> {
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        match stream.read(&mut chunk) {
+            Ok(0) => return Ok(true),
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(false),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 非阻塞写出缓冲区数据(连接尚未建立时保留数据等待可写事件)
+fn flush_pending(stream: &mut Stream, buf: &mut Vec<u8>) -> io::Result<()> {
+    while !buf.is_empty() {
+        match stream.write(buf) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                buf.drain(..n);
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::NotConnected) => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// queen端连接
+struct Connection {
+    stream: Stream,
+    identity: Option<String>,
+    read_buf: Vec<u8>,
+    write_buf: Vec<u8>,
+}
+
+/// queen端监听
+pub struct NativeServer {
+    poll: Poll,
+    events: Events,
+    listener: Listener,
+    local_endpoint: Endpoint,
+    connections: HashMap<Token, Connection>,
+    identities: HashMap<String, Token>,
+    next_token: usize,
+    _socket_file: Option<SocketFile>,
+}
+
+impl NativeServer {
+    /// 绑定地址(如`tcp://127.0.0.1:5555`、`ipc:///tmp/zerg.sock`)
+    pub fn bind(endpoint: &str) -> Result<Self, TransportError> {
+        let endpoint = Endpoint::parse(endpoint)?;
+        let poll = Poll::new()?;
+        let (mut listener, local_endpoint, socket_file) = match endpoint {
+            Endpoint::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)?;
+                let local_addr = listener.local_addr()?;
+                (Listener::Tcp(listener), Endpoint::Tcp(local_addr), None)
+            }
+            #[cfg(unix)]
+            Endpoint::Ipc(path) => {
+                let socket_file = SocketFile::prepare(path.clone())?;
+                let listener = UnixListener::bind(&path)?;
+                (Listener::Unix(listener), Endpoint::Ipc(path), Some(socket_file))
+            }
+            #[cfg(not(unix))]
+            Endpoint::Ipc(path) => return Err(TransportError::InvalidEndpoint(path.display().to_string())),
+        };
+        poll.registry().register(&mut listener, LISTENER, Interest::READABLE)?;
+        tracing::info!(endpoint = %local_endpoint, "原生传输已绑定");
+        Ok(Self {
+            poll,
+            events: Events::with_capacity(256),
+            listener,
+            local_endpoint,
+            connections: HashMap::new(),
+            identities: HashMap::new(),
+            next_token: 1,
+            _socket_file: socket_file,
+        })
+    }
+
+    fn accept(&mut self) -> io::Result<()> {
+        loop {
+            let (mut stream, peer) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            let token = Token(self.next_token);
+            self.next_token += 1;
+            self.poll.registry().register(&mut stream, token, Interest::READABLE | Interest::WRITABLE)?;
+            tracing::debug!(peer = %peer, "接受连接");
+            self.connections.insert(token, Connection {
+                stream,
+                identity: None,
+                read_buf: Vec::new(),
+                write_buf: Vec::new(),
+            });
+        }
+    }
+
+    fn close(&mut self, token: Token) {
+        if let Some(mut conn) = self.connections.remove(&token) {
+            let _ = self.poll.registry().deregister(&mut conn.stream);
+            if let Some(identity) = conn.identity {
+                if self.identities.get(&identity) == Some(&token) {
+                    self.identities.remove(&identity);
+                }
+                tracing::debug!(identity = %identity, "连接已关闭");
+            }
+        }
+    }
+
+    /// 处理连接可读事件，返回收到的数据帧；连接应关闭时返回Err
+    fn read_connection(&mut self, token: Token, messages: &mut Vec<(String, Vec<u8>)>) -> Result<(), TransportError> {
+        let Some(conn) = self.connections.get_mut(&token) else {
+            return Ok(());
+        };
+        let closed = read_available(&mut conn.stream, &mut conn.read_buf)?;
+        let mut identity = conn.identity.clone();
+        for frame in decode_frames(&mut conn.read_buf)? {
+            match &identity {
+                Some(identity) => messages.push((identity.clone(), frame)),
+                None => {
+                    // 首帧为对端身份
+                    let claimed = String::from_utf8_lossy(&frame).to_string();
+                    let owner = self.identities.get(&claimed).copied();
+                    if owner.is_some_and(|owner| owner != token && self.connections.contains_key(&owner)) {
+                        tracing::warn!(identity = %claimed, "身份已被其他连接占用，关闭新连接");
+                        return Err(TransportError::DuplicateIdentity(claimed));
+                    }
+                    self.identities.insert(claimed.clone(), token);
+                    identity = Some(claimed);
+                }
+            }
+        }
+        if let Some(conn) = self.connections.get_mut(&token) {
+            conn.identity = identity;
+        }
+        if closed {
+            return Err(TransportError::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(())
+    }
+}
+
+impl ServerTransport for NativeServer {
+    fn local_endpoint(&self) -> Result<String, TransportError> {
+        Ok(self.local_endpoint.to_string())
+    }
+
+    fn recv(&mut self, timeout_ms: i64) -> Result<Vec<(String, Vec<u8>)>, TransportError> {
+        let mut messages = Vec::new();
+        if let Err(e) = self.poll.poll(&mut self.events, timeout_of(timeout_ms)) {
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e.into());
+            }
+        }
+
+        let ready: Vec<(Token, bool, bool)> = self.events.iter()
+            .map(|event| (event.token(), event.is_readable() || event.is_read_closed(), event.is_writable()))
+            .collect();
+        for (token, readable, writable) in ready {
+            if token == LISTENER {
+                self.accept()?;
+                continue;
+            }
+            if writable {
+                if let Some(conn) = self.connections.get_mut(&token) {
+                    if flush_pending(&mut conn.stream, &mut conn.write_buf).is_err() {
+                        self.close(token);
+                        continue;
+                    }
+                }
+            }
+            if readable {
+                if let Err(e) = self.read_connection(token, &mut messages) {
+                    tracing::debug!(error = %e, "连接读取结束");
+                    self.close(token);
+                }
+            }
+        }
+        Ok(messages)
+    }
+
+    fn send(&mut self, identity: &str, data: &[u8]) -> Result<(), TransportError> {
+        let Some(token) = self.identities.get(identity).copied() else {
+            tracing::debug!(identity = %identity, bytes = data.len(), "丢弃发往未连接节点的消息");
+            return Ok(());
+        };
+        let conn = self.connections.get_mut(&token).unwrap();
+        encode_frame(&mut conn.write_buf, data);
+        if flush_pending(&mut conn.stream, &mut conn.write_buf).is_err() {
+            self.close(token);
+        }
+        Ok(())
+    }
+}
+
+/// drone端连接
+pub struct NativeClient {
+    identity: String,
+    poll: Poll,
+    events: Events,
+    endpoint: Option<(String, Endpoint)>,
+    stream: Option<Stream>,
+    read_buf: Vec<u8>,
+    write_buf: Vec<u8>,
+    inbox: VecDeque<Vec<u8>>,
+    last_attempt: Option<Instant>,
+}
+
+impl NativeClient {
+    /// 创建未连接的客户端
+    pub fn new(identity: &str) -> Result<Self, TransportError> {
+        Ok(Self {
+            identity: identity.to_string(),
+            poll: Poll::new()?,
+            events: Events::with_capacity(16),
+            endpoint: None,
+            stream: None,
+            read_buf: Vec::new(),
+            write_buf: Vec::new(),
+            inbox: VecDeque::new(),
+            last_attempt: None,
+        })
+    }
+
+    /// 未连接时按重连间隔尝试建立连接，成功后首先发送身份帧
+    fn ensure_connected(&mut self) -> Result<(), TransportError> {
+        if self.stream.is_some() {
+            return Ok(());
+        }
+        let Some((raw, endpoint)) = self.endpoint.as_ref() else {
+            return Ok(());
+        };
+        if self.last_attempt.is_some_and(|t| t.elapsed() < RECONNECT_INTERVAL) {
+            return Ok(());
+        }
+        self.last_attempt = Some(Instant::now());
+
+        let mut stream = match Stream::connect(endpoint) {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::debug!(endpoint = %raw, error = %e, "连接失败");
+                return Ok(());
+            }
+        };
+        self.poll.registry().register(&mut stream, CLIENT, Interest::READABLE | Interest::WRITABLE)?;
+
+        // 身份帧必须位于发送缓冲区最前面
+        let mut buf = Vec::new();
+        encode_frame(&mut buf, self.identity.as_bytes());
+        buf.append(&mut self.write_buf);
+        self.write_buf = buf;
+        self.read_buf.clear();
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn drop_connection(&mut self) {
+        if let Some(mut stream) = self.stream.take() {
+            let _ = self.poll.registry().deregister(&mut stream);
+        }
+        // 未写完的数据随连接丢弃，由上层重新注册/重试
+        self.write_buf.clear();
+        self.read_buf.clear();
+    }
+
+    fn flush(&mut self) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+        if flush_pending(stream, &mut self.write_buf).is_err() {
+            self.drop_connection();
+        }
+    }
+}
+
+impl ClientTransport for NativeClient {
+    fn connect(&mut self, endpoint: &str) -> Result<(), TransportError> {
+        self.endpoint = Some((endpoint.to_string(), Endpoint::parse(endpoint)?));
+        self.last_attempt = None;
+        self.ensure_connected()
+    }
+
+    fn disconnect(&mut self, endpoint: &str) -> Result<(), TransportError> {
+        if self.endpoint.as_ref().is_some_and(|(raw, _)| raw == endpoint) {
+            self.endpoint = None;
+            self.drop_connection();
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.ensure_connected()?;
+        encode_frame(&mut self.write_buf, data);
+        self.flush();
+        Ok(())
+    }
+
+    fn recv(&mut self, timeout_ms: i64) -> Result<Option<Vec<u8>>, TransportError> {
+        if let Some(frame) = self.inbox.pop_front() {
+            return Ok(Some(frame));
+        }
+        let deadline = timeout_of(timeout_ms).map(|t| Instant::now() + t);
+        loop {
+            self.ensure_connected()?;
+            let wait = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => RECONNECT_INTERVAL,
+            };
+            // 未连接时以重连间隔为粒度等待
+            let wait = if self.stream.is_none() { wait.min(RECONNECT_INTERVAL) } else { wait };
+            if self.stream.is_none() {
+                std::thread::sleep(wait);
+            } else if let Err(e) = self.poll.poll(&mut self.events, Some(wait)) {
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e.into());
+                }
+            }
+
+            let (readable, writable) = self.events.iter().fold((false, false), |(r, w), event| {
+                (r || event.is_readable() || event.is_read_closed(), w || event.is_writable())
+            });
+            self.events.clear();
+            if writable {
+                self.flush();
+            }
+            if readable {
+                if let Some(stream) = self.stream.as_mut() {
+                    match read_available(stream, &mut self.read_buf) {
+                        Ok(closed) => {
+                            match decode_frames(&mut self.read_buf) {
+                                Ok(frames) => self.inbox.extend(frames),
+                                Err(e) => {
+                                    tracing::warn!(error = %e, "帧解析失败，断开重连");
+                                    self.drop_connection();
+                                }
+                            }
+                            if closed {
+                                self.drop_connection();
+                            }
+                        }
+                        Err(_) => self.drop_connection(),
+                    }
+                }
+            }
+
+            if let Some(frame) = self.inbox.pop_front() {
+                return Ok(Some(frame));
+            }
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(None);
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! ZeroMQ传输实现
+//!
+//! queen端ROUTER收发四帧消息(身份帧 + 两空帧 + 数据帧)，
+//! drone端DEALER收发三帧消息(两空帧 + 数据帧)，身份帧由ROUTER自动添加；
+//! 启用CURVE时握手失败或公钥不在允许列表中的drone无法建立连接
+
+use zmq::{Context, Socket, POLLIN};
+
+use super::curve::{CurveClient, CurveServer, ZapHandler};
+use super::ipc::SocketFile;
+use super::{ipc_path, ClientTransport, ServerTransport, TransportError};
+
+/// 转为ZMQ地址格式(`unix://`写作`ipc://`)
+fn zmq_endpoint(endpoint: &str) -> String {
+    match ipc_path(endpoint) {
+        Some(path) => format!("ipc://{}", path.display()),
+        None => endpoint.to_string(),
+    }
+}
+
+/// 等待可读事件；被信号中断时视为超时，由调用方在下一轮重新等待
+fn poll_readable(socket: &Socket, timeout_ms: i64) -> Result<bool, TransportError> {
+    match socket.poll(POLLIN, timeout_ms) {
+        Ok(ready) => Ok(ready > 0),
+        Err(zmq::Error::EINTR) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// queen端ROUTER
+pub struct ZmqServer {
+    socket: Socket,
+    _zap: Option<ZapHandler>,
+    _ctx: Context,
+    _socket_file: Option<SocketFile>,
+}
+
+impl ZmqServer {
+    /// 绑定地址(如`tcp://127.0.0.1:5555`、`ipc:///tmp/zerg.sock`)
+    pub fn bind(endpoint: &str) -> Result<Self, TransportError> {
+        Self::bind_with(endpoint, None)
+    }
+
+    /// 绑定地址并启用CURVE；配置了允许列表时启动ZAP认证线程
+    pub fn bind_curve(endpoint: &str, curve: &CurveServer) -> Result<Self, TransportError> {
+        Self::bind_with(endpoint, Some(curve))
+    }
+
+    fn bind_with(endpoint: &str, curve: Option<&CurveServer>) -> Result<Self, TransportError> {
+        let socket_file = ipc_path(endpoint).map(SocketFile::prepare).transpose()?;
+        let endpoint = zmq_endpoint(endpoint);
+        let ctx = Context::new();
+        let socket = ctx.socket(zmq::ROUTER)?;
+        let mut zap = None;
+        if let Some(curve) = curve {
+            curve.apply(&socket)?;
+            if let Some(authorized) = &curve.authorized_keys {
+                zap = Some(ZapHandler::start(&ctx, authorized.clone())?);
+            }
+        }
+        socket.bind(&endpoint)?;
+        tracing::info!(
+            endpoint = %endpoint,
+            curve = curve.is_some(),
+            authorized_keys = curve.and_then(|c| c.authorized_keys.as_ref()).map(|keys| keys.len()),
+            "ZMQ传输已绑定"
+        );
+        Ok(Self { socket, _zap: zap, _ctx: ctx, _socket_file: socket_file })
+    }
+
+    /// 校验四帧消息格式并取出(身份, 数据帧)
+    fn parse_frames(mut frames: Vec<Vec<u8>>) -> Option<(String, Vec<u8>)> {
+        if frames.len() != 4 {
+            tracing::warn!(frames = frames.len(), "消息格式错误：期望4帧");
+            return None;
+        }
+
+        // 解析身份帧
+        let identity = String::from_utf8_lossy(&frames[0]).to_string();
+
+        // 验证空帧
+        if !frames[1].is_empty() || !frames[2].is_empty() {
+            tracing::warn!(identity = %identity, "消息格式错误：空帧非空");
+            return None;
+        }
+
+        tracing::trace!(identity = %identity, bytes = frames[3].len(), "收到数据帧");
+        Some((identity, frames.pop().unwrap()))
+    }
+}
+
+impl ServerTransport for ZmqServer {
+    fn local_endpoint(&self) -> Result<String, TransportError> {
+        match self.socket.get_last_endpoint()? {
+            Ok(endpoint) => Ok(endpoint),
+            Err(raw) => {
+                log::error!("ZMQ endpoint error: {}", String::from_utf8_lossy(&raw));
+                Err(TransportError::Zmq(zmq::Error::EINVAL))
+            }
+        }
+    }
+
+    fn recv(&mut self, timeout_ms: i64) -> Result<Vec<(String, Vec<u8>)>, TransportError> {
+        let mut messages = Vec::new();
+        if !poll_readable(&self.socket, timeout_ms)? {
+            return Ok(messages);
+        }
+
+        // 一次读空所有已到达的消息
+        loop {
+            let frames = match self.socket.recv_multipart(zmq::DONTWAIT) {
+                Ok(frames) => frames,
+                Err(zmq::Error::EAGAIN) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if let Some(message) = Self::parse_frames(frames) {
+                messages.push(message);
+            }
+        }
+        Ok(messages)
+    }
+
+    fn send(&mut self, identity: &str, data: &[u8]) -> Result<(), TransportError> {
+        self.socket.send(identity.as_bytes(), zmq::SNDMORE)?;
+        self.socket.send("", zmq::SNDMORE)?;
+        self.socket.send("", zmq::SNDMORE)?;
+        self.socket.send(data, 0)?;
+        Ok(())
+    }
+}
+
+/// drone端DEALER
+pub struct ZmqClient {
+    _ctx: Context,
+    socket: Socket,
+}
+
+impl ZmqClient {
+    /// 创建DEALER并设置身份帧
+    pub fn new(identity: &str) -> Result<Self, TransportError> {
+        let ctx = Context::new();
+        let socket = ctx.socket(zmq::DEALER)?;
+        socket.set_identity(identity.as_bytes())?;
+        Ok(Self { _ctx: ctx, socket })
+    }
+
+    /// 创建使用CURVE握手的DEALER
+    pub fn new_curve(identity: &str, curve: &CurveClient) -> Result<Self, TransportError> {
+        let client = Self::new(identity)?;
+        curve.apply(&client.socket)?;
+        Ok(client)
+    }
+}
+
+impl ClientTransport for ZmqClient {
+    fn connect(&mut self, endpoint: &str) -> Result<(), TransportError> {
+        Ok(self.socket.connect(&zmq_endpoint(endpoint))?)
+    }
+
+    fn disconnect(&mut self, endpoint: &str) -> Result<(), TransportError> {
+        Ok(self.socket.disconnect(&zmq_endpoint(endpoint))?)
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        self.socket.send("", zmq::SNDMORE)?; // 空帧1
+        self.socket.send("", zmq::SNDMORE)?; // 空帧2
+        self.socket.send(data, 0)?; // 数据帧
+        Ok(())
+    }
+
+    fn recv(&mut self, timeout_ms: i64) -> Result<Option<Vec<u8>>, TransportError> {
+        if !poll_readable(&self.socket, timeout_ms)? {
+            return Ok(None);
+        }
+        let mut frames = self.socket.recv_multipart(0)?;
+        Ok(frames.pop())
+    }
+}
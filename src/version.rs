@@ -0,0 +1,114 @@
+//! 版本号解析与兼容性判断
+//!
+//! 采用语义化版本(`major.minor.patch[-pre][+build]`)，crate版本的兼容规则与Cargo一致：
+//! 主版本号大于0时主版本相同即兼容；`0.x`版本要求次版本号也相同。
+//!
+//! 线协议版本独立于crate版本，构建时从`proto/task.proto`的`zerg.protocol_version`选项读取。
+//! drone注册时上报协议版本，queen按兼容矩阵检查后回复`RegistrationAccepted`(携带queen协议版本)
+//! 或`RegistrationRejected`，drone收到后同样按矩阵检查queen的协议版本
+
+use std::fmt;
+use std::str::FromStr;
+
+/// 当前crate版本(drone注册时上报)
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 当前线协议版本(由build.rs从proto descriptor中读取)
+pub const PROTOCOL_VERSION: &str = env!("ZERG_PROTOCOL_VERSION");
+
+/// 未上报协议版本的drone(协商机制之前的实现)按该版本处理
+pub const LEGACY_PROTOCOL_VERSION: &str = "1.0.0";
+
+/// 协议兼容矩阵：只有主版本相同的两端可以互通
+///
+/// 3.0起所有消息封装在`Envelope`中，2.x及之前的drone发送裸protobuf消息，两者无法互相解码，
+/// 因此不承诺跨主版本兼容；变更消息封装或字段语义时必须提升主版本
+///
+/// | 本端 \ 对端 | N-1 | N | N+1 |
+/// |-------------|-----|---|-----|
+/// | N           | ✗   | ✓ | ✗   |
+pub fn protocol_compatible(local: &Version, remote: &Version) -> bool {
+    local.major == remote.major
+}
+
+/// 解析对端上报的协议版本，为空时视为`LEGACY_PROTOCOL_VERSION`
+pub fn parse_protocol(version: &str) -> Result<Version, VersionError> {
+    if version.trim().is_empty() {
+        return LEGACY_PROTOCOL_VERSION.parse();
+    }
+    version.parse()
+}
+
+/// 版本号解析错误
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+#[error("无效的版本号: {0}")]
+pub struct VersionError(pub String);
+
+/// 语义化版本号
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// 预发布标识(如`beta.1`)
+    pub pre: Option<String>,
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch, pre: None }
+    }
+
+    /// 当前crate版本
+    pub fn current() -> Self {
+        CRATE_VERSION.parse().expect("CARGO_PKG_VERSION为合法版本号")
+    }
+
+    /// 当前线协议版本
+    pub fn protocol() -> Self {
+        PROTOCOL_VERSION.parse().expect("zerg.protocol_version为合法版本号")
+    }
+
+    /// 两个版本能否互通
+    pub fn is_compatible(&self, other: &Version) -> bool {
+        match (self.major, other.major) {
+            (0, 0) => self.minor == other.minor,
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl FromStr for Version {
+    type Err = VersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || VersionError(s.to_string());
+        // 构建元数据不参与比较
+        let core = s.trim().split('+').next().unwrap_or_default();
+        let (core, pre) = match core.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return Err(invalid()),
+            None => (core, None),
+        };
+        let mut parts = core.split('.').map(|part| {
+            if part.is_empty() || (part.len() > 1 && part.starts_with('0')) {
+                return Err(invalid());
+            }
+            part.parse::<u64>().map_err(|_| invalid())
+        });
+        let (Some(major), Some(minor), Some(patch), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        Ok(Self { major: major?, minor: minor?, patch: patch?, pre })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! 运维管理接口测试
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+use zerg_pool::config::{PoolConfig, RetryPolicy};
+use zerg_pool::drone::network::{DroneNetwork, NetworkError};
+use zerg_pool::proto::zergpool::{response, RejectReason, Response, Task};
+use zerg_pool::queen::admin::{AdminClient, AdminError, AdminRequest, AdminResponse, AdminServer, WorkerRole};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{DronePool, PoolError, ProcessMessage};
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: id.as_bytes().to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn connect_drone(endpoint: &str, worker_id: &str) -> DroneNetwork {
+    let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, worker_id, vec![endpoint.to_string()]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register(worker_id, vec![]).unwrap();
+    drone
+}
+
+/// 轮询进程池与drone，直到drone收到任务
+fn pump_task(pool: &mut DronePool, drone: &mut DroneNetwork) -> Option<Task> {
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if let Some(ProcessMessage::Task(task)) = drone.poll_message(10).unwrap() {
+            return Some(task);
+        }
+    }
+    None
+}
+
+#[test]
+fn test_drain_promote_pause_and_dead_letters() {
+    let endpoint = "inproc://admin_ops";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .max_main_pool_size(1)
+        .retry(RetryPolicy { max_retries: 0, ..RetryPolicy::default() })
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let mut first = connect_drone(endpoint, "admin-a");
+    let mut second = connect_drone(endpoint, "admin-b");
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        first.poll_message(5).unwrap();
+        second.poll_message(5).unwrap();
+        if pool.workers().len() == 2 {
+            break;
+        }
+    }
+    let roles: Vec<_> = pool.workers().into_iter().map(|w| (w.id, w.role)).collect();
+    assert_eq!(roles, vec![
+        ("admin-a".to_string(), WorkerRole::Main),
+        ("admin-b".to_string(), WorkerRole::Backup),
+    ]);
+
+    // 主池已满时晋升会将原主池节点降级
+    pool.promote_worker(&"admin-b".to_string()).unwrap();
+    assert_eq!(pool.workers()[0].id, "admin-b");
+    assert_eq!(pool.workers()[1].role, WorkerRole::Backup);
+    assert!(matches!(pool.promote_worker(&"nobody".to_string()), Err(PoolError::UnknownWorker(_))));
+
+    // 暂停期间任务留在队列中
+    pool.pause_dispatch();
+    pool.submit_task(new_task("t1")).unwrap();
+    for _ in 0..5 {
+        pool.poll_events().unwrap();
+    }
+    assert_eq!(pool.queue_info().pending, 1);
+    assert!(pool.queue_info().dispatch_paused);
+    pool.resume_dispatch();
+
+    // 排空中的节点不再接收任务
+    pool.drain_worker(&"admin-b".to_string()).unwrap();
+    assert!(pool.workers()[0].draining);
+    let task = pump_task(&mut pool, &mut first).expect("任务应派发给未排空的节点");
+    assert_eq!(task.id, "t1");
+    assert!(second.poll_message(10).unwrap().is_none());
+    assert_eq!(pool.workers()[1].in_flight, 1);
+
+    first.send_response(&Response {
+        worker_id: "admin-a".to_string(),
+        task_id: task.id,
+        result: Some(response::Result::Error("boom".to_string())),
+    }).unwrap();
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if pool.queue_info().dead_letters == 1 {
+            break;
+        }
+    }
+    let dead = pool.dead_letters();
+    assert_eq!(dead.len(), 1);
+    assert_eq!((dead[0].task.id.as_str(), dead[0].worker_id.as_str(), dead[0].error.as_str()), ("t1", "admin-a", "boom"));
+    assert_eq!(pool.in_flight_task_count(), 0);
+}
+
+#[test]
+fn test_evicted_drone_is_told_to_exit() {
+    let endpoint = "inproc://admin_evict";
+    let config = PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let mut drone = connect_drone(endpoint, "evict-a");
+    pool.submit_task(new_task("t1")).unwrap();
+    assert!(pump_task(&mut pool, &mut drone).is_some());
+
+    assert_eq!(pool.evict_worker(&"evict-a".to_string()).unwrap(), 1);
+    assert!(pool.workers().is_empty());
+    assert_eq!(pool.pending_task_count(), 1);
+    assert!(matches!(pool.evict_worker(&"evict-a".to_string()), Err(PoolError::UnknownWorker(_))));
+
+    let result = (0..50).find_map(|_| drone.poll_message(10).err());
+    assert!(matches!(result, Some(NetworkError::Evicted(_))), "{:?}", result);
+    assert_eq!(pool.evicted_workers(), vec!["evict-a".to_string()]);
+
+    // 解除前重新注册被拒绝(如监管器重启的drone，原连接已随进程退出关闭)
+    drop(drone);
+    let mut restarted = connect_drone(endpoint, "evict-a");
+    let result = (0..50).find_map(|_| {
+        pool.poll_events().unwrap();
+        restarted.poll_message(5).err()
+    });
+    assert!(matches!(result, Some(NetworkError::Rejected { reason: RejectReason::WorkerEvicted, .. })), "{:?}", result);
+    assert!(pool.workers().is_empty());
+
+    pool.unevict_worker(&"evict-a".to_string()).unwrap();
+    assert!(matches!(pool.unevict_worker(&"evict-a".to_string()), Err(PoolError::UnknownWorker(_))));
+    drop(restarted);
+    let _restarted = connect_drone(endpoint, "evict-a");
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if !pool.workers().is_empty() {
+            break;
+        }
+    }
+    assert_eq!(pool.workers()[0].id, "evict-a");
+}
+
+#[test]
+fn test_control_endpoint_round_trip() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder().max_main_pool_size(3).build().unwrap();
+    let handle = DronePool::with_config("127.0.0.1", port, config).unwrap().spawn();
+    assert!(AdminServer::bind("127.0.0.1:0", " ", handle.sender()).is_err());
+    let server = AdminServer::bind("127.0.0.1:0", "s3cret", handle.sender()).unwrap();
+
+    let mut intruder = AdminClient::connect(server.local_addr(), "guess", Duration::from_secs(5)).unwrap();
+    assert!(matches!(intruder.request(&AdminRequest::PauseDispatch), Err(AdminError::Rejected(message)) if message.contains("未授权")));
+    let mut client = AdminClient::connect(server.local_addr(), "s3cret", Duration::from_secs(5)).unwrap();
+    assert_eq!(client.request(&AdminRequest::ListWorkers).unwrap(), AdminResponse::Workers { workers: vec![] });
+    assert!(matches!(client.request(&AdminRequest::PauseDispatch).unwrap(), AdminResponse::Ok { .. }));
+    let AdminResponse::Queue(queue) = client.request(&AdminRequest::Queue).unwrap() else {
+        panic!("应返回队列状态");
+    };
+    assert!(queue.dispatch_paused && queue.is_leader);
+    let AdminResponse::Config { config } = client.request(&AdminRequest::Config).unwrap() else {
+        panic!("应返回配置");
+    };
+    assert_eq!(config.max_main_pool_size, 3);
+    assert!(matches!(
+        client.request(&AdminRequest::Evict { worker_id: "nobody".to_string() }),
+        Err(AdminError::Rejected(message)) if message.contains("nobody")
+    ));
+    assert!(matches!(handle.admin(AdminRequest::ResumeDispatch).unwrap(), AdminResponse::Ok { .. }));
+
+    // 请求格式为每行一个JSON
+    let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+    stream.write_all(b"{\"token\":\"s3cret\",\"command\":\"queue\"}\n{\"command\":\"queue\"}\nnot json\n").unwrap();
+    let mut lines = BufReader::new(stream).lines();
+    let queue: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(queue["kind"], "queue");
+    assert_eq!(queue["dispatch_paused"], false);
+    for _ in 0..2 {
+        let error: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(error["kind"], "error");
+    }
+
+    handle.shutdown().unwrap();
+    assert!(matches!(client.request(&AdminRequest::Queue), Err(AdminError::Rejected(_))));
+}
@@ -0,0 +1,312 @@
+//! 注册准入测试：令牌、版本兼容性、节点数上限与重复ID，被拒绝的drone收到原因
+
+use std::collections::HashMap;
+use std::time::Duration;
+use zerg_pool::config::{AdmissionConfig, DroneConfig, DuplicatePolicy, PoolConfig};
+use zerg_pool::drone::network::{DroneNetwork, NetworkError};
+use zerg_pool::proto::zergpool::{response, ControlKind, Heartbeat, Registration, RejectReason, Response, Task};
+use zerg_pool::queen::events::PoolEvent;
+use zerg_pool::transport::{self, ClientTransport, TransportKind};
+use zerg_pool::version::{Version, PROTOCOL_VERSION};
+use zerg_pool::{DronePool, ProcessMessage};
+
+fn connect(endpoint: &str, worker_id: &str, token: Option<&str>) -> DroneNetwork {
+    let mut config = DroneConfig::builder().transport(TransportKind::Inproc);
+    if let Some(token) = token {
+        config = config.token(token);
+    }
+    let mut drone = DroneNetwork::connect_config(&config.build().unwrap(), worker_id, vec![endpoint.to_string()]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register(worker_id, vec![]).unwrap();
+    drone
+}
+
+/// 以原始传输模拟连接身份与节点ID不一致的drone(如冒用他人ID)
+fn register_raw(endpoint: &str, identity: &str, worker_id: &str, token: &str) -> Box<dyn ClientTransport> {
+    let mut drone = transport::client(TransportKind::Inproc, identity).unwrap();
+    drone.connect(endpoint).unwrap();
+    let reg = ProcessMessage::Registration(Registration {
+        worker_id: worker_id.to_string(),
+        max_threads: 1,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        capabilities: vec![],
+        token: token.to_string(),
+        protocol_version: PROTOCOL_VERSION.to_string(),
+    });
+    drone.send(&reg.encode_envelope()).unwrap();
+    drone
+}
+
+/// 轮询queen直到drone收到拒绝回复
+fn expect_rejected(pool: &mut DronePool, drone: &mut DroneNetwork) -> RejectReason {
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        match drone.poll_message(5) {
+            Ok(_) => {}
+            Err(NetworkError::Rejected { reason, queen_version, .. }) => {
+                assert_eq!(queen_version, env!("CARGO_PKG_VERSION"));
+                return reason;
+            }
+            Err(e) => panic!("意外错误: {}", e),
+        }
+    }
+    panic!("未收到拒绝回复");
+}
+
+fn poll(pool: &mut DronePool) {
+    for _ in 0..20 {
+        pool.poll_events().unwrap();
+    }
+}
+
+fn poll_until(pool: &mut DronePool, done: impl Fn(&DronePool) -> bool) {
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if done(pool) {
+            return;
+        }
+    }
+    panic!("等待超时");
+}
+
+#[test]
+fn test_version_compatibility() {
+    let parse = |s: &str| s.parse::<Version>().unwrap();
+    assert_eq!(parse("1.2.3-beta.1+build"), Version { pre: Some("beta.1".to_string()), ..Version::new(1, 2, 3) });
+    assert_eq!(parse("1.2.3-beta.1").to_string(), "1.2.3-beta.1");
+    for invalid in ["", "1.2", "1.2.3.4", "01.2.3", "1.x.3", "1.2.3-"] {
+        assert!(invalid.parse::<Version>().is_err(), "{}", invalid);
+    }
+    assert!(parse("1.2.0").is_compatible(&parse("1.9.4")));
+    assert!(!parse("1.2.0").is_compatible(&parse("2.0.0")));
+    assert!(parse("0.1.0").is_compatible(&parse("0.1.7")));
+    assert!(!parse("0.1.0").is_compatible(&parse("0.2.0")));
+}
+
+#[test]
+fn test_admission_rejects_with_reason() {
+    let endpoint = "inproc://admission_reject";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .admission(AdmissionConfig { token: Some("s3cret".to_string()), max_workers: Some(2), ..Default::default() })
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let events = pool.subscribe();
+
+    let mut anonymous = connect(endpoint, "anonymous", None);
+    assert_eq!(expect_rejected(&mut pool, &mut anonymous), RejectReason::InvalidToken);
+    let mut wrong = connect(endpoint, "wrong", Some("s3cre7"));
+    assert_eq!(expect_rejected(&mut pool, &mut wrong), RejectReason::InvalidToken);
+
+    let _a = connect(endpoint, "a", Some("s3cret"));
+    let _b = connect(endpoint, "b", Some("s3cret"));
+    poll(&mut pool);
+    assert_eq!(pool.workers().len(), 2);
+
+    // 节点数已达上限，但已注册节点重连不受影响
+    let mut c = connect(endpoint, "c", Some("s3cret"));
+    assert_eq!(expect_rejected(&mut pool, &mut c), RejectReason::PoolFull);
+    let mut a = connect(endpoint, "a", Some("s3cret"));
+    poll(&mut pool);
+    assert!(a.poll_message(5).is_ok());
+
+    // 另一个连接使用在线节点的ID
+    let mut imposter = register_raw(endpoint, "imposter", "b", "s3cret");
+    let mut reply = None;
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if let Some(data) = imposter.recv(5).unwrap() {
+            reply = Some(ProcessMessage::decode_envelope(&data).unwrap());
+            break;
+        }
+    }
+    let Some(ProcessMessage::RegistrationRejected(rejected)) = reply else {
+        panic!("未收到拒绝回复: {:?}", reply);
+    };
+    assert_eq!(rejected.reason(), RejectReason::DuplicateId);
+    assert_eq!(pool.workers().len(), 2);
+
+    let rejected: Vec<RejectReason> = events.try_iter()
+        .filter_map(|e| match e {
+            PoolEvent::RegistrationRejected { reason, .. } => Some(reason),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(rejected, vec![
+        RejectReason::InvalidToken,
+        RejectReason::InvalidToken,
+        RejectReason::PoolFull,
+        RejectReason::DuplicateId,
+    ]);
+}
+
+#[test]
+fn test_duplicate_id_replaces_previous_connection() {
+    let endpoint = "inproc://admission_replace";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .admission(AdmissionConfig { duplicate_ids: DuplicatePolicy::Replace, ..Default::default() })
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let mut old = connect(endpoint, "shared", None);
+    poll(&mut pool);
+    let _new = register_raw(endpoint, "shared-new", "shared", "");
+    poll(&mut pool);
+
+    assert_eq!(pool.workers().len(), 1);
+    assert!(matches!(old.poll_message(100), Err(NetworkError::Evicted(_))));
+}
+
+#[test]
+fn test_register_requires_connection_identity() {
+    let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, "self", vec!["inproc://admission_mismatch".to_string()]).unwrap();
+    let result = drone.register("other", vec![]);
+    assert!(matches!(result, Err(NetworkError::WorkerIdMismatch { ref connected, ref requested })
+        if connected == "self" && requested == "other"), "{:?}", result);
+    assert_eq!(drone.worker_id(), "self");
+}
+
+#[test]
+fn test_messages_require_registered_connection() {
+    let endpoint = "inproc://admission_identity";
+    let config = PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let mut a = connect(endpoint, "a", None);
+    pool.submit_task(Task { id: "t1".to_string(), payload: vec![], timestamp: 0, metadata: HashMap::new(), priority: None }).unwrap();
+    let task = (0..50).find_map(|_| {
+        pool.poll_events().unwrap();
+        match a.poll_message(5).unwrap() {
+            Some(ProcessMessage::Task(task)) => Some(task),
+            _ => None,
+        }
+    });
+    assert_eq!(task.expect("任务未派发").id, "t1");
+    let mut b = connect(endpoint, "b", None);
+    poll_until(&mut pool, |pool| pool.workers().len() == 2);
+
+    // 未注册的连接冒用节点a发送心跳与任务结果
+    let mut imposter = transport::client(TransportKind::Inproc, "imposter").unwrap();
+    imposter.connect(endpoint).unwrap();
+    let heartbeat = Heartbeat { worker_id: "a".to_string(), cpu_usage: 0.9, ..Default::default() };
+    imposter.send(&ProcessMessage::Heartbeat(heartbeat).encode_envelope()).unwrap();
+    let result = |worker_id: &str| Response {
+        worker_id: worker_id.to_string(),
+        task_id: "t1".to_string(),
+        result: Some(response::Result::Output(vec![])),
+    };
+    imposter.send(&ProcessMessage::TaskResponse(result("a")).encode_envelope()).unwrap();
+    let mut reply = None;
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if let Some(data) = imposter.recv(5).unwrap() {
+            reply = Some(ProcessMessage::decode_envelope(&data).unwrap());
+            break;
+        }
+    }
+    let Some(ProcessMessage::Control(control)) = reply else {
+        panic!("未收到控制消息: {:?}", reply);
+    };
+    assert_eq!(control.kind(), ControlKind::UnknownWorker);
+    for _ in 0..3 {
+        pool.poll_events().unwrap();
+    }
+    assert!(pool.workers().iter().all(|w| w.cpu_usage != 0.9));
+    assert_eq!(pool.in_flight_task_count(), 1);
+
+    // 已注册的节点b不能完成派发给a的任务
+    b.send_response(&result("b")).unwrap();
+    for _ in 0..3 {
+        pool.poll_events().unwrap();
+    }
+    assert_eq!(pool.in_flight_task_count(), 1);
+
+    a.send_response(&result("a")).unwrap();
+    poll_until(&mut pool, |pool| pool.in_flight_task_count() == 0);
+}
+
+#[test]
+fn test_duplicate_identity_cannot_take_over_connection() {
+    for (kind, endpoint) in [(TransportKind::Native, "tcp://127.0.0.1:0"), (TransportKind::Inproc, "inproc://admission_hijack")] {
+        let config = PoolConfig::builder().transport(kind).build().unwrap();
+        let mut pool = DronePool::bind(endpoint, config).unwrap();
+        let endpoint = pool.local_endpoint().unwrap();
+        let mut drone = DroneNetwork::connect_with(kind, "victim", vec![endpoint.clone()]).unwrap();
+        drone.set_heartbeat_interval(Duration::ZERO);
+        drone.register("victim", vec![]).unwrap();
+        poll_until(&mut pool, |pool| pool.workers().len() == 1);
+
+        // 第二个连接自称同一身份，发送心跳并等待被路由到的消息
+        let mut imposter = transport::client(kind, "victim").unwrap();
+        imposter.connect(&endpoint).unwrap();
+        let heartbeat = Heartbeat { worker_id: "victim".to_string(), cpu_usage: 0.9, ..Default::default() };
+        imposter.send(&ProcessMessage::Heartbeat(heartbeat).encode_envelope()).unwrap();
+        for _ in 0..10 {
+            pool.poll_events().unwrap();
+        }
+        assert!(pool.workers().iter().all(|w| w.cpu_usage != 0.9), "{:?}: 冒用身份的心跳被接受", kind);
+
+        // 任务仍派发给原连接
+        pool.submit_task(Task { id: "t1".to_string(), payload: vec![], timestamp: 0, metadata: HashMap::new(), priority: None }).unwrap();
+        let task = (0..50).find_map(|_| {
+            pool.poll_events().unwrap();
+            match drone.poll_message(5).unwrap() {
+                Some(ProcessMessage::Task(task)) => Some(task),
+                _ => None,
+            }
+        });
+        assert_eq!(task.map(|t| t.id).as_deref(), Some("t1"), "{:?}", kind);
+        assert_eq!(imposter.recv(20).unwrap(), None, "{:?}: 冒用身份的连接收到消息", kind);
+    }
+}
+
+#[test]
+fn test_incompatible_version_rejected() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let mut pool = DronePool::bind(&format!("tcp://127.0.0.1:{}", port), PoolConfig::default()).unwrap();
+    let ctx = zmq::Context::new();
+    let drone = ctx.socket(zmq::DEALER).unwrap();
+    drone.set_identity(b"legacy").unwrap();
+    drone.set_linger(0).unwrap();
+    drone.connect(&format!("tcp://127.0.0.1:{}", port)).unwrap();
+    let reg = ProcessMessage::Registration(Registration {
+        worker_id: "legacy".into(),
+        max_threads: 1,
+        version: "99.0.0".into(),
+        capabilities: vec![],
+        token: String::new(),
+        protocol_version: zerg_pool::version::PROTOCOL_VERSION.into(),
+    });
+    drone.send_multipart(["".as_bytes(), "".as_bytes(), &reg.encode_envelope()], 0).unwrap();
+
+    let mut reply = None;
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if let Ok(frames) = drone.recv_multipart(zmq::DONTWAIT) {
+            reply = frames.last().map(|data| ProcessMessage::decode_envelope(data).unwrap());
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let Some(ProcessMessage::RegistrationRejected(rejected)) = reply else {
+        panic!("未收到拒绝回复: {:?}", reply);
+    };
+    assert_eq!(rejected.reason(), RejectReason::IncompatibleVersion);
+    assert!(rejected.message.contains("99.0.0"), "{}", rejected.message);
+    assert_eq!(pool.workers().len(), 0);
+
+    // 关闭版本检查后可注册
+    let mut config = PoolConfig::default();
+    config.admission.check_version = false;
+    pool.reload_config(config).unwrap();
+    drone.send_multipart(["".as_bytes(), "".as_bytes(), &reg.encode_envelope()], 0).unwrap();
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if pool.workers().len() == 1 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(pool.workers().len(), 1);
+}
@@ -0,0 +1,93 @@
+//! 异步接口集成测试
+
+use std::collections::HashMap;
+use std::time::Duration;
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::proto::zergpool::{response, Response, Task};
+use zerg_pool::{AsyncDroneNetwork, AsyncDronePool, Drone};
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: id.as_bytes().to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn drone_config(id: &str) -> DroneConfig {
+    DroneConfig::builder()
+        .worker_id(id)
+        .heartbeat_interval(Duration::from_millis(50))
+        .heartbeat_timeout(Duration::from_millis(1000))
+        .build()
+        .unwrap()
+}
+
+async fn wait_idle(pool: &AsyncDronePool) -> bool {
+    for _ in 0..100 {
+        let stats = pool.stats().await.unwrap();
+        if stats.pending_tasks == 0 && stats.in_flight_tasks == 0 {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    false
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_async_pool_and_drone_round_trip() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let pool = AsyncDronePool::bind("127.0.0.1", port, PoolConfig::default()).unwrap();
+
+    let endpoint = format!("tcp://127.0.0.1:{}", port);
+    let mut drone = AsyncDroneNetwork::connect(vec![endpoint], &drone_config("async-1")).unwrap();
+    drone.register(vec![]).unwrap();
+    for _ in 0..100 {
+        if pool.stats().await.unwrap().workers == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    for i in 0..3 {
+        pool.submit(new_task(&format!("t{}", i))).await.unwrap();
+    }
+    for _ in 0..3 {
+        let task = tokio::time::timeout(Duration::from_secs(5), drone.recv_task())
+            .await
+            .expect("等待任务超时")
+            .expect("连接已关闭");
+        drone.send_response(Response {
+            worker_id: drone.worker_id().to_string(),
+            task_id: task.id,
+            result: Some(response::Result::Output(task.payload)),
+        }).unwrap();
+    }
+    assert!(wait_idle(&pool).await);
+
+    drone.close().await.unwrap();
+    pool.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_drone_runtime_runs_on_blocking_pool() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let pool = AsyncDronePool::bind("127.0.0.1", port, PoolConfig::default()).unwrap();
+
+    let drone = Drone::builder(format!("tcp://127.0.0.1:{}", port))
+        .config(drone_config("async-2"))
+        .executor(|task: &Task| Ok(task.payload.clone()))
+        .build()
+        .unwrap();
+    let handle = drone.handle();
+    let runner = tokio::spawn(drone.run_async());
+
+    pool.submit(new_task("t0")).await.unwrap();
+    assert!(wait_idle(&pool).await);
+
+    handle.stop();
+    runner.await.unwrap().unwrap();
+    pool.shutdown().await.unwrap();
+}
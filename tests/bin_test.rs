@@ -0,0 +1,178 @@
+//! zerg-queen/zerg-drone可执行文件测试
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use zerg_pool::queen::admin::CONTROL_TOKEN_ENV;
+use zerg_pool::queen::supervisor::{EVICTED_EXIT_CODE, REJECTED_EXIT_CODE};
+
+const CONTROL_TOKEN: &str = "bin-test-token";
+
+fn free_port() -> u16 {
+    portpicker::pick_unused_port().expect("无可用端口")
+}
+
+fn scrape(addr: &str) -> Option<String> {
+    let mut stream = TcpStream::connect(addr).ok()?;
+    stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    Some(response)
+}
+
+/// 等待子进程退出，返回退出状态(超时后强制结束)
+fn wait_exit(child: &mut Child) -> Option<ExitStatus> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if let Some(status) = child.try_wait().unwrap() {
+            return Some(status);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    let _ = child.kill();
+    None
+}
+
+fn terminate(child: &mut Child) -> bool {
+    Command::new("kill").args(["-TERM", &child.id().to_string()]).status().unwrap();
+    wait_exit(child).is_some_and(|status| status.success())
+}
+
+fn ctl(control: &str, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_zerg-ctl"))
+        .args(["--control", control])
+        .args(args)
+        .env(CONTROL_TOKEN_ENV, CONTROL_TOKEN)
+        .output()
+        .unwrap();
+    (output.status.success(), String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn test_queen_and_drone_binaries() {
+    let endpoint = format!("tcp://127.0.0.1:{}", free_port());
+    let metrics = format!("127.0.0.1:{}", free_port());
+    let control = format!("127.0.0.1:{}", free_port());
+    let mut queen = Command::new(env!("CARGO_BIN_EXE_zerg-queen"))
+        .args(["--bind", &endpoint, "--metrics", &metrics, "--control", &control])
+        .env(CONTROL_TOKEN_ENV, CONTROL_TOKEN)
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut drone = Command::new(env!("CARGO_BIN_EXE_zerg-drone"))
+        .args(["--queen", &endpoint, "--worker-id", "bin-drone", "--capability", "a,b", "-j", "2"])
+        .env("ZERG_DRONE_HEARTBEAT_INTERVAL_MS", "100")
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut registered = false;
+    while !registered && Instant::now() < deadline {
+        registered = scrape(&metrics).is_some_and(|body| body.contains("\nzergpool_workers 1\n"));
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(registered, "drone未注册到queen");
+    let body = scrape(&metrics).unwrap();
+    assert!(body.contains("zergpool_is_leader 1"));
+    assert!(body.contains("zergpool_worker_up{worker=\"bin-drone\"} 1"), "{}", body);
+
+    let (ok, workers) = ctl(&control, &["-o", "json", "workers"]);
+    assert!(ok);
+    let workers: serde_json::Value = serde_json::from_str(&workers).unwrap();
+    assert_eq!(workers["workers"][0]["id"], "bin-drone");
+    assert_eq!(workers["workers"][0]["capabilities"], serde_json::json!(["a", "b"]));
+    let (ok, queue) = ctl(&control, &["queue"]);
+    assert!(ok && queue.contains("dispatch_paused  false"), "{}", queue);
+    assert!(!ctl(&control, &["drain", "nobody"]).0);
+    let unauthorized = Command::new(env!("CARGO_BIN_EXE_zerg-ctl"))
+        .args(["--control", &control, "--token", "wrong", "queue"])
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!unauthorized.success());
+
+    assert!(terminate(&mut drone), "zerg-drone未正常退出");
+    assert!(terminate(&mut queen), "zerg-queen未正常退出");
+}
+
+#[test]
+fn test_ctl_evicts_drone() {
+    let endpoint = format!("tcp://127.0.0.1:{}", free_port());
+    let control = format!("127.0.0.1:{}", free_port());
+    let mut queen = Command::new(env!("CARGO_BIN_EXE_zerg-queen"))
+        .args(["--bind", &endpoint, "--control", &control])
+        .env(CONTROL_TOKEN_ENV, CONTROL_TOKEN)
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut drone = Command::new(env!("CARGO_BIN_EXE_zerg-drone"))
+        .args(["--queen", &endpoint, "--worker-id", "evict-drone"])
+        .env("ZERG_DRONE_HEARTBEAT_INTERVAL_MS", "100")
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut registered = false;
+    while !registered && Instant::now() < deadline {
+        registered = ctl(&control, &["workers"]).1.contains("evict-drone");
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(registered, "drone未注册到queen");
+
+    let (ok, message) = ctl(&control, &["evict", "evict-drone"]);
+    assert!(ok, "{}", message);
+    // 被移除的drone以专用退出码退出，监管器据此不再重启
+    assert_eq!(wait_exit(&mut drone).and_then(|status| status.code()), Some(EVICTED_EXIT_CODE));
+    assert!(!ctl(&control, &["workers"]).1.contains("evict-drone"));
+    assert!(ctl(&control, &["unevict", "evict-drone"]).0);
+    assert!(!ctl(&control, &["unevict", "evict-drone"]).0);
+    assert!(terminate(&mut queen), "zerg-queen未正常退出");
+}
+
+#[test]
+fn test_rejected_drone_exits_for_good() {
+    let endpoint = format!("tcp://127.0.0.1:{}", free_port());
+    let mut queen = Command::new(env!("CARGO_BIN_EXE_zerg-queen"))
+        .args(["--bind", &endpoint])
+        .env("ZERG_POOL_ADMISSION", r#"{"token":"queen-token"}"#)
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut drone = Command::new(env!("CARGO_BIN_EXE_zerg-drone"))
+        .args(["--queen", &endpoint, "--worker-id", "rejected-drone"])
+        .env("ZERG_DRONE_TOKEN", "wrong-token")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // 令牌错误重试无效，drone以专用退出码退出，监管器据此不再重启
+    assert_eq!(wait_exit(&mut drone).and_then(|status| status.code()), Some(REJECTED_EXIT_CODE));
+    assert!(terminate(&mut queen), "zerg-queen未正常退出");
+}
+
+#[test]
+fn test_queen_requires_control_token() {
+    let status = Command::new(env!("CARGO_BIN_EXE_zerg-queen"))
+        .args(["--bind", "tcp://127.0.0.1:*", "--control", "127.0.0.1:0"])
+        .env_remove(CONTROL_TOKEN_ENV)
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+fn test_drone_requires_queen_endpoint() {
+    let status = Command::new(env!("CARGO_BIN_EXE_zerg-drone"))
+        .env_remove("ZERG_QUEEN_ENDPOINT")
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+}
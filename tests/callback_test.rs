@@ -0,0 +1,103 @@
+//! 任务队列回调测试：回调挂在队列实例上，多个队列互不影响
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zerg_pool::config::DroneConfig;
+use zerg_pool::drone::network::NetworkError;
+use zerg_pool::drone::{CallbackHandler, TaskQueue};
+use zerg_pool::proto::zergpool::{Response, Task};
+
+/// 记录收到的任务ID
+#[derive(Debug, Default)]
+struct Recorder(Mutex<Vec<String>>);
+
+impl Recorder {
+    fn ids(&self) -> Vec<String> {
+        let mut ids = self.0.lock().unwrap().clone();
+        ids.sort();
+        ids
+    }
+}
+
+impl CallbackHandler for Recorder {
+    fn handle(&self, response: &Response) -> Result<(), NetworkError> {
+        self.0.lock().unwrap().push(response.task_id.clone());
+        Ok(())
+    }
+}
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: vec![],
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn wait_until(condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn test_callbacks_are_per_queue() {
+    let first = TaskQueue::with_config("queue-1", &DroneConfig::default());
+    let second = TaskQueue::with_config("queue-2", &DroneConfig::default());
+    let a = Arc::new(Recorder::default());
+    let b = Arc::new(Recorder::default());
+    let c = Arc::new(Recorder::default());
+    first.add_callback(a.clone());
+    let removed = first.add_callback(b.clone());
+    second.add_callback(c.clone());
+    assert_eq!(first.callback_count(), 2);
+
+    first.submit(new_task("1a")).unwrap();
+    second.submit(new_task("2a")).unwrap();
+    wait_until(|| a.ids().len() == 1 && b.ids().len() == 1 && c.ids().len() == 1);
+    assert_eq!(a.ids(), vec!["1a"]);
+    assert_eq!(b.ids(), vec!["1a"]);
+    assert_eq!(c.ids(), vec!["2a"]);
+
+    assert!(first.remove_callback(removed));
+    assert!(!first.remove_callback(removed));
+    first.submit(new_task("1b")).unwrap();
+    // 队列释放时等待已提交任务的回调执行完毕
+    drop(first);
+    assert_eq!(a.ids(), vec!["1a", "1b"]);
+    assert_eq!(b.ids(), vec!["1a"]);
+    assert_eq!(c.ids(), vec!["2a"]);
+}
+
+#[test]
+fn test_async_callback_runs_on_runtime() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let queue = TaskQueue::new();
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    queue.add_async_callback(runtime.handle().clone(), move |response: Response| {
+        let sender = sender.clone();
+        async move {
+            tokio::task::yield_now().await;
+            sender.send(response.task_id).unwrap();
+            Ok(())
+        }
+    });
+    for i in 0..3 {
+        queue.submit(new_task(&format!("async-{}", i))).unwrap();
+    }
+
+    let mut ids = runtime.block_on(async {
+        let mut ids = Vec::new();
+        while ids.len() < 3 {
+            let id = tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap();
+            ids.push(id);
+        }
+        ids
+    });
+    ids.sort();
+    assert_eq!(ids, vec!["async-0", "async-1", "async-2"]);
+}
@@ -0,0 +1,110 @@
+//! 配置热加载测试
+
+use std::time::Duration;
+use zerg_pool::config::PoolConfig;
+use zerg_pool::{DronePool, Process};
+
+fn new_pool(config: PoolConfig) -> DronePool {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    DronePool::with_config("127.0.0.1", port, config).expect("Failed to create DronePool")
+}
+
+fn register(pool: &mut DronePool, count: usize) {
+    for i in 0..count {
+        let drone = Process::new(format!("worker{}", i), vec![], Some(10));
+        pool.register_drone(drone).expect("注册失败");
+    }
+}
+
+#[test]
+fn test_reload_resizes_main_pool_without_dropping_drones() {
+    let config = PoolConfig::builder().max_main_pool_size(2).build().unwrap();
+    let mut pool = new_pool(config.clone());
+    register(&mut pool, 4);
+    assert_eq!(pool.get_worker_count(), 2);
+    assert_eq!(pool.get_backup_count(), 2);
+
+    // 扩容：备用节点晋升
+    let bigger = PoolConfig { max_main_pool_size: 3, ..config.clone() };
+    let changes = pool.reload_config(bigger).unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "max_main_pool_size");
+    assert!(!changes[0].requires_restart());
+    assert_eq!(pool.get_worker_count(), 3);
+    assert_eq!(pool.get_backup_count(), 1);
+
+    // 缩容：主池节点降级
+    let smaller = PoolConfig { max_main_pool_size: 1, ..config };
+    pool.reload_config(smaller).unwrap();
+    assert_eq!(pool.get_worker_count(), 1);
+    assert_eq!(pool.get_backup_count(), 3);
+}
+
+#[test]
+fn test_engine_sizing_requires_restart() {
+    let mut pool = new_pool(PoolConfig::default());
+    let resized = PoolConfig { engine_workers: 1000, engine_queue_capacity: 7, ..PoolConfig::default() };
+
+    let changes = pool.reload_config(resized).unwrap();
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().all(|c| c.requires_restart()), "{:?}", changes);
+    assert_eq!(pool.config().engine_queue_capacity, PoolConfig::default().engine_queue_capacity);
+}
+
+#[test]
+fn test_bind_time_fields_keep_running_values() {
+    let mut pool = new_pool(PoolConfig::default());
+    let changed = PoolConfig {
+        journal_path: Some(std::env::temp_dir().join("zerg_pool_reload_other.journal")),
+        lease_path: Some(std::env::temp_dir().join("zerg_pool_reload_other.lease")),
+        lease_ttl_ms: 10_000,
+        queen_id: Some("other".to_string()),
+        transport: zerg_pool::transport::TransportKind::Native,
+        ipc_mode: Some(0o600),
+        metrics_addr: Some("127.0.0.1:9999".to_string()),
+        otlp_endpoint: Some("http://127.0.0.1:4318".to_string()),
+        max_main_pool_size: 3,
+        ..PoolConfig::default()
+    };
+
+    let changes = pool.reload_config(changed).unwrap();
+    assert_eq!(changes.len(), 9, "{:?}", changes);
+    let restart: Vec<&str> = changes.iter().filter(|c| c.requires_restart()).map(|c| c.field.as_str()).collect();
+    assert_eq!(restart.len(), 8, "{:?}", restart);
+    // 只有可热加载的字段生效，配置导出反映实际运行的取值
+    assert_eq!(pool.config(), &PoolConfig { max_main_pool_size: 3, ..PoolConfig::default() });
+}
+
+#[test]
+fn test_reload_rejects_invalid_config() {
+    let mut pool = new_pool(PoolConfig::default());
+    let invalid = PoolConfig { max_load_threshold: 2.0, ..PoolConfig::default() };
+
+    assert!(pool.reload_config(invalid).is_err());
+    assert_eq!(pool.config().max_load_threshold, 0.8);
+}
+
+#[test]
+fn test_watch_config_file() {
+    let path = std::env::temp_dir().join(format!("zerg_pool_reload_{}.toml", std::process::id()));
+    std::fs::write(&path, "max_main_pool_size = 10\n").unwrap();
+
+    let mut pool = new_pool(PoolConfig::default());
+    pool.watch_config(&path, Duration::from_millis(20));
+
+    std::thread::sleep(Duration::from_millis(50));
+    std::fs::write(&path, "max_main_pool_size = 5\nmax_load_threshold = 0.6\n").unwrap();
+
+    let mut applied = false;
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if pool.config().max_main_pool_size == 5 {
+            applied = true;
+            break;
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+
+    assert!(applied, "配置文件变更未被应用");
+    assert_eq!(pool.config().max_load_threshold, 0.6);
+}
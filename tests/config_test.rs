@@ -0,0 +1,61 @@
+//! 运行时配置测试
+
+use std::time::Duration;
+use zerg_pool::config::{ConfigError, DroneConfig, PoolConfig};
+
+#[test]
+fn test_pool_config_builder() {
+    let config = PoolConfig::builder()
+        .max_main_pool_size(20)
+        .max_load_threshold(0.7)
+        .warmup_duration(Duration::from_secs(2))
+        .build()
+        .expect("配置应有效");
+
+    assert_eq!(config.max_main_pool_size, 20);
+    assert_eq!(config.max_load_threshold, 0.7);
+    assert_eq!(config.warmup_duration(), Duration::from_secs(2));
+    // 未设置的字段保持默认值
+    assert_eq!(config.heartbeat_timeout(), Duration::from_secs(9));
+}
+
+#[test]
+fn test_pool_config_validation() {
+    let err = PoolConfig::builder().max_main_pool_size(0).build().unwrap_err();
+    assert!(matches!(err, ConfigError::Invalid { field: "max_main_pool_size", .. }));
+
+    let err = PoolConfig::builder().max_load_threshold(1.5).build().unwrap_err();
+    assert!(matches!(err, ConfigError::Invalid { field: "max_load_threshold", .. }));
+}
+
+#[test]
+fn test_load_from_toml_and_json() {
+    let config = PoolConfig::from_toml_str(
+        r#"
+        max_main_pool_size = 4
+        cpu_overload_threshold = 0.75
+        "#,
+    ).unwrap();
+    assert_eq!(config.max_main_pool_size, 4);
+    assert_eq!(config.cpu_overload_threshold, 0.75);
+
+    let drone = DroneConfig::from_json_str(r#"{"queue_capacity": 64, "heartbeat_interval_ms": 500}"#).unwrap();
+    assert_eq!(drone.queue_capacity, 64);
+    assert_eq!(drone.heartbeat_interval(), Duration::from_millis(500));
+
+    // 超时必须大于心跳间隔
+    assert!(DroneConfig::from_json_str(r#"{"heartbeat_timeout_ms": 100}"#).is_err());
+}
+
+#[test]
+fn test_env_overrides() {
+    std::env::set_var("ZERG_POOL_MAX_MAIN_POOL_SIZE", "32");
+    let config = PoolConfig::from_env().unwrap();
+    std::env::remove_var("ZERG_POOL_MAX_MAIN_POOL_SIZE");
+    assert_eq!(config.max_main_pool_size, 32);
+
+    std::env::set_var("ZERG_DRONE_MAX_TASKS", "many");
+    let result = DroneConfig::from_env();
+    std::env::remove_var("ZERG_DRONE_MAX_TASKS");
+    assert!(matches!(result, Err(ConfigError::InvalidEnv { .. })));
+}
@@ -0,0 +1,133 @@
+//! CURVE安全测试：密钥文件读写、配置校验，以及queen拒绝未认证的drone
+//!
+//! 握手测试需要libzmq启用CURVE(链接libsodium)，未启用时只验证会明确报错
+
+use std::path::PathBuf;
+use std::time::Duration;
+use zerg_pool::config::{CurveClientConfig, CurveServerConfig, DroneConfig, PoolConfig};
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::transport::curve::{self, CurveClient, CurveKeyPair};
+use zerg_pool::transport::{TransportError, TransportKind};
+use zerg_pool::DronePool;
+
+/// libzmq文档(zmq_curve)中的示例密钥
+const SERVER_PUBLIC: &str = "rq:rM>}U?@Lns47E1%kR.o@n%FcmmsL/@{H8]yf7";
+const SERVER_SECRET: &str = "JTKVSB%%)wK0E.X)V>+}o?pNmC{O&4W4b!Ni{Lh6";
+const CLIENT_PUBLIC: &str = "Yne@$w-vo<fVvi]a<NY6T1ed:M$fCG*[IaLV{hID";
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zerg_pool_curve_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_key_files() {
+    let dir = temp_dir("files");
+    let keypair = CurveKeyPair { public_key: SERVER_PUBLIC.to_string(), secret_key: SERVER_SECRET.to_string() };
+    keypair.save(dir.join("queen.key")).unwrap();
+    keypair.save_public(dir.join("queen.key.pub")).unwrap();
+    assert_eq!(CurveKeyPair::load(dir.join("queen.key")).unwrap(), keypair);
+    assert_eq!(curve::load_public_key(dir.join("queen.key.pub")).unwrap(), SERVER_PUBLIC);
+    // 密钥文件也可作为公钥文件使用
+    assert_eq!(curve::load_public_key(dir.join("queen.key")).unwrap(), SERVER_PUBLIC);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(dir.join("queen.key")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // 允许列表：目录中的公钥文件与逐行书写的公钥
+    let authorized = dir.join("authorized");
+    std::fs::create_dir(&authorized).unwrap();
+    keypair.save_public(authorized.join("a.pub")).unwrap();
+    std::fs::write(authorized.join("b.keys"), format!("# drone b\n{}\n\n", CLIENT_PUBLIC)).unwrap();
+    let keys = curve::load_authorized_keys(&authorized).unwrap();
+    assert_eq!(keys.len(), 2);
+    assert!(keys.contains(&curve::decode_key(CLIENT_PUBLIC).unwrap()));
+    assert_eq!(curve::encode_key(&curve::decode_key(SERVER_PUBLIC).unwrap()), SERVER_PUBLIC);
+
+    for invalid in ["", "short", "rq:rM>}U?@Lns47E1%kR.o@n%FcmmsL/@{H8]yf~", &format!("{}x", SERVER_PUBLIC)] {
+        assert!(matches!(curve::decode_key(invalid), Err(TransportError::InvalidKey(_))), "{}", invalid);
+    }
+    std::fs::write(authorized.join("c.keys"), "# empty\n").unwrap();
+    assert!(curve::load_authorized_keys(&authorized).is_err());
+    std::fs::write(dir.join("bad.key"), "public_key = \"bad\"\nsecret_key = \"bad\"\n").unwrap();
+    assert!(CurveKeyPair::load(dir.join("bad.key")).is_err());
+
+    // 只有zmq传输支持CURVE
+    let server = CurveServerConfig { key_file: dir.join("queen.key"), authorized_keys: Some(authorized) };
+    assert!(PoolConfig::builder().curve(server.clone()).build().is_ok());
+    assert!(PoolConfig::builder().curve(server).transport(TransportKind::Native).build().is_err());
+    let client = CurveClientConfig { key_file: dir.join("queen.key"), server_key_file: dir.join("queen.key.pub") };
+    assert!(DroneConfig::builder().curve(client.clone()).transport(TransportKind::Inproc).build().is_err());
+    let client = CurveClient::from_config(&client).unwrap();
+    assert_eq!(client.server_key, SERVER_PUBLIC);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_curve_rejects_unauthenticated_drones() {
+    let dir = temp_dir("handshake");
+    if !curve::is_available() {
+        assert!(matches!(CurveKeyPair::generate(), Err(TransportError::CurveUnavailable)));
+        let keypair = CurveKeyPair { public_key: SERVER_PUBLIC.to_string(), secret_key: SERVER_SECRET.to_string() };
+        keypair.save(dir.join("queen.key")).unwrap();
+        let config = PoolConfig::builder()
+            .curve(CurveServerConfig { key_file: dir.join("queen.key"), authorized_keys: None })
+            .build()
+            .unwrap();
+        assert!(DronePool::bind("tcp://127.0.0.1:*", config).is_err());
+        eprintln!("libzmq未启用CURVE，跳过握手测试");
+        return;
+    }
+
+    let queen = CurveKeyPair::generate().unwrap();
+    let trusted = CurveKeyPair::generate().unwrap();
+    let stranger = CurveKeyPair::generate().unwrap();
+    queen.save(dir.join("queen.key")).unwrap();
+    queen.save_public(dir.join("queen.key.pub")).unwrap();
+    trusted.save(dir.join("trusted.key")).unwrap();
+    trusted.save_public(dir.join("trusted.key.pub")).unwrap();
+    stranger.save(dir.join("stranger.key")).unwrap();
+
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let endpoint = format!("tcp://127.0.0.1:{}", port);
+    let config = PoolConfig::builder()
+        .curve(CurveServerConfig {
+            key_file: dir.join("queen.key"),
+            authorized_keys: Some(dir.join("trusted.key.pub")),
+        })
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(&endpoint, config).unwrap();
+
+    let connect = |id: &str, key_file: Option<&str>| {
+        let config = match key_file {
+            Some(key_file) => DroneConfig::builder().curve(CurveClientConfig {
+                key_file: dir.join(key_file),
+                server_key_file: dir.join("queen.key.pub"),
+            }),
+            None => DroneConfig::builder(),
+        };
+        let mut drone = DroneNetwork::connect_config(&config.build().unwrap(), id, vec![endpoint.clone()]).unwrap();
+        drone.set_heartbeat_interval(Duration::ZERO);
+        drone.register(id, vec![]).unwrap();
+        drone
+    };
+    let _trusted = connect("trusted", Some("trusted.key"));
+    let stranger = connect("stranger", Some("stranger.key"));
+    let plaintext = connect("plaintext", None);
+
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+    }
+    let workers: Vec<String> = pool.workers().iter().map(|w| w.id.clone()).collect();
+    assert_eq!(workers, vec!["trusted".to_string()]);
+    // 被拒绝的连接中仍有未送达的注册消息，按ZMQ默认linger释放时会一直等待
+    std::mem::forget(stranger);
+    std::mem::forget(plaintext);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,122 @@
+//! Drone运行时集成测试
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use zerg_pool::config::{DroneConfig, PoolConfig, RetryPolicy};
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::{Drone, DroneError, DronePool};
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: id.as_bytes().to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+#[test]
+fn test_builder_requires_executor() {
+    let result = Drone::builder("tcp://127.0.0.1:1").build();
+    assert!(matches!(result, Err(DroneError::MissingExecutor)));
+}
+
+#[test]
+fn test_drone_executes_tasks_and_reports_load() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let mut pool = DronePool::new("127.0.0.1", port).expect("Failed to create DronePool");
+
+    let config = DroneConfig::builder()
+        .worker_id("runtime-1")
+        .heartbeat_interval(Duration::from_millis(50))
+        .heartbeat_timeout(Duration::from_millis(1000))
+        .worker_threads(2)
+        .build()
+        .unwrap();
+    let drone = Drone::builder(format!("tcp://127.0.0.1:{}", port))
+        .config(config)
+        .executor(|task: &Task| {
+            thread::sleep(Duration::from_millis(300));
+            Ok(task.payload.clone())
+        })
+        .build()
+        .unwrap();
+    assert_eq!(drone.worker_id(), "runtime-1");
+    let handle = drone.handle();
+    let runner = thread::spawn(move || drone.run());
+
+    for _ in 0..30 {
+        pool.poll_events().unwrap();
+        if pool.get_worker_count() == 1 {
+            break;
+        }
+    }
+    assert_eq!(pool.get_worker_count(), 1);
+
+    pool.submit_task(new_task("t1")).unwrap();
+    pool.submit_task(new_task("t2")).unwrap();
+
+    // 执行期间心跳上报当前任务数
+    let mut reported_busy = false;
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        let metrics = pool.get_worker_metrics(&"runtime-1".to_string()).unwrap();
+        if handle.active_tasks() == 2 && metrics.current_tasks == 2 {
+            reported_busy = true;
+        }
+        if pool.in_flight_task_count() == 0 && pool.pending_task_count() == 0 {
+            break;
+        }
+    }
+    assert!(reported_busy);
+    assert_eq!(pool.in_flight_task_count(), 0);
+    assert_eq!(pool.pending_task_count(), 0);
+
+    handle.stop();
+    runner.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_executor_panic_reported_as_error() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder()
+        .retry(RetryPolicy { max_retries: 0, ..Default::default() })
+        .build()
+        .unwrap();
+    let mut pool = DronePool::with_config("127.0.0.1", port, config).expect("Failed to create DronePool");
+
+    let drone = Drone::builder(format!("tcp://127.0.0.1:{}", port))
+        .config(DroneConfig::builder().worker_id("runtime-panic").build().unwrap())
+        .executor(|task: &Task| {
+            if task.id == "boom" {
+                panic!("boom");
+            }
+            Ok(task.payload.clone())
+        })
+        .build()
+        .unwrap();
+    let handle = drone.handle();
+    let runner = thread::spawn(move || drone.run());
+
+    pool.submit_task(new_task("boom")).unwrap();
+    pool.submit_task(new_task("ok")).unwrap();
+    for _ in 0..100 {
+        pool.poll_events().unwrap();
+        if pool.in_flight_task_count() == 0 && pool.pending_task_count() == 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(pool.in_flight_task_count(), 0);
+    let dead = pool.dead_letters();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].task.id, "boom");
+    assert!(dead[0].error.contains("boom"), "{}", dead[0].error);
+
+    // panic的任务不会遗留在执行中任务数里，停止时不会一直等待
+    assert_eq!(handle.active_tasks(), 0);
+    handle.stop();
+    runner.join().unwrap().unwrap();
+}
@@ -0,0 +1,140 @@
+//! Queen事件循环测试
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::proto::zergpool::{Registration, Task};
+use zerg_pool::queen::PoolStats;
+use zerg_pool::{Drone, DronePool, PoolError, ProcessMessage};
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: id.as_bytes().to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn wait_for(mut check: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if check() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn test_spawned_pool_runs_tasks_from_other_threads() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let handle = DronePool::new("127.0.0.1", port).expect("Failed to create DronePool").spawn();
+
+    let config = DroneConfig::builder()
+        .worker_id("loop-drone")
+        .heartbeat_interval(Duration::from_millis(50))
+        .heartbeat_timeout(Duration::from_millis(1000))
+        .build()
+        .unwrap();
+    let drone = Drone::builder(format!("tcp://127.0.0.1:{}", port))
+        .config(config)
+        .executor(|task: &Task| Ok(task.payload.clone()))
+        .build()
+        .unwrap();
+    let drone_handle = drone.handle();
+    let runner = thread::spawn(move || drone.run());
+
+    assert!(wait_for(|| handle.stats().unwrap().workers == 1));
+    let submitters: Vec<_> = (0..4).map(|i| {
+        let sender = handle.sender();
+        thread::spawn(move || {
+            let (reply, result) = tokio::sync::oneshot::channel();
+            sender.send(zerg_pool::queen::PoolCommand::Submit(new_task(&format!("t{}", i)), reply)).unwrap();
+            result.blocking_recv().unwrap()
+        })
+    }).collect();
+    for submitter in submitters {
+        submitter.join().unwrap().unwrap();
+    }
+
+    assert!(wait_for(|| {
+        let stats = handle.stats().unwrap();
+        stats.pending_tasks == 0 && stats.in_flight_tasks == 0
+    }));
+    assert_eq!(handle.stats().unwrap(), PoolStats {
+        workers: 1,
+        backups: 0,
+        pending_tasks: 0,
+        in_flight_tasks: 0,
+        is_leader: true,
+    });
+
+    let sender = handle.sender();
+    handle.shutdown().unwrap();
+    let (reply, _result) = tokio::sync::oneshot::channel();
+    assert!(sender.send(zerg_pool::queen::PoolCommand::Stats(reply)).is_err());
+
+    drone_handle.stop();
+    runner.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_reaper_removes_silent_worker_and_requeues_tasks() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder()
+        .check_interval(Duration::from_millis(50))
+        .heartbeat_timeout(Duration::from_millis(100))
+        .circuit_breaker_threshold(2)
+        .build()
+        .unwrap();
+    let handle = DronePool::with_config("127.0.0.1", port, config).unwrap().spawn();
+
+    // 注册后不再发送心跳的drone
+    let ctx = zmq::Context::new();
+    let drone = ctx.socket(zmq::DEALER).unwrap();
+    drone.set_identity(b"silent").unwrap();
+    drone.connect(&format!("tcp://127.0.0.1:{}", port)).unwrap();
+    let reg = ProcessMessage::Registration(Registration {
+        worker_id: "silent".into(),
+        max_threads: 4,
+        version: env!("CARGO_PKG_VERSION").into(),
+        capabilities: vec![],
+        token: String::new(),
+        protocol_version: zerg_pool::version::PROTOCOL_VERSION.into(),
+    });
+    drone.send_multipart(["".as_bytes(), "".as_bytes(), &reg.encode_envelope()], 0).unwrap();
+    assert!(wait_for(|| handle.stats().unwrap().workers == 1));
+
+    handle.submit(new_task("orphan")).unwrap();
+    assert!(wait_for(|| handle.stats().unwrap().workers == 0));
+    let stats = handle.stats().unwrap();
+    assert_eq!(stats.pending_tasks, 1);
+    assert_eq!(stats.in_flight_tasks, 0);
+
+    handle.shutdown().unwrap();
+}
+
+#[tokio::test]
+async fn test_handle_in_runtime_does_not_block() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let handle = DronePool::new("127.0.0.1", port).expect("Failed to create DronePool").spawn();
+    assert!(matches!(handle.stats(), Err(PoolError::BlockingInRuntime)));
+    assert!(matches!(handle.submit(new_task("t1")), Err(PoolError::BlockingInRuntime)));
+
+    // 在运行时中释放句柄只通知事件循环停止，事件循环随后在后台退出
+    let sender = handle.sender();
+    drop(handle);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let (reply, _result) = tokio::sync::oneshot::channel();
+        if sender.send(zerg_pool::queen::PoolCommand::Stats(reply)).is_err() {
+            break;
+        }
+        assert!(Instant::now() < deadline, "事件循环未退出");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
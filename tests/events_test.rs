@@ -0,0 +1,147 @@
+//! 生命周期事件测试：订阅者按顺序收到节点与任务的状态变化
+
+use std::collections::HashMap;
+use std::time::Duration;
+use crossbeam_channel::Receiver;
+use zerg_pool::config::{PoolConfig, RetryPolicy};
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::proto::zergpool::{response, HealthState, Response, Task};
+use zerg_pool::queen::events::{PoolEvent, RemovalReason, WorkerRole, EVENT_CHANNEL_CAPACITY};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{DronePool, ProcessMessage};
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: vec![],
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn drain(events: &Receiver<PoolEvent>) -> Vec<PoolEvent> {
+    events.try_iter().collect()
+}
+
+#[test]
+fn test_subscriber_receives_lifecycle_events() {
+    let endpoint = "inproc://events_lifecycle";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .max_main_pool_size(1)
+        .heartbeat_timeout(Duration::from_millis(50))
+        .circuit_breaker_threshold(1)
+        .retry(RetryPolicy { max_retries: 1, backoff_ms: 1, max_backoff_ms: 1 })
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let events = pool.subscribe();
+    // 丢弃的订阅者自动移除，不影响其他订阅者
+    drop(pool.subscribe());
+
+    let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, "events-a", vec![endpoint.to_string()]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register("events-a", vec!["gpu".to_string()]).unwrap();
+    for _ in 0..20 {
+        pool.poll_events().unwrap();
+        if pool.get_worker_count() == 1 {
+            break;
+        }
+    }
+    pool.submit_task(new_task("flaky")).unwrap();
+
+    // 第一次失败后重试成功
+    let mut attempts = 0;
+    for _ in 0..100 {
+        pool.poll_events().unwrap();
+        if let Some(ProcessMessage::Task(task)) = drone.poll_message(5).unwrap() {
+            attempts += 1;
+            let result = if attempts == 1 {
+                response::Result::Error("boom".to_string())
+            } else {
+                response::Result::Output(vec![])
+            };
+            drone.send_response(&Response { worker_id: "events-a".to_string(), task_id: task.id, result: Some(result) }).unwrap();
+        }
+        if attempts == 2 && pool.in_flight_task_count() == 0 {
+            break;
+        }
+    }
+    assert_eq!(pool.in_flight_task_count(), 0);
+
+    let received = drain(&events);
+    let expected = [
+        PoolEvent::WorkerRegistered { worker_id: "events-a".to_string(), role: WorkerRole::Main, capabilities: vec!["gpu".to_string()] },
+        PoolEvent::TaskQueued { task_id: "flaky".to_string() },
+        PoolEvent::TaskDispatched { task_id: "flaky".to_string(), worker_id: "events-a".to_string(), attempt: 0 },
+        PoolEvent::TaskRetried { task_id: "flaky".to_string(), worker_id: "events-a".to_string(), attempt: 1, error: "boom".to_string() },
+        PoolEvent::TaskDispatched { task_id: "flaky".to_string(), worker_id: "events-a".to_string(), attempt: 1 },
+    ];
+    assert_eq!(&received[..expected.len()], &expected, "{:#?}", received);
+    assert!(matches!(
+        &received[expected.len()..],
+        [PoolEvent::TaskCompleted { task_id, worker_id, .. }] if task_id == "flaky" && worker_id == "events-a"
+    ), "{:#?}", received);
+
+    // 过载心跳触发健康状态变化
+    pool.update_worker_metrics(&"events-a".to_string(), 0.99, 0.1, 10, 0);
+    assert_eq!(drain(&events), vec![PoolEvent::WorkerHealthChanged {
+        worker_id: "events-a".to_string(),
+        from: HealthState::Healthy,
+        to: HealthState::Unhealthy,
+    }]);
+
+    // 在途任务随节点心跳超时重新入队，备用节点补位
+    pool.submit_task(new_task("orphan")).unwrap();
+    pool.update_worker_metrics(&"events-a".to_string(), 0.1, 0.1, 10, 0);
+    pool.poll_events().unwrap();
+    assert_eq!(pool.in_flight_task_count(), 1);
+    let mut backup = DroneNetwork::connect_with(TransportKind::Inproc, "events-b", vec![endpoint.to_string()]).unwrap();
+    backup.set_heartbeat_interval(Duration::ZERO);
+    backup.register("events-b", vec![]).unwrap();
+    for _ in 0..20 {
+        pool.poll_events().unwrap();
+        if pool.get_backup_count() == 1 {
+            break;
+        }
+    }
+    assert!(drain(&events).contains(&PoolEvent::WorkerRegistered {
+        worker_id: "events-b".to_string(),
+        role: WorkerRole::Backup,
+        capabilities: vec![],
+    }));
+    std::thread::sleep(Duration::from_millis(120));
+    pool.update_worker_metrics(&"events-b".to_string(), 0.1, 0.1, 10, 0);
+    pool.reap_stale_workers();
+    let received = drain(&events);
+    for event in [
+        PoolEvent::TaskRequeued { task_id: "orphan".to_string(), worker_id: "events-a".to_string() },
+        PoolEvent::WorkerRemoved { worker_id: "events-a".to_string(), reason: RemovalReason::HeartbeatTimeout },
+        PoolEvent::ScaledOut { worker_id: "events-b".to_string() },
+    ] {
+        assert!(received.contains(&event), "缺少 {:?}: {:#?}", event, received);
+    }
+    assert!(received.iter().any(|e| matches!(e, PoolEvent::WorkerHealthChanged { worker_id, to: HealthState::CircuitBreaker, .. } if worker_id == "events-a")));
+
+    let json = serde_json::to_value(&received[0]).unwrap();
+    assert!(json["event"].is_string(), "{}", json);
+}
+
+#[test]
+fn test_slow_subscriber_drops_overflow() {
+    let config = PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap();
+    let mut pool = DronePool::bind("inproc://events_overflow", config).unwrap();
+    let slow = pool.subscribe();
+    for i in 0..EVENT_CHANNEL_CAPACITY + 10 {
+        pool.submit_task(new_task(&format!("t{}", i))).unwrap();
+    }
+    // 通道已满后的事件被丢弃，提交不受影响
+    let received = drain(&slow);
+    assert_eq!(received.len(), EVENT_CHANNEL_CAPACITY);
+    assert_eq!(received[0], PoolEvent::TaskQueued { task_id: "t0".to_string() });
+    assert_eq!(pool.pending_task_count(), EVENT_CHANNEL_CAPACITY + 10);
+
+    pool.submit_task(new_task("after")).unwrap();
+    assert_eq!(drain(&slow), vec![PoolEvent::TaskQueued { task_id: "after".to_string() }]);
+}
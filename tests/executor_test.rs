@@ -0,0 +1,41 @@
+//! 内置执行器测试
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use zerg_pool::drone::executor::{self, CommandExecutor, EchoExecutor};
+use zerg_pool::drone::TaskExecutor;
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::DroneError;
+
+fn task(payload: &[u8]) -> Task {
+    Task {
+        id: "exec-1".to_string(),
+        payload: payload.to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+#[test]
+fn test_echo_and_command_executors() {
+    assert_eq!(EchoExecutor.execute(&task(b"abc")).unwrap(), b"abc");
+
+    let upper = CommandExecutor::new("sh", vec!["-c".to_string(), "printf \"$ZERG_TASK_ID:\"; tr a-z A-Z".to_string()]);
+    assert_eq!(upper.execute(&task(b"abc")).unwrap(), b"exec-1:ABC");
+
+    let failing = CommandExecutor::new("sh", vec!["-c".to_string(), "echo boom >&2; exit 3".to_string()]);
+    let err = failing.execute(&task(b"")).unwrap_err();
+    assert!(err.contains("boom"), "{}", err);
+}
+
+#[test]
+fn test_builtin_lookup() {
+    let echo = executor::builtin("echo", &[]).unwrap();
+    assert_eq!(echo.execute(&task(b"x")).unwrap(), b"x");
+    let cat = executor::builtin("command", &["cat".to_string()]).unwrap();
+    assert_eq!(cat.execute(&task(b"y")).unwrap(), b"y");
+
+    assert!(matches!(executor::builtin("command", &[]), Err(DroneError::InvalidExecutor(_))));
+    assert!(matches!(executor::builtin("wasm", &[]), Err(DroneError::InvalidExecutor(_))));
+}
@@ -0,0 +1,104 @@
+//! Prometheus指标导出测试
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+use zerg_pool::config::PoolConfig;
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::exporter::{self, PrometheusRecorder};
+use zerg_pool::proto::zergpool::{response, Response, Task};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{DronePool, ProcessMessage};
+
+fn get(addr: SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn test_recorder_renders_prometheus_text() {
+    let recorder = PrometheusRecorder::new();
+    metrics::with_local_recorder(&recorder, || {
+        exporter::describe_metrics();
+        metrics::counter!("zergpool.selections").increment(3);
+        metrics::counter!("zergpool.selections").absolute(2);
+        metrics::gauge!("zergpool.worker.cpu_usage", "worker" => "w1").set(0.5);
+        metrics::gauge!("zergpool.worker.cpu_usage", "worker" => "w2").increment(0.25);
+        metrics::histogram!("zergpool.task.latency_seconds", "outcome" => "success").record(0.003);
+        // 标签与已注册的不一致时忽略，不影响已有指标
+        metrics::gauge!("zergpool.worker.cpu_usage").set(1.0);
+        metrics::counter!("zergpool.worker.cpu_usage", "worker" => "w1").increment(1);
+    });
+
+    let body = recorder.handle().render();
+    assert!(body.contains("# HELP zergpool_selections_total 负载均衡选择次数\n"), "{}", body);
+    assert!(body.contains("# TYPE zergpool_selections_total counter\nzergpool_selections_total 3\n"), "{}", body);
+    assert!(body.contains("zergpool_worker_cpu_usage{worker=\"w1\"} 0.5\n"), "{}", body);
+    assert!(body.contains("zergpool_worker_cpu_usage{worker=\"w2\"} 0.25\n"), "{}", body);
+    assert!(body.contains("zergpool_task_latency_seconds_bucket{outcome=\"success\",le=\"0.004\"} 1\n"), "{}", body);
+    assert!(body.contains("zergpool_task_latency_seconds_count{outcome=\"success\"} 1\n"), "{}", body);
+    assert!(!body.contains("zergpool_worker_cpu_usage 1"));
+}
+
+#[test]
+fn test_pool_serves_worker_metrics() {
+    let endpoint = "inproc://exporter";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .metrics_addr("127.0.0.1:0")
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    let addr = pool.metrics_addr().expect("应启动指标服务");
+    // 已安装时复用同一记录器
+    exporter::install_recorder().unwrap();
+
+    let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, "metrics-1", vec![endpoint.to_string()]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register("metrics-1", vec![]).unwrap();
+    pool.submit_task(Task {
+        id: "m1".to_string(),
+        payload: vec![],
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }).unwrap();
+    let mut task = None;
+    for _ in 0..50 {
+        drone.send_heartbeat().unwrap();
+        pool.poll_events().unwrap();
+        if let Some(ProcessMessage::Task(t)) = drone.poll_message(10).unwrap() {
+            task = Some(t);
+            break;
+        }
+    }
+    drone.send_response(&Response {
+        worker_id: "metrics-1".to_string(),
+        task_id: task.expect("任务应派发给drone").id,
+        result: Some(response::Result::Output(vec![])),
+    }).unwrap();
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if pool.in_flight_task_count() == 0 {
+            break;
+        }
+    }
+
+    let response = get(addr, "/metrics");
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+    for expected in [
+        "zergpool_workers 1\n",
+        "zergpool_in_flight_tasks 0\n",
+        "zergpool_worker_up{worker=\"metrics-1\"} 1\n",
+        "zergpool_worker_max_tasks{worker=\"metrics-1\"}",
+        "zergpool_worker_health{worker=\"metrics-1\"}",
+        "zergpool_task_latency_seconds_count{outcome=\"success\"} 1\n",
+    ] {
+        assert!(response.contains(expected), "缺少 {}:\n{}", expected, response);
+    }
+    assert!(get(addr, "/").starts_with("HTTP/1.1 404"));
+}
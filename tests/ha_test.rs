@@ -0,0 +1,155 @@
+//! Queen高可用选主与故障转移测试
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use zerg_pool::config::PoolConfig;
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::queen::election::{LeaderElection, LeadershipChange};
+use zerg_pool::{DronePool, PoolError};
+
+const TTL: Duration = Duration::from_millis(400);
+
+fn temp_file(name: &str, ext: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zerg_pool_ha_{}_{}.{}", name, std::process::id(), ext));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: id.as_bytes().to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn open_queen(id: &str, lease: &PathBuf, journal: &PathBuf) -> (DronePool, String) {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder()
+        .lease(lease, TTL)
+        .queen_id(id)
+        .journal_path(journal)
+        .build()
+        .unwrap();
+    let pool = DronePool::with_config("127.0.0.1", port, config).expect("Failed to create DronePool");
+    (pool, format!("tcp://127.0.0.1:{}", port))
+}
+
+#[test]
+fn test_lease_acquire_and_takeover() {
+    let lease = temp_file("election", "lease");
+    let mut a = LeaderElection::new(&lease, "a", "tcp://127.0.0.1:1", TTL);
+    let mut b = LeaderElection::new(&lease, "b", "tcp://127.0.0.1:2", TTL);
+
+    assert_eq!(a.tick().unwrap(), LeadershipChange::Acquired);
+    assert_eq!(b.tick().unwrap(), LeadershipChange::Unchanged);
+    assert!(a.is_leader());
+    assert!(!b.is_leader());
+    assert_eq!(b.current_lease().unwrap().unwrap().endpoint, "tcp://127.0.0.1:1");
+
+    // a停止续约，租约过期后b接管且任期递增
+    std::thread::sleep(TTL + Duration::from_millis(100));
+    assert_eq!(b.tick().unwrap(), LeadershipChange::Acquired);
+    assert_eq!(b.term(), 2);
+    assert_eq!(a.tick().unwrap(), LeadershipChange::Lost);
+    assert!(!a.is_leader());
+
+    // 正常释放后可立即被接管
+    b.release().unwrap();
+    std::thread::sleep(TTL / 3);
+    assert_eq!(a.tick().unwrap(), LeadershipChange::Acquired);
+    assert_eq!(a.term(), 3);
+    let _ = std::fs::remove_file(&lease);
+}
+
+#[test]
+fn test_leadership_lapses_before_lease_expires() {
+    let lease = temp_file("margin", "lease");
+    let mut a = LeaderElection::new(&lease, "a", "tcp://127.0.0.1:1", TTL);
+    assert_eq!(a.tick().unwrap(), LeadershipChange::Acquired);
+
+    // 未续约时在租约到期前就停止行使leader职责
+    std::thread::sleep(TTL * 3 / 4 + Duration::from_millis(20));
+    assert!(!a.current_lease().unwrap().unwrap().is_expired());
+    assert!(!a.is_leader());
+
+    // 租约文件被其他queen锁定、无法续约时不沿用缓存的leader身份
+    std::fs::write(lease.with_extension("lock"), b"").unwrap();
+    assert_eq!(a.tick().unwrap(), LeadershipChange::Lost);
+    let _ = std::fs::remove_file(lease.with_extension("lock"));
+    let _ = std::fs::remove_file(&lease);
+}
+
+#[test]
+fn test_stale_leader_stops_journaling() {
+    let lease = temp_file("stale", "lease");
+    let journal = temp_file("stale", "journal");
+    let (mut queen, _) = open_queen("queen-a", &lease, &journal);
+    assert!(queen.is_leader());
+    queen.submit_task(new_task("t1")).unwrap();
+
+    std::thread::sleep(TTL * 3 / 4 + Duration::from_millis(20));
+    assert!(matches!(queen.submit_task(new_task("t2")), Err(PoolError::NotLeader(_))));
+    assert_eq!(queen.pending_task_count(), 1);
+    let lines = std::fs::read_to_string(&journal).unwrap().lines().count();
+    assert_eq!(lines, 1);
+
+    let _ = std::fs::remove_file(&lease);
+    let _ = std::fs::remove_file(&journal);
+}
+
+#[test]
+fn test_standby_redirects_drone_and_takes_over_tasks() {
+    let lease = temp_file("failover", "lease");
+    let journal = temp_file("failover", "journal");
+    let (mut leader, leader_ep) = open_queen("queen-a", &lease, &journal);
+    let (mut standby, standby_ep) = open_queen("queen-b", &lease, &journal);
+    assert!(leader.is_leader());
+    assert!(!standby.is_leader());
+    assert!(matches!(standby.submit_task(new_task("t0")), Err(PoolError::NotLeader(ep)) if ep == leader_ep));
+
+    leader.submit_task(new_task("t1")).unwrap();
+
+    // drone先连到备用queen，被引导切换到leader
+    let mut drone = DroneNetwork::connect_as("w1", vec![standby_ep.clone(), leader_ep.clone()]).unwrap();
+    drone.register("w1", vec![]).unwrap();
+    for _ in 0..20 {
+        standby.poll_events().unwrap();
+        leader.poll_events().unwrap();
+        drone.poll_message(50).unwrap();
+        if drone.current_endpoint() == leader_ep {
+            break;
+        }
+    }
+    assert_eq!(drone.current_endpoint(), leader_ep);
+    assert_eq!(standby.get_worker_count(), 0);
+
+    for _ in 0..50 {
+        leader.poll_events().unwrap();
+        if leader.in_flight_task_count() == 1 {
+            break;
+        }
+    }
+    assert_eq!(leader.get_worker_count(), 1);
+    assert_eq!(leader.in_flight_task_count(), 1);
+
+    // leader崩溃(不释放租约)，备用queen在租约过期后接管未完成任务
+    drop(leader);
+    std::thread::sleep(TTL + Duration::from_millis(100));
+    for _ in 0..10 {
+        standby.poll_events().unwrap();
+        if standby.is_leader() {
+            break;
+        }
+    }
+    assert!(standby.is_leader());
+    assert_eq!(standby.pending_task_count(), 1);
+    assert_eq!(standby.leader_endpoint().as_deref(), Some(standby_ep.as_str()));
+
+    let _ = std::fs::remove_file(&lease);
+    let _ = std::fs::remove_file(&journal);
+}
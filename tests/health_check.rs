@@ -10,17 +10,19 @@ fn test_basic_message_flow() -> Result<(), Box<dyn std::error::Error>> {
     // 创建ZMQ上下文和ROUTER socket
     let ctx = Context::new();
     let router = ctx.socket(SocketType::ROUTER)?;
-    router.bind("tcp://127.0.0.1:5555")?;
+    // 绑定系统分配的端口，再读回实际地址
+    router.bind("tcp://127.0.0.1:*")?;
+    let endpoint = router.get_last_endpoint()?.expect("绑定地址应为UTF-8");
 
     // 创建mio的Poll实例
     let mut poll = Poll::new()?;
     let mut events = Events::with_capacity(128);
 
     // 手动轮询模式（因Windows下直接集成限制）
-    let client_thread = std::thread::spawn(|| {
+    let client_thread = std::thread::spawn(move || {
         let ctx = Context::new();
         let client = ctx.socket(SocketType::DEALER).unwrap();
-        client.connect("tcp://127.0.0.1:5555").unwrap();
+        client.connect(&endpoint).unwrap();
         
         client.send("Hello from client", 0).unwrap();
         println!("[Client] Sent request");
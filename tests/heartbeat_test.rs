@@ -0,0 +1,43 @@
+//! 独立心跳管理器测试：在同一连接上注册后，queen接受其心跳
+
+use std::time::Duration;
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::proto::zergpool::HealthState;
+use zerg_pool::queen::admin::{AdminRequest, AdminResponse};
+use zerg_pool::{DronePool, HeartbeatManager};
+
+#[test]
+fn test_heartbeat_manager_keeps_worker_alive() {
+    // 收不到心跳时节点在一次超时后即被移出
+    let config = PoolConfig::builder()
+        .heartbeat_timeout(Duration::from_millis(150))
+        .circuit_breaker_threshold(1)
+        .check_interval(Duration::from_millis(20))
+        .build()
+        .unwrap();
+    let pool = DronePool::bind("tcp://127.0.0.1:*", config).unwrap();
+    let endpoint = pool.local_endpoint().unwrap();
+    let handle = pool.spawn();
+
+    let config = DroneConfig::builder()
+        .heartbeat_interval(Duration::from_millis(20))
+        .heartbeat_timeout(Duration::from_millis(200))
+        .circuit_breaker_threshold(2)
+        .build()
+        .unwrap();
+    let mut heartbeat = HeartbeatManager::with_config("hb-drone".to_string(), &endpoint, &config).unwrap();
+    heartbeat.register(vec!["hb".to_string()]).unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    // 心跳循环只在超时或出错时返回
+    let result = runtime.block_on(async { tokio::time::timeout(Duration::from_millis(800), heartbeat.start()).await });
+    assert!(result.is_err(), "心跳循环提前退出: {:?}", result);
+    assert_eq!(heartbeat.health_state(), HealthState::Healthy);
+
+    let AdminResponse::Workers { workers } = handle.admin(AdminRequest::ListWorkers).unwrap() else {
+        panic!("应返回节点列表");
+    };
+    assert_eq!(workers.len(), 1);
+    assert_eq!(workers[0].id, "hb-drone");
+    assert!(workers[0].last_heartbeat_ms < 150, "{}", workers[0].last_heartbeat_ms);
+    handle.shutdown().unwrap();
+}
@@ -0,0 +1,68 @@
+//! Drone节点身份测试
+
+use std::collections::HashMap;
+use zerg_pool::config::DroneConfig;
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::{DronePool, ProcessMessage};
+
+#[test]
+fn test_identity_is_persisted_across_restarts() {
+    let path = std::env::temp_dir().join(format!("zerg_pool_identity_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let config = DroneConfig::builder().identity_path(&path).build().unwrap();
+    let first = config.resolve_worker_id().unwrap();
+    let second = config.resolve_worker_id().unwrap();
+    assert_eq!(first, second);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+
+    // 显式指定的ID优先于持久化文件
+    let config = DroneConfig::builder().identity_path(&path).worker_id("drone-a").build().unwrap();
+    assert_eq!(config.resolve_worker_id().unwrap(), "drone-a");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_multiple_drones_in_one_process() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let mut pool = DronePool::new("127.0.0.1", port).expect("Failed to create DronePool");
+    let endpoint = format!("tcp://127.0.0.1:{}", port);
+
+    let mut drones: Vec<DroneNetwork> = ["d1", "d2"].iter()
+        .map(|id| DroneNetwork::connect_as(*id, vec![endpoint.clone()]).unwrap())
+        .collect();
+    for drone in drones.iter_mut() {
+        let id = drone.worker_id().to_string();
+        drone.register(&id, vec![]).unwrap();
+    }
+    for _ in 0..30 {
+        pool.poll_events().unwrap();
+        if pool.get_worker_count() == 2 {
+            break;
+        }
+    }
+    assert_eq!(pool.get_worker_count(), 2);
+
+    for i in 0..4 {
+        pool.submit_task(Task {
+            id: format!("t{}", i),
+            payload: vec![],
+            timestamp: 0,
+            metadata: HashMap::new(),
+            priority: None,
+        }).unwrap();
+    }
+    pool.poll_events().unwrap();
+    assert_eq!(pool.in_flight_task_count(), 4);
+
+    // 身份帧即节点ID，任务按ID路由到各自的drone
+    let mut received = 0;
+    for drone in drones.iter_mut() {
+        while let Some(message) = drone.poll_message(200).unwrap() {
+            assert!(matches!(message, ProcessMessage::Task(_)));
+            received += 1;
+        }
+    }
+    assert_eq!(received, 4);
+}
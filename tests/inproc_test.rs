@@ -0,0 +1,119 @@
+//! 进程内传输测试(不占用网络端口)
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::transport::{self, TransportError, TransportKind};
+use zerg_pool::{Drone, DronePool};
+
+fn pool_config() -> PoolConfig {
+    PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap()
+}
+
+#[test]
+fn test_inproc_frames_round_trip() {
+    let endpoint = "inproc://frames";
+    // queen尚未绑定时发送的数据在连接建立后送达
+    let mut client = transport::client(TransportKind::Inproc, "drone-x").unwrap();
+    client.connect(endpoint).unwrap();
+    client.send(b"hello").unwrap();
+
+    let mut server = transport::bind(TransportKind::Inproc, endpoint).unwrap();
+    assert_eq!(server.local_endpoint().unwrap(), endpoint);
+    assert!(matches!(
+        transport::bind(TransportKind::Inproc, endpoint),
+        Err(TransportError::Io(e)) if e.kind() == std::io::ErrorKind::AddrInUse
+    ));
+
+    let mut received = Vec::new();
+    for _ in 0..20 {
+        assert_eq!(client.recv(20).unwrap(), None);
+        received.extend(server.recv(0).unwrap());
+        if !received.is_empty() {
+            break;
+        }
+    }
+    assert_eq!(received, vec![("drone-x".to_string(), b"hello".to_vec())]);
+
+    server.send("drone-x", b"world").unwrap();
+    assert_eq!(client.recv(1000).unwrap(), Some(b"world".to_vec()));
+    // 未连接的身份直接丢弃
+    server.send("nobody", b"lost").unwrap();
+    assert_eq!(client.recv(50).unwrap(), None);
+
+    // 释放后地址可重新绑定
+    drop(server);
+    transport::bind(TransportKind::Inproc, endpoint).unwrap();
+}
+
+#[test]
+fn test_inproc_runs_full_stack() {
+    let endpoint = "inproc://full_stack";
+    let mut pool = DronePool::bind(endpoint, pool_config()).unwrap();
+
+    let drone_config = DroneConfig::builder()
+        .worker_id("inproc-1")
+        .transport(TransportKind::Inproc)
+        .heartbeat_interval(Duration::from_millis(50))
+        .heartbeat_timeout(Duration::from_millis(1000))
+        .build()
+        .unwrap();
+    let drone = Drone::builder(endpoint)
+        .config(drone_config)
+        .executor(|task: &Task| Ok(task.payload.clone()))
+        .build()
+        .unwrap();
+    let handle = drone.handle();
+    let runner = thread::spawn(move || drone.run());
+
+    for i in 0..5 {
+        pool.submit_task(Task {
+            id: format!("t{}", i),
+            payload: vec![i as u8],
+            timestamp: 0,
+            metadata: HashMap::new(),
+            priority: None,
+        }).unwrap();
+    }
+    for _ in 0..100 {
+        pool.poll_events().unwrap();
+        if pool.get_worker_count() == 1 && pool.pending_task_count() == 0 && pool.in_flight_task_count() == 0 {
+            break;
+        }
+    }
+    assert_eq!(pool.get_worker_count(), 1);
+    assert_eq!(pool.pending_task_count(), 0);
+    assert_eq!(pool.in_flight_task_count(), 0);
+
+    handle.stop();
+    runner.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_inproc_client_reconnects_after_queen_restart() {
+    let endpoint = "inproc://restart";
+    let mut pool = DronePool::bind(endpoint, pool_config()).unwrap();
+
+    let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, "inproc-2", vec![endpoint.to_string()]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register("inproc-2", vec![]).unwrap();
+    let mut pump = |pool: &mut DronePool| {
+        for _ in 0..50 {
+            drone.send_heartbeat().unwrap();
+            pool.poll_events().unwrap();
+            drone.poll_message(20).unwrap();
+            if pool.get_worker_count() == 1 {
+                return true;
+            }
+        }
+        false
+    };
+    assert!(pump(&mut pool));
+
+    drop(pool);
+    let mut pool = DronePool::bind(endpoint, pool_config()).unwrap();
+    assert!(pump(&mut pool));
+}
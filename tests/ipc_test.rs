@@ -0,0 +1,99 @@
+//! Unix域套接字(ipc)传输测试
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::transport::{self, TransportError, TransportKind};
+use zerg_pool::{Drone, DronePool};
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zerg_pool_ipc_{}_{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_stale_socket_file_is_replaced() {
+    for (name, kind) in [("stale_zmq", TransportKind::Zmq), ("stale_native", TransportKind::Native)] {
+        let path = socket_path(name);
+        let endpoint = format!("ipc://{}", path.display());
+
+        // 模拟进程异常退出后遗留的socket文件
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let server = transport::bind(kind, &endpoint).unwrap();
+        // 仍在监听的socket文件不能被抢占
+        let taken = transport::bind(kind, &endpoint);
+        assert!(matches!(taken, Err(TransportError::Io(e)) if e.kind() == std::io::ErrorKind::AddrInUse));
+
+        drop(server);
+        assert!(!path.exists(), "{:?} 释放后应删除socket文件", kind);
+    }
+}
+
+#[test]
+fn test_refuses_to_replace_regular_file() {
+    let path = socket_path("regular");
+    std::fs::write(&path, b"data").unwrap();
+    let result = transport::bind(TransportKind::Native, &format!("ipc://{}", path.display()));
+    assert!(matches!(result, Err(TransportError::InvalidEndpoint(_))));
+    assert_eq!(std::fs::read(&path).unwrap(), b"data");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_full_stack_over_ipc() {
+    for (name, kind) in [("stack_zmq", TransportKind::Zmq), ("stack_native", TransportKind::Native)] {
+        let path = socket_path(name);
+        let config = PoolConfig::builder().transport(kind).ipc_mode(0o600).build().unwrap();
+        let mut pool = DronePool::bind(&format!("ipc://{}", path.display()), config).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let drone_config = DroneConfig::builder()
+            .worker_id(format!("ipc-{}", name))
+            .transport(kind)
+            .heartbeat_interval(Duration::from_millis(50))
+            .heartbeat_timeout(Duration::from_millis(1000))
+            .build()
+            .unwrap();
+        // `unix://`与`ipc://`等价
+        let drone = Drone::builder(format!("unix://{}", path.display()))
+            .config(drone_config)
+            .executor(|task: &Task| Ok(task.payload.clone()))
+            .build()
+            .unwrap();
+        let handle = drone.handle();
+        let runner = thread::spawn(move || drone.run());
+
+        for i in 0..5 {
+            pool.submit_task(Task {
+                id: format!("{}-{}", name, i),
+                payload: vec![i as u8],
+                timestamp: 0,
+                metadata: HashMap::new(),
+                priority: None,
+            }).unwrap();
+        }
+        for _ in 0..100 {
+            pool.poll_events().unwrap();
+            if pool.get_worker_count() == 1 && pool.pending_task_count() == 0 && pool.in_flight_task_count() == 0 {
+                break;
+            }
+        }
+        assert_eq!(pool.get_worker_count(), 1, "{:?}", kind);
+        assert_eq!(pool.pending_task_count(), 0);
+        assert_eq!(pool.in_flight_task_count(), 0);
+
+        handle.stop();
+        runner.join().unwrap().unwrap();
+        drop(pool);
+        assert!(!path.exists());
+    }
+}
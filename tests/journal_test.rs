@@ -0,0 +1,174 @@
+//! 任务日志与崩溃恢复测试
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+use zerg_pool::config::PoolConfig;
+use zerg_pool::proto::zergpool::{response, Registration, Response, Task};
+use zerg_pool::queen::journal::{JournalRecord, RecoveredTask, TaskJournal};
+use zerg_pool::{DronePool, ProcessMessage};
+
+fn temp_journal(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("zerg_pool_{}_{}.journal", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: id.as_bytes().to_vec(),
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+fn open_pool(journal: &PathBuf) -> (DronePool, u16) {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder().journal_path(journal).build().unwrap();
+    let pool = DronePool::with_config("127.0.0.1", port, config).expect("Failed to create DronePool");
+    (pool, port)
+}
+
+#[test]
+fn test_replay_returns_unfinished_tasks_in_order() {
+    let path = temp_journal("replay");
+    {
+        let (mut journal, recovered) = TaskJournal::open(&path).unwrap();
+        assert!(recovered.is_empty());
+        for id in ["t1", "t2", "t3"] {
+            journal.append(&JournalRecord::Submit { task: new_task(id) }).unwrap();
+        }
+        journal.append(&JournalRecord::Dispatch { task_id: "t1".into(), worker_id: "w1".into() }).unwrap();
+        journal.append(&JournalRecord::Dispatch { task_id: "t2".into(), worker_id: "w1".into() }).unwrap();
+        journal.append(&JournalRecord::Complete { task_id: "t2".into() }).unwrap();
+    }
+
+    // 模拟写入中途崩溃留下的半条记录
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"event":"complete","task_"#).unwrap();
+    drop(file);
+
+    let recovered = TaskJournal::replay(&path).unwrap();
+    let ids: Vec<_> = recovered.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["t1", "t3"]);
+    assert_eq!(recovered[1].payload, b"t3".to_vec());
+
+    // 重新打开时压缩，仅保留未完成任务
+    let (_journal, recovered) = TaskJournal::open(&path).unwrap();
+    assert_eq!(recovered.len(), 2);
+    let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+    assert_eq!(lines, 2);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_queen_recovers_tasks_after_crash() {
+    let path = temp_journal("crash");
+    let (mut pool, port) = open_pool(&path);
+
+    // 模拟drone: 设置身份帧后通过信封注册
+    let ctx = zmq::Context::new();
+    let drone = ctx.socket(zmq::DEALER).unwrap();
+    drone.set_identity(b"w1").unwrap();
+    drone.set_rcvtimeo(2000).unwrap();
+    drone.connect(&format!("tcp://127.0.0.1:{}", port)).unwrap();
+    let reg = ProcessMessage::Registration(Registration {
+        worker_id: "w1".into(),
+        max_threads: 4,
+        version: env!("CARGO_PKG_VERSION").into(),
+        capabilities: vec![],
+        token: String::new(),
+        protocol_version: zerg_pool::version::PROTOCOL_VERSION.into(),
+    });
+    drone.send_multipart(["".as_bytes(), "".as_bytes(), &reg.encode_envelope()], 0).unwrap();
+
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if pool.get_worker_count() == 1 {
+            break;
+        }
+    }
+    assert_eq!(pool.get_worker_count(), 1);
+
+    pool.submit_task(new_task("t1")).unwrap();
+    pool.submit_task(new_task("t2")).unwrap();
+    pool.poll_events().unwrap();
+    assert_eq!(pool.in_flight_task_count(), 2);
+
+    // drone收到两个任务(跳过注册成功回复)，只完成第一个
+    let task = loop {
+        let frames = drone.recv_multipart(0).unwrap();
+        match ProcessMessage::decode_envelope(frames.last().unwrap()).unwrap() {
+            ProcessMessage::Task(task) => break task,
+            ProcessMessage::RegistrationAccepted(_) => continue,
+            other => panic!("期望收到任务消息: {:?}", other),
+        }
+    };
+    let resp = ProcessMessage::TaskResponse(Response {
+        worker_id: "w1".into(),
+        task_id: task.id.clone(),
+        result: Some(response::Result::Output(vec![])),
+    });
+    drone.send_multipart(["".as_bytes(), "".as_bytes(), &resp.encode_envelope()], 0).unwrap();
+
+    for _ in 0..50 {
+        pool.poll_events().unwrap();
+        if pool.in_flight_task_count() == 1 {
+            break;
+        }
+    }
+    assert_eq!(pool.in_flight_task_count(), 1);
+
+    // queen崩溃后重启，未完成任务重新入队
+    drop(pool);
+    std::thread::sleep(Duration::from_millis(50));
+    let (pool, _) = open_pool(&path);
+    assert_eq!(pool.pending_task_count(), 1);
+    assert_eq!(pool.in_flight_task_count(), 0);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_retry_attempts_survive_restart() {
+    let path = temp_journal("retry");
+    {
+        let (mut journal, _) = TaskJournal::open(&path).unwrap();
+        journal.append(&JournalRecord::Submit { task: new_task("t1") }).unwrap();
+        journal.append(&JournalRecord::Submit { task: new_task("t2") }).unwrap();
+        journal.append(&JournalRecord::Retry { task_id: "t1".into(), attempts: 1 }).unwrap();
+        journal.append(&JournalRecord::Retry { task_id: "t1".into(), attempts: 2 }).unwrap();
+        assert_eq!(journal.unsynced(), 4);
+        journal.sync().unwrap();
+        assert_eq!(journal.unsynced(), 0);
+    }
+
+    // 启动时的压缩保留重试次数，再次重启仍可恢复
+    for _ in 0..2 {
+        let (_journal, recovered) = TaskJournal::open(&path).unwrap();
+        assert_eq!(recovered, vec![
+            RecoveredTask { task: new_task("t1"), attempts: 2 },
+            RecoveredTask { task: new_task("t2"), attempts: 0 },
+        ]);
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_submit_acknowledged_after_journal_sync() {
+    let path = temp_journal("ack");
+    let (pool, _) = open_pool(&path);
+    let handle = pool.spawn();
+
+    // 提交确认返回时记录已落盘，此时读取日志即可回放出该任务
+    for id in ["t1", "t2"] {
+        handle.submit(new_task(id)).unwrap();
+        let recovered = TaskJournal::replay(&path).unwrap();
+        assert_eq!(recovered.last().map(|t| t.id.as_str()), Some(id));
+    }
+
+    drop(handle);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,82 @@
+//! 诊断日志测试：网络与调度路径的诊断经log输出，带结构化字段与模块target
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use log::{Level, Log, Metadata, Record};
+use zerg_pool::config::PoolConfig;
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::proto::zergpool::{response, Response, Task};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{DronePool, ProcessMessage};
+
+/// 记录所有日志的logger
+struct CaptureLogger(Mutex<Vec<(Level, String, String)>>);
+
+impl Log for CaptureLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push((record.level(), record.target().to_string(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger(Mutex::new(Vec::new()));
+
+fn find(level: Level, target: &str, fields: &[&str]) -> Option<String> {
+    LOGGER.0.lock().unwrap().iter()
+        .find(|(l, t, message)| *l == level && t == target && fields.iter().all(|f| message.contains(f)))
+        .map(|(_, _, message)| message.clone())
+}
+
+#[test]
+fn test_diagnostics_use_structured_log_records() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let endpoint = "inproc://logging";
+    let mut pool = DronePool::bind(endpoint, PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap()).unwrap();
+    let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, "log-drone", vec![endpoint.to_string()]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register("log-drone", vec![]).unwrap();
+    pool.submit_task(Task {
+        id: "log-task".to_string(),
+        payload: vec![],
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }).unwrap();
+    let mut task = None;
+    for _ in 0..50 {
+        drone.send_heartbeat().unwrap();
+        pool.poll_events().unwrap();
+        if let Some(ProcessMessage::Task(t)) = drone.poll_message(10).unwrap() {
+            task = Some(t);
+            break;
+        }
+    }
+    drone.send_response(&Response {
+        worker_id: "log-drone".to_string(),
+        task_id: task.expect("任务应派发给drone").id,
+        result: Some(response::Result::Error("boom".to_string())),
+    }).unwrap();
+    for _ in 0..20 {
+        pool.poll_events().unwrap();
+    }
+
+    assert!(find(Level::Info, "zerg_pool::queen", &["注册新工作节点", "worker_id=log-drone", "pool=\"main\""]).is_some());
+    assert!(find(Level::Debug, "zerg_pool::drone::network", &["已发送注册消息", "worker_id=log-drone", "bytes="]).is_some());
+    assert!(find(Level::Trace, "zerg_pool::drone::network", &["已发送心跳", "bytes="]).is_some());
+    assert!(find(Level::Debug, "zerg_pool::queen::network", &["已派发任务", "identity=", "task_id=log-task"]).is_some());
+    assert!(find(Level::Warn, "zerg_pool::queen::dispatch", &["task_id=log-task", "worker_id=log-drone", "error=boom"]).is_some());
+    // 逐次轮询的诊断只在trace级别输出
+    let noisy: Vec<_> = LOGGER.0.lock().unwrap().iter()
+        .filter(|(level, _, message)| *level <= Level::Info && (message.contains("轮询") || message.contains("收到网络消息")))
+        .cloned()
+        .collect();
+    assert!(noisy.is_empty(), "{:?}", noisy);
+}
@@ -0,0 +1,177 @@
+//! 指标目录测试：用本地记录器验证各组件记录的指标及其取值
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
+use zerg_pool::balancer::{BackupPool, ZergRushSelector};
+use zerg_pool::config::{DroneConfig, PoolConfig, RetryPolicy};
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::drone::TaskQueue;
+use zerg_pool::engine::TaskEngine;
+use zerg_pool::exporter::{self, MetricKind, PrometheusRecorder, CATALOGUE};
+use zerg_pool::proto::zergpool::{response, Response, Task};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{DronePool, Process, ProcessMessage};
+
+fn new_task(id: &str) -> Task {
+    Task {
+        id: id.to_string(),
+        payload: vec![],
+        timestamp: 0,
+        metadata: HashMap::new(),
+        priority: None,
+    }
+}
+
+/// 导出文本中指定样本(指标名含标签)的值
+fn sample(body: &str, series: &str) -> Option<f64> {
+    body.lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .and_then(|value| value.parse().ok())
+}
+
+/// 导出的每个指标都必须登记在目录中且类型一致
+fn assert_catalogued(body: &str) {
+    for line in body.lines().filter_map(|l| l.strip_prefix("# TYPE ")) {
+        let (name, kind) = line.split_once(' ').unwrap();
+        let def = CATALOGUE.iter()
+            .find(|def| def.exported_name() == name)
+            .unwrap_or_else(|| panic!("指标 {} 未登记在目录中", name));
+        let expected = match def.kind {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram => "histogram",
+        };
+        assert_eq!(kind, expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_queen_task_lifecycle_metrics() {
+    let endpoint = "inproc://metrics_lifecycle";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .heartbeat_timeout(Duration::from_millis(50))
+        .circuit_breaker_threshold(1)
+        .retry(RetryPolicy { max_retries: 1, backoff_ms: 1, max_backoff_ms: 1 })
+        .build()
+        .unwrap();
+    let recorder = PrometheusRecorder::new();
+    metrics::with_local_recorder(&recorder, || {
+        exporter::describe_metrics();
+        let mut pool = DronePool::bind(endpoint, config).unwrap();
+        let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, "metrics-a", vec![endpoint.to_string()]).unwrap();
+        drone.set_heartbeat_interval(Duration::ZERO);
+        drone.register("metrics-a", vec![]).unwrap();
+        pool.submit_task(new_task("ok")).unwrap();
+        pool.submit_task(new_task("flaky")).unwrap();
+
+        // 一个成功，一个失败后重试
+        let mut received = Vec::new();
+        for _ in 0..100 {
+            pool.poll_events().unwrap();
+            if let Some(ProcessMessage::Task(task)) = drone.poll_message(5).unwrap() {
+                let result = if task.id == "flaky" && !received.contains(&task.id) {
+                    response::Result::Error("boom".to_string())
+                } else {
+                    response::Result::Output(vec![])
+                };
+                received.push(task.id.clone());
+                if received.len() == 3 {
+                    break;
+                }
+                drone.send_response(&Response { worker_id: "metrics-a".to_string(), task_id: task.id, result: Some(result) }).unwrap();
+            }
+        }
+        assert_eq!(received.len(), 3, "{:?}", received);
+        pool.poll_events().unwrap();
+        let body = recorder.handle().render();
+        assert_eq!(sample(&body, "zergpool_worker_in_flight{worker=\"metrics-a\"}"), Some(1.0));
+        assert_eq!(sample(&body, "zergpool_in_flight_tasks"), Some(1.0));
+
+        // 重试中的任务随节点心跳超时重新入队
+        std::thread::sleep(Duration::from_millis(120));
+        assert_eq!(pool.reap_stale_workers(), vec!["metrics-a".to_string()]);
+        pool.poll_events().unwrap();
+    });
+
+    let body = recorder.handle().render();
+    assert_catalogued(&body);
+    for (series, expected) in [
+        ("zergpool_tasks_submitted_total", 2.0),
+        ("zergpool_tasks_dispatched_total", 3.0),
+        ("zergpool_tasks_completed_total", 1.0),
+        ("zergpool_tasks_failed_total", 1.0),
+        ("zergpool_tasks_retried_total", 1.0),
+        ("zergpool_tasks_timed_out_total", 1.0),
+        ("zergpool_workers_reaped_total", 1.0),
+        ("zergpool_pending_tasks", 1.0),
+        ("zergpool_in_flight_tasks", 0.0),
+        ("zergpool_workers", 0.0),
+        ("zergpool_worker_up{worker=\"metrics-a\"}", 0.0),
+        ("zergpool_worker_in_flight{worker=\"metrics-a\"}", 0.0),
+        ("zergpool_task_latency_seconds_count{outcome=\"failure\"}", 1.0),
+        ("zergpool_task_latency_seconds_count{outcome=\"success\"}", 1.0),
+    ] {
+        assert_eq!(sample(&body, series), Some(expected), "{}:\n{}", series, body);
+    }
+    assert!(body.contains("# HELP zergpool_tasks_timed_out_total "));
+}
+
+#[test]
+fn test_backup_pool_gauge_tracks_size() {
+    let recorder = PrometheusRecorder::new();
+    metrics::with_local_recorder(&recorder, || {
+        let mut backups = BackupPool::new();
+        backups.add_node(Process::new("b1".to_string(), vec![], Some(1)));
+        backups.add_node(Process::new("b2".to_string(), vec![], Some(1)));
+        assert_eq!(sample(&recorder.handle().render(), "zergpool_backup_nodes"), Some(2.0));
+        assert!(backups.take_node().is_some());
+        assert!(backups.take_node().is_some());
+        assert!(backups.take_node().is_none());
+    });
+    let body = recorder.handle().render();
+    assert_catalogued(&body);
+    assert_eq!(sample(&body, "zergpool_backup_nodes"), Some(0.0));
+}
+
+#[test]
+fn test_engine_queue_depth() {
+    let recorder = PrometheusRecorder::new();
+    metrics::with_local_recorder(&recorder, || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            let selector = ZergRushSelector::new(0.8, Duration::from_secs(1), Duration::ZERO);
+            let engine = TaskEngine::new(Arc::new(tokio::sync::Mutex::new(selector)), 1);
+            let done = Arc::new(StdMutex::new(0));
+            for _ in 0..3 {
+                let done = Arc::clone(&done);
+                engine.submit(Box::new(move || *done.lock().unwrap() += 1)).await;
+            }
+            // 单线程运行时中worker尚未运行，任务都在队列中
+            assert_eq!(sample(&recorder.handle().render(), "zergpool_engine_queue_depth"), Some(3.0));
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while *done.lock().unwrap() < 3 && Instant::now() < deadline {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            engine.shutdown().await;
+        });
+    });
+    let body = recorder.handle().render();
+    assert_catalogued(&body);
+    assert_eq!(sample(&body, "zergpool_engine_queue_depth"), Some(0.0));
+}
+
+#[test]
+fn test_task_queue_counts_drain() {
+    let config = DroneConfig::default();
+    let queue = TaskQueue::with_config("queue-metrics", &config);
+    for i in 0..5 {
+        queue.submit(new_task(&format!("q{}", i))).unwrap();
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while queue.queue_usage() != (0, config.queue_capacity, 0, config.queue_capacity) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(queue.queue_usage(), (0, config.queue_capacity, 0, config.queue_capacity));
+}
@@ -18,7 +18,9 @@ struct TestResources {
 fn setup_mio_with_zmq() -> io::Result<()> {
     let ctx = Context::new();
     let mut server = ctx.socket(zmq::REP)?;
-    server.bind("tcp://127.0.0.1:5555")?;
+    // 绑定系统分配的端口，再读回实际地址
+    server.bind("tcp://127.0.0.1:*")?;
+    let endpoint = server.get_last_endpoint()?.expect("绑定地址应为UTF-8");
 
     let zmq_fd = server.get_fd()?;
     println!("[ZMQ] 平台FD: {}", zmq_fd);
@@ -35,7 +37,7 @@ fn setup_mio_with_zmq() -> io::Result<()> {
         thread::sleep(Duration::from_millis(500));
         let ctx = Context::new();
         let mut client = ctx.socket(zmq::REQ).unwrap();
-        client.connect("tcp://127.0.0.1:5555").unwrap();
+        client.connect(&endpoint).unwrap();
         client.send(TEST_MESSAGE, 0).unwrap();
         let reply = client.recv_msg(0).unwrap();
         println!("[CLIENT] 收到回复: {:?}", reply);
@@ -102,4 +104,38 @@ fn test_mio_zmq_integration() {
         Ok(_) => println!("[SUCCESS] 测试通过"),
         Err(e) => panic!("测试失败: {:?}", e)
     }
+}
+
+/// 对比ZMQ与原生mio TCP传输的往返吞吐
+fn transport_round_trips(kind: zerg_pool::transport::TransportKind, rounds: usize) -> Duration {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let endpoint = format!("tcp://127.0.0.1:{}", port);
+    let mut server = zerg_pool::transport::bind(kind, &endpoint).unwrap();
+    let mut client = zerg_pool::transport::client(kind, "perf-drone").unwrap();
+    client.connect(&endpoint).unwrap();
+    let payload = vec![0u8; 256];
+
+    let start = std::time::Instant::now();
+    for _ in 0..rounds {
+        client.send(&payload).unwrap();
+        let (identity, data) = loop {
+            if let Some(message) = server.recv(1000).unwrap().pop() {
+                break message;
+            }
+        };
+        server.send(&identity, &data).unwrap();
+        assert_eq!(client.recv(1000).unwrap().map(|d| d.len()), Some(payload.len()));
+    }
+    start.elapsed()
+}
+
+#[test]
+fn test_transport_round_trip_comparison() {
+    use zerg_pool::transport::TransportKind;
+    const ROUNDS: usize = 1000;
+    for kind in [TransportKind::Zmq, TransportKind::Native] {
+        let elapsed = transport_round_trips(kind, ROUNDS);
+        println!("[PERF] {:?}: {}次往返耗时 {:?}, {:.0} 次/秒",
+                 kind, ROUNDS, elapsed, ROUNDS as f64 / elapsed.as_secs_f64());
+    }
 }
\ No newline at end of file
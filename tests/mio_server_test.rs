@@ -9,14 +9,13 @@ const CLIENT: Token = Token(1);
 
 #[test]
 fn test_basic_mio_communication() {
-    let port = 5555;
-    
+    // 绑定系统分配的端口，再读回实际地址
+    let mut listener = TcpListener::bind("127.0.0.1:0".parse().unwrap()).expect("Failed to bind");
+    let addr = listener.local_addr().expect("Failed to read bound address");
+    println!("[SERVER] Bound to {}", addr);
+
     // 启动服务器线程
     let server_thread = thread::spawn(move || {
-        let addr = format!("127.0.0.1:{}", port).parse().unwrap();
-        println!("[SERVER] Binding to {}", addr);
-        let mut listener = TcpListener::bind(addr).expect("Failed to bind");
-        
         let mut poll = Poll::new().unwrap();
         poll.registry()
             .register(&mut listener, SERVER, Interest::READABLE)
@@ -74,7 +73,6 @@ fn test_basic_mio_communication() {
 
     // 客户端测试
     println!("[CLIENT] Connecting to server...");
-    let addr: std::net::SocketAddr = format!("127.0.0.1:{}", port).parse().expect("Invalid address");
     let mut stream = TcpStream::connect(addr).expect("Connect failed");
     let mut poll = Poll::new().unwrap();
     poll.registry()
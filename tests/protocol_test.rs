@@ -0,0 +1,156 @@
+//! 线协议版本协商测试：queen与drone只与同一主版本互通，不兼容时双方都明确报错；
+//! 3.0之前的drone发送裸protobuf消息(无Envelope)，queen不会将其当作注册
+
+use std::time::Duration;
+use prost::Message;
+use zerg_pool::config::PoolConfig;
+use zerg_pool::drone::network::{DroneNetwork, NetworkError};
+use zerg_pool::proto::zergpool::{Heartbeat, Registration, RegistrationAccepted, RejectReason};
+use zerg_pool::transport::{self, TransportKind};
+use zerg_pool::version::{self, Version, PROTOCOL_VERSION};
+use zerg_pool::{DronePool, ProcessMessage};
+
+/// 相对当前协议主版本偏移`offset`的协议版本
+fn protocol(offset: i64) -> String {
+    format!("{}.3.0", Version::protocol().major as i64 + offset)
+}
+
+fn registration(worker_id: &str, protocol_version: &str) -> Vec<u8> {
+    ProcessMessage::Registration(Registration {
+        worker_id: worker_id.to_string(),
+        max_threads: 1,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        capabilities: vec![],
+        token: String::new(),
+        protocol_version: protocol_version.to_string(),
+    })
+    .encode_envelope()
+}
+
+/// 以原始传输模拟指定协议版本的drone，返回queen的回复
+fn register_raw(pool: &mut DronePool, endpoint: &str, worker_id: &str, protocol_version: &str) -> Option<ProcessMessage> {
+    let mut drone = transport::client(TransportKind::Inproc, worker_id).unwrap();
+    drone.connect(endpoint).unwrap();
+    drone.send(&registration(worker_id, protocol_version)).unwrap();
+    for _ in 0..20 {
+        pool.poll_events().unwrap();
+        if let Some(data) = drone.recv(5).unwrap() {
+            return Some(ProcessMessage::decode_envelope(&data).unwrap());
+        }
+    }
+    None
+}
+
+#[test]
+fn test_protocol_version_from_descriptor() {
+    let current = Version::protocol();
+    assert_eq!(current.to_string(), PROTOCOL_VERSION);
+    // 3.0起消息封装为Envelope
+    assert!(current.major >= 3);
+
+    let compatible = |offset| version::protocol_compatible(&current, &protocol(offset).parse().unwrap());
+    assert!(!compatible(-1));
+    assert!(compatible(0));
+    assert!(!compatible(1));
+    assert_eq!(version::parse_protocol("").unwrap(), Version::new(1, 0, 0));
+    assert!(version::parse_protocol("two").is_err());
+}
+
+#[test]
+fn test_queen_accepts_only_current_protocol() {
+    let endpoint = "inproc://protocol_queen";
+    let config = PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+
+    let reply = register_raw(&mut pool, endpoint, "current", &protocol(0));
+    let Some(ProcessMessage::RegistrationAccepted(accepted)) = reply else {
+        panic!("未收到注册成功回复: {:?}", reply);
+    };
+    assert_eq!(accepted.protocol_version, PROTOCOL_VERSION);
+    assert_eq!(accepted.queen_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(pool.workers().len(), 1);
+
+    let rejected_versions = [
+        ("previous", protocol(-1)),
+        ("next", protocol(1)),
+        ("unversioned", String::new()),
+        ("garbage", "v2".to_string()),
+    ];
+    for (worker_id, protocol_version) in rejected_versions {
+        let reply = register_raw(&mut pool, endpoint, worker_id, &protocol_version);
+        let Some(ProcessMessage::RegistrationRejected(rejected)) = reply else {
+            panic!("{}: 未收到拒绝回复: {:?}", worker_id, reply);
+        };
+        assert_eq!(rejected.reason(), RejectReason::IncompatibleProtocol);
+        assert_eq!(rejected.protocol_version, PROTOCOL_VERSION);
+        assert!(rejected.message.contains(&protocol_version), "{}", rejected.message);
+    }
+    assert_eq!(pool.workers().len(), 1);
+}
+
+#[test]
+fn test_queen_ignores_pre_envelope_frames() {
+    let endpoint = "inproc://protocol_legacy";
+    let config = PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+
+    // 2.x drone直接发送裸protobuf消息，且不上报协议版本
+    let mut legacy = transport::client(TransportKind::Inproc, "legacy-drone").unwrap();
+    legacy.connect(endpoint).unwrap();
+    let registration = Registration {
+        worker_id: "legacy-drone".to_string(),
+        max_threads: 4,
+        version: "0.1.0".to_string(),
+        capabilities: vec![],
+        token: String::new(),
+        protocol_version: String::new(),
+    };
+    let heartbeat = Heartbeat { worker_id: "legacy-drone".to_string(), timestamp: 1, ..Default::default() };
+    legacy.send(&registration.encode_to_vec()).unwrap();
+    legacy.send(&heartbeat.encode_to_vec()).unwrap();
+    for _ in 0..10 {
+        pool.poll_events().unwrap();
+    }
+    assert!(pool.workers().is_empty());
+    assert!(legacy.recv(5).unwrap().is_none());
+
+    // 同一queen仍正常接受当前协议的drone
+    let reply = register_raw(&mut pool, endpoint, "current", &protocol(0));
+    assert!(matches!(reply, Some(ProcessMessage::RegistrationAccepted(_))), "{:?}", reply);
+    assert_eq!(pool.workers().len(), 1);
+}
+
+#[test]
+fn test_drone_checks_queen_protocol() {
+    let endpoint = "inproc://protocol_drone";
+    let mut queen = transport::bind(TransportKind::Inproc, endpoint).unwrap();
+
+    for (offset, compatible) in [(0, true), (-1, false), (1, false)] {
+        let worker_id = format!("drone{}", offset);
+        let mut drone = DroneNetwork::connect_with(TransportKind::Inproc, worker_id.as_str(), vec![endpoint.to_string()]).unwrap();
+        drone.set_heartbeat_interval(Duration::from_secs(60));
+        drone.register(&worker_id, vec![]).unwrap();
+
+        let (identity, data) = queen.recv(1000).unwrap().pop().expect("未收到注册消息");
+        let Ok(ProcessMessage::Registration(reg)) = ProcessMessage::decode_envelope(&data) else {
+            panic!("期望收到注册消息");
+        };
+        assert_eq!(reg.protocol_version, PROTOCOL_VERSION);
+
+        // 模拟不同协议版本的queen
+        let accepted = RegistrationAccepted { protocol_version: protocol(offset), queen_version: "0.0.1".to_string() };
+        queen.send(&identity, &ProcessMessage::RegistrationAccepted(accepted).encode_envelope()).unwrap();
+        let result = drone.poll_message(200);
+        if compatible {
+            assert!(matches!(result, Ok(None)), "{:?}", result);
+            assert_eq!(drone.queen_protocol_version(), Some(protocol(offset).as_str()));
+        } else {
+            let Err(NetworkError::IncompatibleProtocol { local, remote }) = result else {
+                panic!("期望协议不兼容错误: {:?}", result);
+            };
+            assert_eq!(local, PROTOCOL_VERSION);
+            assert_eq!(remote, protocol(offset));
+            assert_eq!(drone.queen_protocol_version(), None);
+        }
+    }
+}
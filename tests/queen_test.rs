@@ -1,5 +1,7 @@
 use std::sync::{Arc, Mutex};
 use zerg_pool::{DronePool, ProcessMessage};
+use zerg_pool::config::PoolConfig;
+use zerg_pool::transport::TransportKind;
 use zerg_pool::proto::zergpool::Registration;
 use zmq::{Context, Socket};
 use prost::Message;
@@ -10,7 +12,10 @@ async fn test_drone_pool_with_queen() {
 
     // 创建DronePool服务器
     let pool = Arc::new(Mutex::new(
-        DronePool::new("127.0.0.1", 5555).expect("Failed to create DronePool")
+        DronePool::bind(
+            "inproc://queen_test",
+            PoolConfig::builder().transport(TransportKind::Inproc).build().unwrap(),
+        ).expect("Failed to create DronePool")
     ));
     let pool_clone = Arc::clone(&pool);
 
@@ -0,0 +1,40 @@
+//! Queen重启后drone自动重新注册测试
+
+use std::time::Duration;
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::DronePool;
+
+fn pump(pool: &mut DronePool, drone: &mut DroneNetwork, until: impl Fn(&DronePool) -> bool) -> bool {
+    for _ in 0..30 {
+        drone.send_heartbeat().unwrap();
+        pool.poll_events().unwrap();
+        drone.poll_message(20).unwrap();
+        if until(pool) {
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn test_drone_reregisters_after_queen_restart() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let mut pool = DronePool::new("127.0.0.1", port).expect("Failed to create DronePool");
+
+    let mut drone = DroneNetwork::connect_as("w1", vec![format!("tcp://127.0.0.1:{}", port)]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register("w1", vec!["compute".to_string()]).unwrap();
+    assert!(pump(&mut pool, &mut drone, |p| p.get_worker_count() == 1));
+
+    // 重复注册不会产生重复节点
+    drone.reregister().unwrap();
+    assert!(!pump(&mut pool, &mut drone, |p| p.get_worker_count() > 1));
+
+    // queen重启后丢失注册信息，心跳得到UNKNOWN_WORKER回复后drone以相同ID重新注册
+    drop(pool);
+    std::thread::sleep(Duration::from_millis(100));
+    let mut pool = DronePool::new("127.0.0.1", port).expect("Failed to restart DronePool");
+    assert_eq!(pool.get_worker_count(), 0);
+    assert!(pump(&mut pool, &mut drone, |p| p.get_worker_count() == 1));
+    assert!(pool.get_worker_metrics(&"w1".to_string()).is_some());
+}
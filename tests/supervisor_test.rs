@@ -0,0 +1,167 @@
+//! drone子进程监管测试(使用`sh`模拟drone可执行文件)
+#![cfg(unix)]
+
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+use zerg_pool::config::{AdmissionConfig, CurveServerConfig, DroneConfig, PoolConfig, SupervisorConfig};
+use zerg_pool::queen::supervisor::{Supervisor, SupervisorError, EVICTED_EXIT_CODE, REJECTED_EXIT_CODE};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::DronePool;
+
+fn shell(script: &str) -> SupervisorConfig {
+    SupervisorConfig {
+        program: Some(PathBuf::from("/bin/sh")),
+        args: vec!["-c".to_string(), script.to_string()],
+        restart_backoff_ms: 20,
+        max_restart_backoff_ms: 200,
+        shutdown_timeout_ms: 300,
+        ..SupervisorConfig::default()
+    }
+}
+
+fn is_alive(pid: u32) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[test]
+fn test_crashed_drones_restart_with_stable_ids() {
+    let output = std::env::temp_dir().join(format!("zerg_pool_supervisor_{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&output);
+    let mut config = shell(&format!(
+        "echo \"$ZERG_DRONE_WORKER_ID $ZERG_DRONE_TRANSPORT $ZERG_QUEEN_ENDPOINT $EXTRA\" >> {}; exit 1",
+        output.display()
+    ));
+    config.instances = 2;
+    config.env.insert("EXTRA".to_string(), "x".to_string());
+
+    let mut supervisor = Supervisor::new(config, "tcp://127.0.0.1:5555", TransportKind::Native).unwrap();
+    supervisor.start().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while supervisor.restarts() < 4 && Instant::now() < deadline {
+        supervisor.tick();
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(supervisor.restarts() >= 4);
+    supervisor.shutdown();
+    assert_eq!(supervisor.running(), 0);
+
+    let lines = std::fs::read_to_string(&output).unwrap();
+    for id in ["drone-0", "drone-1"] {
+        let expected = format!("{} native tcp://127.0.0.1:5555 x", id);
+        assert!(lines.lines().filter(|line| *line == expected).count() >= 2, "{}", lines);
+    }
+    std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn test_shutdown_kills_unresponsive_drones() {
+    let mut supervisor = Supervisor::new(
+        shell("trap '' TERM; while true; do sleep 0.05; done"),
+        "tcp://127.0.0.1:5555",
+        TransportKind::Zmq,
+    ).unwrap();
+    supervisor.start().unwrap();
+    let pids = supervisor.pids();
+    assert_eq!(pids.len(), 1);
+    assert_eq!(pids[0].0, "drone-0");
+    // 等待shell设置好信号处理
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(supervisor.tick(), 0);
+
+    let started = Instant::now();
+    supervisor.shutdown();
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert_eq!(supervisor.running(), 0);
+    assert!(!is_alive(pids[0].1));
+}
+
+#[test]
+fn test_evicted_and_rejected_drones_wait_for_resume() {
+    for code in [EVICTED_EXIT_CODE, REJECTED_EXIT_CODE] {
+        let mut supervisor = Supervisor::new(
+            shell(&format!("exit {}", code)),
+            "tcp://127.0.0.1:5555",
+            TransportKind::Native,
+        ).unwrap();
+        supervisor.start().unwrap();
+        let deadline = Instant::now() + Duration::from_millis(300);
+        while Instant::now() < deadline {
+            supervisor.tick();
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(supervisor.restarts(), 0, "exit {}", code);
+        assert_eq!(supervisor.running(), 0);
+
+        assert!(!supervisor.resume("drone-9"));
+        assert!(supervisor.resume("drone-0"));
+        assert_eq!(supervisor.tick(), 1);
+        supervisor.shutdown();
+    }
+}
+
+#[test]
+fn test_drones_inherit_queen_token() {
+    let output = std::env::temp_dir().join(format!("zerg_pool_supervisor_token_{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&output);
+    let drones = shell(&format!("printf '%s' \"$ZERG_DRONE_TOKEN\" > {}; exec sleep 30", output.display()));
+    let admission = AdmissionConfig { token: Some("12345".to_string()), ..AdmissionConfig::default() };
+    let pool = PoolConfig::builder().admission(admission).build().unwrap();
+
+    let mut supervisor = Supervisor::for_pool(drones, "tcp://127.0.0.1:5555", &pool).unwrap();
+    supervisor.start().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !output.exists() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(5));
+    }
+    thread::sleep(Duration::from_millis(20));
+    supervisor.shutdown();
+
+    // 纯数字令牌按字符串注入，drone按`ZERG_DRONE_*`规则解析后与queen一致
+    let injected = std::fs::read_to_string(&output).unwrap();
+    let token: serde_json::Value = serde_json::from_str(&injected).unwrap();
+    let config: DroneConfig = serde_json::from_value(serde_json::json!({ "token": token })).unwrap();
+    assert_eq!(config.token.as_deref(), Some("12345"));
+    std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn test_curve_requires_drone_keys() {
+    let curve = CurveServerConfig { key_file: PathBuf::from("/nonexistent/queen.key"), authorized_keys: None };
+    let pool = PoolConfig { transport: TransportKind::Zmq, curve: Some(curve), ..PoolConfig::default() };
+    let result = Supervisor::for_pool(shell("exit 0"), "tcp://127.0.0.1:5555", &pool);
+    assert!(matches!(result, Err(SupervisorError::Config(_))));
+
+    let mut drones = shell("exit 0");
+    drones.env.insert("ZERG_DRONE_CURVE".to_string(), r#"{"key_file":"drone.key","server_key_file":"queen.pub"}"#.to_string());
+    assert!(Supervisor::for_pool(drones, "tcp://127.0.0.1:5555", &pool).is_ok());
+}
+
+#[test]
+fn test_rejects_inproc_transport() {
+    let result = Supervisor::new(shell("exit 0"), "inproc://queen", TransportKind::Inproc);
+    assert!(matches!(result, Err(SupervisorError::Config(_))));
+}
+
+#[test]
+fn test_pool_starts_and_stops_drones() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let mut drones = shell("exec sleep 30");
+    drones.instances = 2;
+    let config = PoolConfig::builder().drones(drones).build().unwrap();
+    let pool = DronePool::with_config("127.0.0.1", port, config).unwrap();
+
+    let supervisor = pool.supervisor().unwrap();
+    assert_eq!(supervisor.running(), 2);
+    let pids = supervisor.pids();
+    assert!(pids.iter().all(|(_, pid)| is_alive(*pid)));
+
+    let handle = pool.spawn();
+    handle.shutdown().unwrap();
+    assert!(pids.iter().all(|(_, pid)| !is_alive(*pid)));
+}
@@ -0,0 +1,192 @@
+//! 分布式追踪测试：trace-context经任务元数据在queen与drone之间传递，
+//! span导出到本地的OTLP收集器替身
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use serde_json::Value;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::Registry;
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::telemetry::{self, TraceContext, TraceLayer, TRACEPARENT_KEY};
+use zerg_pool::transport::TransportKind;
+use zerg_pool::{Drone, DronePool};
+
+/// 接收OTLP/HTTP JSON请求的收集器替身
+fn start_collector() -> (SocketAddr, Arc<Mutex<Vec<Value>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::clone(&requests);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            assert!(request_line.starts_with("POST /v1/traces "), "{}", request_line);
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            received.lock().unwrap().push(serde_json::from_slice(&body).unwrap());
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}").unwrap();
+        }
+    });
+    (addr, requests)
+}
+
+/// 展开导出请求中的全部span
+fn exported_spans(requests: &[Value]) -> Vec<Value> {
+    requests.iter()
+        .flat_map(|r| r["resourceSpans"].as_array().unwrap().clone())
+        .flat_map(|r| r["scopeSpans"].as_array().unwrap().clone())
+        .flat_map(|s| s["spans"].as_array().unwrap().clone())
+        .collect()
+}
+
+#[test]
+fn test_traceparent_format() {
+    let context = TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+    assert_eq!(context.trace_id, 0x4bf92f3577b34da6a3ce929d0e0e4736);
+    assert_eq!(context.span_id, 0x00f067aa0ba902b7);
+    assert!(context.sampled);
+    assert_eq!(context.to_string(), "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01");
+
+    for invalid in [
+        "",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+        "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+        "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+    ] {
+        assert!(TraceContext::parse(invalid).is_none(), "{}", invalid);
+    }
+    // 更高版本可追加字段
+    assert!(TraceContext::parse("01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-extra").is_some());
+
+    let child = context.child();
+    assert_eq!(child.trace_id, context.trace_id);
+    assert_ne!(child.span_id, context.span_id);
+
+    let mut metadata = HashMap::new();
+    telemetry::inject(&mut metadata, &child);
+    assert_eq!(telemetry::extract(&metadata), Some(child));
+    assert!(PoolConfig::builder().otlp_endpoint("grpc://127.0.0.1:4317").build().is_err());
+}
+
+#[test]
+fn test_trace_spans_queen_and_drone() {
+    let (collector, requests) = start_collector();
+    let endpoint = "inproc://tracing";
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .otlp_endpoint(format!("http://{}", collector))
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind(endpoint, config).unwrap();
+    // 库只启动导出器，订阅器由应用组合安装
+    assert!(!tracing::dispatcher::has_been_set());
+    tracing::subscriber::set_global_default(Registry::default().with(TraceLayer)).unwrap();
+
+    let seen = Arc::new(Mutex::new(None));
+    let executed = Arc::new(AtomicBool::new(false));
+    let drone = {
+        let seen = Arc::clone(&seen);
+        let executed = Arc::clone(&executed);
+        let config = DroneConfig::builder()
+            .transport(TransportKind::Inproc)
+            .worker_id("trace-drone")
+            .heartbeat_interval(Duration::from_millis(20))
+            .otlp_endpoint(format!("http://{}", collector))
+            .build()
+            .unwrap();
+        Drone::builder(endpoint)
+            .config(config)
+            .executor(move |task: &Task| {
+                *seen.lock().unwrap() = Some((task.metadata[TRACEPARENT_KEY].clone(), telemetry::current_context()));
+                executed.store(true, Ordering::Release);
+                Ok(task.payload.clone())
+            })
+            .build()
+            .unwrap()
+    };
+    let handle = drone.handle();
+    let runner = thread::spawn(move || drone.run());
+    for _ in 0..200 {
+        pool.poll_events().unwrap();
+        if !pool.workers().is_empty() {
+            break;
+        }
+    }
+
+    // 模拟上游服务已开启的trace
+    let upstream = TraceContext::new_root();
+    let mut metadata = HashMap::new();
+    telemetry::inject(&mut metadata, &upstream);
+    pool.submit_task(Task { id: "traced".to_string(), payload: vec![1], timestamp: 0, metadata, priority: None }).unwrap();
+    for _ in 0..200 {
+        pool.poll_events().unwrap();
+        if executed.load(Ordering::Acquire) && pool.in_flight_task_count() == 0 {
+            break;
+        }
+    }
+    assert_eq!(pool.in_flight_task_count(), 0);
+    handle.stop();
+    runner.join().unwrap().unwrap();
+    telemetry::flush();
+
+    let spans = exported_spans(&requests.lock().unwrap());
+    let find = |name: &str| -> Value {
+        spans.iter()
+            .find(|s| s["name"] == name && s["attributes"].as_array().unwrap().iter().any(|a| a["value"]["stringValue"] == "traced"))
+            .unwrap_or_else(|| panic!("缺少span {}: {:#?}", name, spans))
+            .clone()
+    };
+    let submit = find("task.submit");
+    let dispatch = find("task.dispatch");
+    let complete = find("task.complete");
+    let execute = find("task.execute");
+    let respond = find("task.respond");
+    let select = spans.iter()
+        .find(|s| s["name"] == "task.select" && s["parentSpanId"] == dispatch["spanId"])
+        .expect("选择节点的span应挂在派发span之下");
+
+    for span in [&submit, &dispatch, select, &execute, &respond, &complete] {
+        assert_eq!(span["traceId"], upstream.trace_id_hex(), "{:#?}", span);
+    }
+    assert_eq!(submit["parentSpanId"], upstream.span_id_hex());
+    assert_eq!(dispatch["parentSpanId"], submit["spanId"]);
+    assert_eq!(execute["parentSpanId"], dispatch["spanId"]);
+    assert_eq!(respond["parentSpanId"], execute["spanId"]);
+    assert_eq!(complete["parentSpanId"], submit["spanId"]);
+    assert!(dispatch["attributes"].as_array().unwrap().iter()
+        .any(|a| a["key"] == "worker" && a["value"]["stringValue"] == "trace-drone"));
+    assert!(complete["attributes"].as_array().unwrap().iter()
+        .any(|a| a["key"] == "outcome" && a["value"]["stringValue"] == "success"));
+
+    // 执行器看到的是执行span的上下文
+    let (traceparent, current) = seen.lock().unwrap().clone().unwrap();
+    let current = current.expect("执行器应处于执行span中");
+    assert_eq!(traceparent, current.to_string());
+    assert_eq!(current.span_id_hex(), execute["spanId"].as_str().unwrap());
+    assert_eq!(requests.lock().unwrap()[0]["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"], "zerg-queen");
+}
@@ -0,0 +1,116 @@
+//! 传输层测试(ZMQ与原生TCP)
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use zerg_pool::config::{DroneConfig, PoolConfig};
+use zerg_pool::drone::network::DroneNetwork;
+use zerg_pool::proto::zergpool::Task;
+use zerg_pool::transport::{self, TransportKind};
+use zerg_pool::{Drone, DronePool};
+
+fn endpoint() -> String {
+    format!("tcp://127.0.0.1:{}", portpicker::pick_unused_port().expect("无可用端口"))
+}
+
+#[test]
+fn test_native_frames_round_trip() {
+    let ep = endpoint();
+    let mut server = transport::bind(TransportKind::Native, &ep).unwrap();
+    assert_eq!(server.local_endpoint().unwrap(), ep);
+    let mut client = transport::client(TransportKind::Native, "drone-x").unwrap();
+    client.connect(&ep).unwrap();
+
+    let large = vec![7u8; 1024 * 1024];
+    client.send(b"hello").unwrap();
+    client.send(&large).unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..50 {
+        received.extend(server.recv(20).unwrap());
+        if received.len() == 2 {
+            break;
+        }
+    }
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0], ("drone-x".to_string(), b"hello".to_vec()));
+    assert_eq!(received[1].1.len(), large.len());
+
+    server.send("drone-x", b"world").unwrap();
+    assert_eq!(client.recv(1000).unwrap(), Some(b"world".to_vec()));
+    // 未连接的身份直接丢弃
+    server.send("nobody", b"lost").unwrap();
+    assert_eq!(client.recv(50).unwrap(), None);
+}
+
+#[test]
+fn test_native_transport_runs_full_stack() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder().transport(TransportKind::Native).build().unwrap();
+    let mut pool = DronePool::with_config("127.0.0.1", port, config).unwrap();
+
+    let drone_config = DroneConfig::builder()
+        .worker_id("native-1")
+        .transport(TransportKind::Native)
+        .heartbeat_interval(Duration::from_millis(50))
+        .heartbeat_timeout(Duration::from_millis(1000))
+        .build()
+        .unwrap();
+    let drone = Drone::builder(format!("tcp://127.0.0.1:{}", port))
+        .config(drone_config)
+        .executor(|task: &Task| Ok(task.payload.clone()))
+        .build()
+        .unwrap();
+    let handle = drone.handle();
+    let runner = thread::spawn(move || drone.run());
+
+    for i in 0..5 {
+        pool.submit_task(Task {
+            id: format!("t{}", i),
+            payload: vec![i as u8],
+            timestamp: 0,
+            metadata: HashMap::new(),
+            priority: None,
+        }).unwrap();
+    }
+    for _ in 0..100 {
+        pool.poll_events().unwrap();
+        if pool.get_worker_count() == 1 && pool.pending_task_count() == 0 && pool.in_flight_task_count() == 0 {
+            break;
+        }
+    }
+    assert_eq!(pool.get_worker_count(), 1);
+    assert_eq!(pool.pending_task_count(), 0);
+    assert_eq!(pool.in_flight_task_count(), 0);
+
+    handle.stop();
+    runner.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_native_client_reconnects_after_queen_restart() {
+    let port = portpicker::pick_unused_port().expect("无可用端口");
+    let config = PoolConfig::builder().transport(TransportKind::Native).build().unwrap();
+    let mut pool = DronePool::with_config("127.0.0.1", port, config.clone()).unwrap();
+
+    let mut drone = DroneNetwork::connect_with(TransportKind::Native, "native-2",
+        vec![format!("tcp://127.0.0.1:{}", port)]).unwrap();
+    drone.set_heartbeat_interval(Duration::ZERO);
+    drone.register("native-2", vec![]).unwrap();
+    let mut pump = |pool: &mut DronePool| {
+        for _ in 0..50 {
+            drone.send_heartbeat().unwrap();
+            pool.poll_events().unwrap();
+            drone.poll_message(20).unwrap();
+            if pool.get_worker_count() == 1 {
+                return true;
+            }
+        }
+        false
+    };
+    assert!(pump(&mut pool));
+
+    drop(pool);
+    let mut pool = DronePool::with_config("127.0.0.1", port, config).unwrap();
+    assert!(pump(&mut pool));
+}
@@ -0,0 +1,99 @@
+//! 节点预热测试
+
+use std::time::{Duration, Instant};
+use zerg_pool::balancer::{WarmupCurve, WarmupTracker, ZergRushSelector};
+use zerg_pool::config::PoolConfig;
+use zerg_pool::transport::TransportKind;
+use zerg_pool::DronePool;
+mod test_utils;
+
+#[test]
+fn test_linear_warmup_ramp() {
+    let mut tracker = WarmupTracker::new(Duration::from_secs(10), WarmupCurve::Linear);
+    let id = "node1".to_string();
+    let start = Instant::now();
+    tracker.begin_at(&id, start);
+
+    // 起点使用最小系数，避免完全无流量
+    assert_eq!(tracker.factor_at(&id, start), 0.1);
+    assert!((tracker.factor_at(&id, start + Duration::from_secs(5)) - 0.5).abs() < 1e-9);
+    assert_eq!(tracker.factor_at(&id, start + Duration::from_secs(20)), 1.0);
+
+    // 未预热的节点按满权重计算
+    assert_eq!(tracker.factor_at(&"other".to_string(), start), 1.0);
+}
+
+#[test]
+fn test_quadratic_warmup_ramp() {
+    let mut tracker = WarmupTracker::new(Duration::from_secs(10), WarmupCurve::Quadratic);
+    let id = "node1".to_string();
+    let start = Instant::now();
+    tracker.begin_at(&id, start);
+
+    assert!((tracker.factor_at(&id, start + Duration::from_secs(5)) - 0.25).abs() < 1e-9);
+    assert!((tracker.factor_at(&id, start + Duration::from_secs(8)) - 0.64).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_scale_out_starts_warmup() {
+    let mut selector = ZergRushSelector::new(0.8, Duration::from_secs(1), Duration::from_secs(60));
+    selector.scale_in(&mut vec![test_utils::new_test_process("backup1".into())]).await.unwrap();
+
+    let mut main_pool = vec![test_utils::new_test_process("node1".into())];
+    selector.scale_out(&mut main_pool).await.unwrap();
+
+    assert_eq!(main_pool.len(), 2);
+    assert!(selector.warmup().is_warming(&"backup1".to_string()));
+    assert!(selector.effective_weight(&main_pool[1]) < 0.2);
+    assert_eq!(selector.effective_weight(&main_pool[0]), 1.0);
+}
+
+#[test]
+fn test_selection_prefers_warm_nodes() {
+    let mut selector = ZergRushSelector::new(0.8, Duration::from_secs(1), Duration::from_secs(60));
+    let warm = test_utils::new_test_process("warm".into());
+    let cold = test_utils::new_test_process("cold".into());
+    selector.begin_warmup(&cold.id);
+
+    let nodes = vec![(&warm, 0.5), (&cold, 0.5)];
+    let cold_hits = (0..1000)
+        .filter(|_| selector.select(&nodes).unwrap().id == "cold")
+        .count();
+
+    // 冷节点权重约为0.1，命中率应远低于一半
+    assert!(cold_hits < 300, "cold node selected {} times", cold_hits);
+}
+
+#[test]
+fn test_warmup_applies_to_least_loaded_node() {
+    let mut selector = ZergRushSelector::new(0.8, Duration::from_secs(1), Duration::from_secs(60));
+    let busy = test_utils::new_test_process("busy".into());
+    let cold = test_utils::new_test_process("cold".into());
+    selector.begin_warmup(&cold.id);
+
+    // 负载最低的节点仍在预热，不应因负载排序而独占流量(与CPU核心数无关)
+    let nodes = vec![(&busy, 0.5), (&cold, 0.1)];
+    let cold_hits = (0..1000)
+        .filter(|_| selector.select(&nodes).unwrap().id == "cold")
+        .count();
+    assert!(cold_hits > 0 && cold_hits < 400, "cold node selected {} times", cold_hits);
+}
+
+#[test]
+fn test_pool_ramps_warming_worker_proportionally() {
+    let config = PoolConfig::builder()
+        .transport(TransportKind::Inproc)
+        .warmup_duration(Duration::ZERO)
+        .build()
+        .unwrap();
+    let mut pool = DronePool::bind("inproc://warmup_ramp", config.clone()).unwrap();
+    pool.register_drone(test_utils::new_test_process("warm".into())).unwrap();
+    pool.reload_config(PoolConfig { warmup_duration_ms: 60_000, ..config }).unwrap();
+    pool.register_drone(test_utils::new_test_process("cold".into())).unwrap();
+
+    // 冷节点有效权重约为热节点的0.1倍，按比例获得少量流量而不是完全没有
+    let cold_hits = (0..1000)
+        .filter(|_| pool.get_optimal_worker().as_deref() == Some("cold"))
+        .count();
+    assert!((20..250).contains(&cold_hits), "cold node selected {} times", cold_hits);
+}
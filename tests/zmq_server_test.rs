@@ -1,4 +1,6 @@
 use std::sync::{Arc, Mutex};
+use zerg_pool::config::PoolConfig;
+use zerg_pool::transport::TransportKind;
 use zerg_pool::{DronePool, ProcessMessage};
 use zerg_pool::proto::zergpool::Registration;
 use zmq::{Context, Socket};
@@ -8,10 +10,11 @@ use prost::Message;
 fn test_drone_pool_with_zmq() {
     println!("[TEST CLIENT] 初始化ZMQ上下文");
 
-    // 创建DronePool服务器
-    let pool = Arc::new(Mutex::new(
-        DronePool::new("127.0.0.1", 5555).expect("Failed to create DronePool")
-    ));
+    // 创建DronePool服务器(ZMQ传输，端口由系统分配)
+    let config = PoolConfig::builder().transport(TransportKind::Zmq).build().unwrap();
+    let pool = DronePool::bind("tcp://127.0.0.1:*", config).expect("Failed to create DronePool");
+    let endpoint = pool.local_endpoint().expect("读取绑定地址失败");
+    let pool = Arc::new(Mutex::new(pool));
     let pool_clone = Arc::clone(&pool);
 
     // 在后台线程运行事件轮询
@@ -29,7 +32,7 @@ fn test_drone_pool_with_zmq() {
     });
 
     // 使用DroneNetwork进行注册(与生产环境完全一致)
-    let mut drone_net = zerg_pool::drone::network::DroneNetwork::connect("127.0.0.1", 5555)
+    let mut drone_net = zerg_pool::drone::network::DroneNetwork::connect_with(TransportKind::Zmq, "test-drone-1", vec![endpoint])
         .expect("创建DroneNetwork失败");
     
     println!("[CLIENT] 使用DroneNetwork进行注册...");